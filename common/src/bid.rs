@@ -4,7 +4,7 @@
 use crate::eth2_types::*;
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 pub struct Bid {
     pub shard: Shard,
     pub slot: Slot,
@@ -1,24 +1,86 @@
 //! Configuration of the Eth2 system.
 use crate::eth2_types::{Epoch, Gwei, Root, Slot, H256};
+use std::collections::BTreeMap;
 
-pub const GENESIS_SLOT: Slot = 0;
-pub const GENESIS_EPOCH: Epoch = 0;
-pub const GENESIS_PARENT_ROOT: Root = H256::zero();
-pub const SLOTS_PER_EPOCH: u64 = 32;
-/// Note: For now, we assume the number of shard is static, so do not define `MAX_SHARDS`.
-pub const SHARD_NUM: u64 = 64;
-pub const SECONDS_PER_SLOT: u64 = 12;
-
-/// Ref: https://github.com/ethereum/eth2.0-specs/blob/074c09c018e77db8a8c88d9fa88f097fd646d5eb/specs/phase1/beacon-chain.md
-pub const POINTS_PER_SAMPLE: u64 = 8; // = 2 ** 3, 31 * 8 = 248 bytes
-pub const MAX_SAMPLES_PER_BLOCK: u64 = 2048; // = 2 ** 11, 248 * 2,048 = 507,904 bytes
-pub const TARGET_SAMPLES_PER_BLOCK: u64 = 1024; // = 2 ** 10, 248 * 1,024 = 253,952 bytes
-pub const MAX_POINTS_PER_BLOCK: u64 = POINTS_PER_SAMPLE * MAX_SAMPLES_PER_BLOCK;
-pub const MAX_SHARD_HEADERS_PER_SHARD: u64 = 4;
-pub const MAX_SHARD_HEADERS: u64 = SHARD_NUM * MAX_SHARD_HEADERS_PER_SHARD;
-pub const MAX_GASPRICE: Gwei = 8589934592; // = 2 * 33
-pub const MIN_GASPRICE: Gwei = 8; // = 2 * 3
-pub const GASPRICE_ADJUSTMENT_COEFFICIENT: u64 = 8;
-pub const GASPRICE_ADJUSTMENT_QUOTIENT: u64 =
-    SHARD_NUM as u64 * SLOTS_PER_EPOCH * GASPRICE_ADJUSTMENT_COEFFICIENT;
-pub const INIT_SHARD_GASPRICE: u64 = 0;
+/// A configuration value in the format of the Beacon Node API.
+trait SpecValue {
+    fn spec_value(&self) -> String;
+}
+
+impl SpecValue for u64 {
+    fn spec_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl SpecValue for H256 {
+    fn spec_value(&self) -> String {
+        format!("{:#x}", self)
+    }
+}
+
+/// Define the constants and `spec()` listing all of them, so that no constant is missing in `spec()`.
+macro_rules! spec_constants {
+    ($($(#[$attr:meta])* $name:ident: $ty:ty = $value:expr;)*) => {
+        $($(#[$attr])* pub const $name: $ty = $value;)*
+
+        /// The configuration values in the format of the Beacon Node API.
+        /// Ref: https://ethereum.github.io/eth2.0-APIs/#/Config/getSpec
+        pub fn spec() -> BTreeMap<&'static str, String> {
+            let mut spec = BTreeMap::new();
+            $(spec.insert(stringify!($name), $name.spec_value());)*
+            spec
+        }
+    };
+}
+
+spec_constants! {
+    GENESIS_SLOT: Slot = 0;
+    GENESIS_EPOCH: Epoch = 0;
+    GENESIS_PARENT_ROOT: Root = H256::zero();
+    SLOTS_PER_EPOCH: u64 = 32;
+    /// Note: For now, we assume the number of shard is static, so do not define `MAX_SHARDS`.
+    SHARD_NUM: u64 = 64;
+    SECONDS_PER_SLOT: u64 = 12;
+
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/074c09c018e77db8a8c88d9fa88f097fd646d5eb/specs/phase1/beacon-chain.md
+    POINTS_PER_SAMPLE: u64 = 8; // = 2 ** 3, 31 * 8 = 248 bytes
+    MAX_SAMPLES_PER_BLOCK: u64 = 2048; // = 2 ** 11, 248 * 2,048 = 507,904 bytes
+    TARGET_SAMPLES_PER_BLOCK: u64 = 1024; // = 2 ** 10, 248 * 1,024 = 253,952 bytes
+    MAX_POINTS_PER_BLOCK: u64 = POINTS_PER_SAMPLE * MAX_SAMPLES_PER_BLOCK;
+    MAX_SHARD_HEADERS_PER_SHARD: u64 = 4;
+    MAX_SHARD_HEADERS: u64 = SHARD_NUM * MAX_SHARD_HEADERS_PER_SHARD;
+    MAX_GASPRICE: Gwei = 8589934592; // = 2 * 33
+    MIN_GASPRICE: Gwei = 8; // = 2 * 3
+    GASPRICE_ADJUSTMENT_COEFFICIENT: u64 = 8;
+    GASPRICE_ADJUSTMENT_QUOTIENT: u64 =
+        SHARD_NUM as u64 * SLOTS_PER_EPOCH * GASPRICE_ADJUSTMENT_COEFFICIENT;
+    INIT_SHARD_GASPRICE: u64 = 0;
+
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#constants
+    FAR_FUTURE_EPOCH: Epoch = Epoch::MAX;
+    MAX_EFFECTIVE_BALANCE: Gwei = 32_000_000_000; // = 2 ** 5 * 10 ** 9
+    EJECTION_BALANCE: Gwei = 16_000_000_000; // = 2 ** 4 * 10 ** 9
+    MIN_DEPOSIT_AMOUNT: Gwei = 1_000_000_000; // = 2 ** 0 * 10 ** 9
+    EFFECTIVE_BALANCE_INCREMENT: Gwei = 1_000_000_000; // = 2 ** 0 * 10 ** 9
+    HYSTERESIS_QUOTIENT: u64 = 4;
+    HYSTERESIS_DOWNWARD_MULTIPLIER: u64 = 1;
+    HYSTERESIS_UPWARD_MULTIPLIER: u64 = 5;
+    MAX_SEED_LOOKAHEAD: u64 = 4;
+    MIN_PER_EPOCH_CHURN_LIMIT: u64 = 4;
+    CHURN_LIMIT_QUOTIENT: u64 = 65536;
+    MIN_VALIDATOR_WITHDRAWABILITY_DELAY: u64 = 256;
+    EPOCHS_PER_SLASHINGS_VECTOR: u64 = 8192;
+    MIN_SLASHING_PENALTY_QUOTIENT: u64 = 128;
+    MAX_VALIDATORS_PER_COMMITTEE: u64 = 2048;
+    MAX_PROPOSER_SLASHINGS: u64 = 16;
+    MAX_ATTESTER_SLASHINGS: u64 = 2;
+    MAX_DEPOSITS: u64 = 16;
+    MAX_VOLUNTARY_EXITS: u64 = 16;
+    JUSTIFICATION_BITS_LENGTH: u64 = 4;
+    MIN_EPOCHS_TO_INACTIVITY_PENALTY: u64 = 4;
+    INACTIVITY_PENALTY_QUOTIENT: u64 = 67108864; // = 2 ** 26
+    /// The number of the validators in the genesis state.
+    /// Note: This is much smaller than `MIN_GENESIS_ACTIVE_VALIDATOR_COUNT` (16384) to keep the simulation light.
+    GENESIS_VALIDATOR_COUNT: u64 = 128;
+}
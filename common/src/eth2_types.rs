@@ -1,7 +1,8 @@
 //! Custom types in the Eth2 system
 //! Ref: https://github.com/ethereum/eth2.0-specs/blob/849837a07d1e3dbf7c75d71b14034c10315f6341/specs/phase1/beacon-chain.md
 use crate::eth2_config::*;
-use crate::eth2_utils::{calculate_hash, root};
+use crate::eth2_utils::{calculate_hash, compute_epoch_at_slot, root};
+use crate::merkle::{merkle_root, BEACON_BLOCK_HEADER_FIELDS_DEPTH, BEACON_STATE_FIELDS_DEPTH};
 pub use ethereum_types::{H256, U256};
 use serde_derive::{Deserialize, Serialize};
 pub use ssz_types::{typenum, FixedVector, VariableList};
@@ -11,6 +12,7 @@ big_array! { BigArray; }
 
 const BLS_SIGNATURE_BYTE_LEN: usize = 96;
 const BLS_COMMITMENT_BYTE_LEN: usize = 48;
+const BLS_PUBKEY_BYTE_LEN: usize = 48;
 pub const BYTES_PER_POINT: usize = 31;
 
 /// u64.
@@ -23,12 +25,18 @@ pub type Shard = u64;
 pub type ShardId = Shard;
 /// u64.
 pub type Gwei = u64;
+/// u64.
+pub type ValidatorIndex = u64;
 /// H256.
 pub type Root = H256;
 /// [u8; 96].
 pub type BlsSignature = [u8; BLS_SIGNATURE_BYTE_LEN];
 /// [u8; 48].
 pub type BlsCommitment = [u8; BLS_COMMITMENT_BYTE_LEN];
+/// [u8; 48].
+pub type BlsPubkey = [u8; BLS_PUBKEY_BYTE_LEN];
+/// `VALIDATOR_REGISTRY_LIMIT` (= 2 ** 40).
+pub type ValidatorRegistryLimit = typenum::U1099511627776;
 /// U256.
 /// Call this `FieldElement` instead of `BLSPoint`.
 /// Ref: https://github.com/ethereum/eth2.0-specs/pull/2172#discussion_r550884186
@@ -66,6 +74,11 @@ impl Default for DataCommitment {
 }
 
 impl DataCommitment {
+    /// The number of points of data of `bytes_len` bytes, which is the length of its commitment.
+    pub fn length_of_bytes(bytes_len: usize) -> u64 {
+        ((bytes_len + BYTES_PER_POINT - 1) / BYTES_PER_POINT) as u64
+    }
+
     /// Generate a dummy commitment based on the data's hash.
     /// TODO: Use the real KZG commitment.
     pub fn dummy_from_bytes(bytes: &[u8]) -> Self {
@@ -83,8 +96,7 @@ impl DataCommitment {
 
         Self {
             point,
-            // Each point is 31 bytes.
-            length: (bytes.len() as f64 / BYTES_PER_POINT as f64).ceil() as u64,
+            length: Self::length_of_bytes(bytes.len()),
         }
     }
 }
@@ -151,14 +163,196 @@ impl PendingShardHeader {
     }
 }
 
+/// `withdrawal_credentials` field is omitted.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+pub struct Validator {
+    #[serde(with = "BigArray")]
+    pub pubkey: BlsPubkey,
+    pub effective_balance: Gwei,
+    pub slashed: bool,
+    pub activation_eligibility_epoch: Epoch,
+    pub activation_epoch: Epoch,
+    pub exit_epoch: Epoch,
+    pub withdrawable_epoch: Epoch,
+}
+
+impl Validator {
+    /// A new validator by a deposit, which is not eligible for activation yet.
+    pub fn from_deposit(deposit: &Deposit) -> Self {
+        Self {
+            pubkey: deposit.pubkey,
+            effective_balance: std::cmp::min(
+                deposit.amount - deposit.amount % EFFECTIVE_BALANCE_INCREMENT,
+                MAX_EFFECTIVE_BALANCE,
+            ),
+            slashed: false,
+            activation_eligibility_epoch: FAR_FUTURE_EPOCH,
+            activation_epoch: FAR_FUTURE_EPOCH,
+            exit_epoch: FAR_FUTURE_EPOCH,
+            withdrawable_epoch: FAR_FUTURE_EPOCH,
+        }
+    }
+
+    /// Generate a dummy public key based on the index's hash, so deterministic.
+    /// TODO: Use the real BLS public key.
+    pub fn dummy_pubkey(index: ValidatorIndex) -> BlsPubkey {
+        let mut hash: u64 = calculate_hash(&index);
+        let mut pubkey: BlsPubkey = [0; BLS_PUBKEY_BYTE_LEN];
+        for chunk in pubkey.chunks_mut(8) {
+            hash = calculate_hash(&hash);
+            chunk.copy_from_slice(&u64::to_le_bytes(hash));
+        }
+        pubkey
+    }
+
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#is_active_validator
+    pub fn is_active(&self, epoch: Epoch) -> bool {
+        self.activation_epoch <= epoch && epoch < self.exit_epoch
+    }
+
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#is_slashable_validator
+    pub fn is_slashable(&self, epoch: Epoch) -> bool {
+        !self.slashed && self.activation_epoch <= epoch && epoch < self.withdrawable_epoch
+    }
+
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#is_eligible_for_activation_queue
+    pub fn is_eligible_for_activation_queue(&self) -> bool {
+        self.activation_eligibility_epoch == FAR_FUTURE_EPOCH
+            && self.effective_balance == MAX_EFFECTIVE_BALANCE
+    }
+
+    /// The status in the format of the Beacon Node API.
+    /// Ref: https://ethereum.github.io/eth2.0-APIs/#/Beacon/getStateValidators
+    pub fn status(&self, epoch: Epoch) -> ValidatorStatus {
+        if epoch < self.activation_epoch {
+            if self.activation_eligibility_epoch == FAR_FUTURE_EPOCH {
+                ValidatorStatus::PendingInitialized
+            } else {
+                ValidatorStatus::PendingQueued
+            }
+        } else if epoch < self.exit_epoch {
+            if self.exit_epoch == FAR_FUTURE_EPOCH {
+                ValidatorStatus::ActiveOngoing
+            } else if self.slashed {
+                ValidatorStatus::ActiveSlashed
+            } else {
+                ValidatorStatus::ActiveExiting
+            }
+        } else if epoch < self.withdrawable_epoch {
+            if self.slashed {
+                ValidatorStatus::ExitedSlashed
+            } else {
+                ValidatorStatus::ExitedUnslashed
+            }
+        } else {
+            ValidatorStatus::WithdrawalPossible
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorStatus {
+    PendingInitialized,
+    PendingQueued,
+    ActiveOngoing,
+    ActiveExiting,
+    ActiveSlashed,
+    ExitedUnslashed,
+    ExitedSlashed,
+    WithdrawalPossible,
+}
+
+/// `proof` and `withdrawal_credentials` fields are omitted.
+/// Assumption: Deposits are made in the deposit contract without the Eth1 voting.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+pub struct Deposit {
+    #[serde(with = "BigArray")]
+    pub pubkey: BlsPubkey,
+    pub amount: Gwei,
+}
+
+/// `signature` field is omitted, i.e., `VoluntaryExit` is used instead of `SignedVoluntaryExit`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+pub struct VoluntaryExit {
+    /// The earliest epoch when the exit can be processed.
+    pub epoch: Epoch,
+    pub validator_index: ValidatorIndex,
+}
+
+/// `signature` fields are omitted, i.e., `BeaconBlockHeader` is used instead of `SignedBeaconBlockHeader`.
+/// `proposer_index` is moved out of the headers, which do not have the field in the simulation.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+pub struct ProposerSlashing {
+    pub proposer_index: ValidatorIndex,
+    pub header_1: BeaconBlockHeader,
+    pub header_2: BeaconBlockHeader,
+}
+
+/// `index` (the committee index) field is omitted.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+pub struct AttestationData {
+    pub slot: Slot,
+    pub beacon_block_root: Root,
+    pub source: Checkpoint,
+    pub target: Checkpoint,
+}
+
+impl AttestationData {
+    /// Whether or not the two attestations are a double vote or a surround vote.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#is_slashable_attestation_data
+    pub fn is_slashable_with(&self, other: &AttestationData) -> bool {
+        (self != other && self.target.epoch == other.target.epoch)
+            || (self.source.epoch < other.source.epoch && other.target.epoch < self.target.epoch)
+    }
+}
+
+/// `signature` field is omitted.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+pub struct IndexedAttestation {
+    /// The max length is MAX_VALIDATORS_PER_COMMITTEE.
+    pub attesting_indices: Vec<ValidatorIndex>,
+    pub data: AttestationData,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+pub struct AttesterSlashing {
+    pub attestation_1: IndexedAttestation,
+    pub attestation_2: IndexedAttestation,
+}
+
+impl AttesterSlashing {
+    /// The sorted indices of the validators attesting to both attestations.
+    pub fn get_intersecting_indices(&self) -> Vec<ValidatorIndex> {
+        let mut indices: Vec<ValidatorIndex> = self
+            .attestation_1
+            .attesting_indices
+            .iter()
+            .filter(|index| self.attestation_2.attesting_indices.contains(index))
+            .copied()
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+}
+
 /// Only necessary fields are defined.
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BeaconBlock {
     pub slot: Slot,
     pub parent_root: Root,
     pub state_root: Root,
     /// The length is MAX_SHARD_HEADERS (= SHARD_NUM * MAX_SHARD_HEADERS_PER_SHARD).
     pub shard_headers: VariableList<SignedShardHeader, typenum::U256>,
+    /// The length is MAX_PROPOSER_SLASHINGS.
+    pub proposer_slashings: VariableList<ProposerSlashing, typenum::U16>,
+    /// The length is MAX_ATTESTER_SLASHINGS.
+    pub attester_slashings: VariableList<AttesterSlashing, typenum::U2>,
+    /// The length is MAX_DEPOSITS.
+    pub deposits: VariableList<Deposit, typenum::U16>,
+    /// The length is MAX_VOLUNTARY_EXITS.
+    pub voluntary_exits: VariableList<VoluntaryExit, typenum::U16>,
 }
 
 /// Implement `Hash` manually to handle `VariableList`.
@@ -167,24 +361,40 @@ impl Hash for BeaconBlock {
         self.slot.hash(state);
         self.parent_root.hash(state);
         self.state_root.hash(state);
-        let headers: Vec<SignedShardHeader> = VariableList::into(self.shard_headers.clone());
-        headers.hash(state);
+        self.body_root().hash(state);
     }
 }
 
 impl BeaconBlock {
     pub fn header(&self) -> BeaconBlockHeader {
-        let headers: &Vec<SignedShardHeader> = &VariableList::into(self.shard_headers.clone());
         BeaconBlockHeader {
             slot: self.slot,
             parent_root: self.parent_root,
             state_root: self.state_root,
-            body_root: root(headers),
+            body_root: self.body_root(),
         }
     }
+
+    /// The dummy root of the body. Note: It is not the SSZ root since the body is simplified.
+    fn body_root(&self) -> Root {
+        let headers: Vec<SignedShardHeader> = VariableList::into(self.shard_headers.clone());
+        let proposer_slashings: Vec<ProposerSlashing> =
+            VariableList::into(self.proposer_slashings.clone());
+        let attester_slashings: Vec<AttesterSlashing> =
+            VariableList::into(self.attester_slashings.clone());
+        let deposits: Vec<Deposit> = VariableList::into(self.deposits.clone());
+        let voluntary_exits: Vec<VoluntaryExit> = VariableList::into(self.voluntary_exits.clone());
+        root(&(
+            headers,
+            proposer_slashings,
+            attester_slashings,
+            deposits,
+            voluntary_exits,
+        ))
+    }
 }
 
-#[derive(Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
 pub struct BeaconBlockHeader {
     pub slot: Slot,
     pub parent_root: Root,
@@ -193,8 +403,9 @@ pub struct BeaconBlockHeader {
 }
 
 impl BeaconBlockHeader {
+    /// The SSZ `hash_tree_root`, which is the Merkle root of the fields.
     pub fn root(&self) -> Root {
-        root(&self)
+        merkle_root(&self.field_roots(), BEACON_BLOCK_HEADER_FIELDS_DEPTH)
     }
 }
 
@@ -210,6 +421,13 @@ pub struct BeaconState {
     pub grandparent_epoch_confirmed_commitments:
         FixedVector<FixedVector<DataCommitment, typenum::U32>, typenum::U64>,
     pub shard_gasprice: Gwei,
+    pub validators: VariableList<Validator, ValidatorRegistryLimit>,
+    pub balances: VariableList<Gwei, ValidatorRegistryLimit>,
+    /// `Bitvector[JUSTIFICATION_BITS_LENGTH]`
+    /// The bit at the index `i` is whether or not the checkpoint of the `i`-th previous epoch is justified.
+    pub justification_bits: [bool; JUSTIFICATION_BITS_LENGTH as usize],
+    pub previous_justified_checkpoint: Checkpoint,
+    pub current_justified_checkpoint: Checkpoint,
 }
 
 /// Implement `Hash` manually to handle `VariableList`.
@@ -224,8 +442,9 @@ impl Hash for BeaconState {
 }
 
 impl BeaconState {
+    /// The SSZ `hash_tree_root`, which is the Merkle root of the fields.
     pub fn root(&self) -> Root {
-        root(&self)
+        merkle_root(&self.field_roots(), BEACON_STATE_FIELDS_DEPTH)
     }
 
     pub fn genesis_state() -> Self {
@@ -236,9 +455,145 @@ impl BeaconState {
             current_epoch_pending_shard_headers: VariableList::from(Vec::new()),
             grandparent_epoch_confirmed_commitments: Self::default_confirmed_commitments(),
             shard_gasprice: INIT_SHARD_GASPRICE,
+            validators: VariableList::from(
+                (0..GENESIS_VALIDATOR_COUNT)
+                    .map(|index| Validator {
+                        pubkey: Validator::dummy_pubkey(index),
+                        effective_balance: MAX_EFFECTIVE_BALANCE,
+                        slashed: false,
+                        activation_eligibility_epoch: GENESIS_EPOCH,
+                        activation_epoch: GENESIS_EPOCH,
+                        exit_epoch: FAR_FUTURE_EPOCH,
+                        withdrawable_epoch: FAR_FUTURE_EPOCH,
+                    })
+                    .collect::<Vec<Validator>>(),
+            ),
+            balances: VariableList::from(vec![
+                MAX_EFFECTIVE_BALANCE;
+                GENESIS_VALIDATOR_COUNT as usize
+            ]),
+            justification_bits: [false; JUSTIFICATION_BITS_LENGTH as usize],
+            previous_justified_checkpoint: Checkpoint::genesis_finalized_checkpoint(),
+            current_justified_checkpoint: Checkpoint::genesis_finalized_checkpoint(),
+        }
+    }
+
+    /// The epoch of the state's slot.
+    pub fn get_current_epoch(&self) -> Epoch {
+        compute_epoch_at_slot(self.slot)
+    }
+
+    /// The previous epoch, which is the genesis epoch at the genesis epoch.
+    pub fn get_previous_epoch(&self) -> Epoch {
+        self.get_current_epoch().saturating_sub(1)
+    }
+
+    /// The number of the epochs from the finalized checkpoint to the previous epoch.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#helpers
+    pub fn get_finality_delay(&self) -> Epoch {
+        self.get_previous_epoch()
+            .saturating_sub(self.finalized_checkpoint.epoch)
+    }
+
+    /// Whether or not the chain is in the inactivity leak, where the validators not attesting are penalized.
+    pub fn is_in_inactivity_leak(&self) -> bool {
+        self.get_finality_delay() > MIN_EPOCHS_TO_INACTIVITY_PENALTY
+    }
+
+    /// The indices of the active validators at the epoch.
+    pub fn get_active_validator_indices(&self, epoch: Epoch) -> Vec<ValidatorIndex> {
+        self.validators
+            .iter()
+            .enumerate()
+            .filter(|(_, validator)| validator.is_active(epoch))
+            .map(|(index, _)| index as ValidatorIndex)
+            .collect()
+    }
+
+    /// The sum of the effective balances of the validators, which is at least `EFFECTIVE_BALANCE_INCREMENT`.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#get_total_balance
+    pub fn get_total_balance(&self, indices: &[ValidatorIndex]) -> Gwei {
+        std::cmp::max(
+            EFFECTIVE_BALANCE_INCREMENT,
+            indices
+                .iter()
+                .map(|index| self.validators[*index as usize].effective_balance)
+                .sum(),
+        )
+    }
+
+    /// Justify the checkpoints with the attestations and finalize the checkpoints by the four rules of Casper FFG.
+    /// `checkpoints` are the checkpoints of each epoch in the chain, and a checkpoint not defined yet is not justified.
+    /// `previous_epoch_target_balance` and `current_epoch_target_balance` are the balances of the validators
+    /// attesting to the checkpoints of the previous and current epochs respectively.
+    /// Note: Unlike the spec, this is processed from the genesis epoch so that the checkpoint of the epoch 0 can be finalized.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#justification-and-finalization
+    pub fn weigh_justification_and_finalization(
+        &mut self,
+        current_epoch: Epoch,
+        checkpoints: &[Checkpoint],
+        previous_epoch_target_balance: Gwei,
+        current_epoch_target_balance: Gwei,
+    ) {
+        let previous_epoch = current_epoch.saturating_sub(1);
+        let total_balance =
+            self.get_total_balance(&self.get_active_validator_indices(current_epoch));
+        let old_previous_justified_checkpoint = self.previous_justified_checkpoint.clone();
+        let old_current_justified_checkpoint = self.current_justified_checkpoint.clone();
+
+        // Process justifications
+        self.previous_justified_checkpoint = self.current_justified_checkpoint.clone();
+        self.justification_bits.rotate_right(1);
+        self.justification_bits[0] = false;
+        if previous_epoch_target_balance * 3 >= total_balance * 2 {
+            if let Some(checkpoint) = checkpoints.get(previous_epoch as usize) {
+                self.current_justified_checkpoint = checkpoint.clone();
+                self.justification_bits[1] = true;
+            }
+        }
+        if current_epoch_target_balance * 3 >= total_balance * 2 {
+            if let Some(checkpoint) = checkpoints.get(current_epoch as usize) {
+                self.current_justified_checkpoint = checkpoint.clone();
+                self.justification_bits[0] = true;
+            }
+        }
+
+        // Process finalizations
+        let bits = self.justification_bits;
+        // The 2nd/3rd/4th most recent epochs are justified, the 2nd using the 4th as source.
+        if bits[1..4].iter().all(|bit| *bit)
+            && old_previous_justified_checkpoint.epoch + 3 == current_epoch
+        {
+            self.finalized_checkpoint = old_previous_justified_checkpoint.clone();
+        }
+        // The 2nd/3rd most recent epochs are justified, the 2nd using the 3rd as source.
+        if bits[1..3].iter().all(|bit| *bit)
+            && old_previous_justified_checkpoint.epoch + 2 == current_epoch
+        {
+            self.finalized_checkpoint = old_previous_justified_checkpoint;
+        }
+        // The 1st/2nd/3rd most recent epochs are justified, the 1st using the 3rd as source.
+        if bits[0..3].iter().all(|bit| *bit)
+            && old_current_justified_checkpoint.epoch + 2 == current_epoch
+        {
+            self.finalized_checkpoint = old_current_justified_checkpoint.clone();
+        }
+        // The 1st/2nd most recent epochs are justified, the 1st using the 2nd as source.
+        if bits[0..2].iter().all(|bit| *bit)
+            && old_current_justified_checkpoint.epoch + 1 == current_epoch
+        {
+            self.finalized_checkpoint = old_current_justified_checkpoint;
         }
     }
 
+    /// The index of the validator with the public key.
+    pub fn get_validator_index(&self, pubkey: &BlsPubkey) -> Option<ValidatorIndex> {
+        self.validators
+            .iter()
+            .position(|validator| validator.pubkey == *pubkey)
+            .map(|index| index as ValidatorIndex)
+    }
+
     pub fn default_confirmed_commitments(
     ) -> FixedVector<FixedVector<DataCommitment, typenum::U32>, typenum::U64> {
         FixedVector::from(
@@ -266,6 +621,19 @@ pub struct ShardBlob {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::eth2_utils::compute_start_slot_at_epoch;
+
+    #[test]
+    fn commitment_length_of_bytes() {
+        assert_eq!(DataCommitment::length_of_bytes(0), 0);
+        assert_eq!(DataCommitment::length_of_bytes(1), 1);
+        assert_eq!(DataCommitment::length_of_bytes(BYTES_PER_POINT), 1);
+        assert_eq!(DataCommitment::length_of_bytes(BYTES_PER_POINT + 1), 2);
+        assert_eq!(
+            DataCommitment::dummy_from_bytes(&[0; BYTES_PER_POINT * 3]).length,
+            3
+        );
+    }
 
     #[test]
     fn calc_root() {
@@ -296,12 +664,17 @@ mod tests {
             ),
             grandparent_epoch_confirmed_commitments: BeaconState::default_confirmed_commitments(),
             shard_gasprice: 0,
+            ..BeaconState::genesis_state()
         };
         let block1 = BeaconBlock {
             slot: 0,
             parent_root: H256::zero(),
             state_root: state1.root(),
             shard_headers: VariableList::from(signed_headers[..SHARD_NUM as usize].to_vec()),
+            proposer_slashings: VariableList::from(Vec::new()),
+            attester_slashings: VariableList::from(Vec::new()),
+            deposits: VariableList::from(Vec::new()),
+            voluntary_exits: VariableList::from(Vec::new()),
         };
         let state2 = BeaconState {
             slot: 1,
@@ -317,12 +690,17 @@ mod tests {
             ),
             grandparent_epoch_confirmed_commitments: BeaconState::default_confirmed_commitments(),
             shard_gasprice: 0,
+            ..BeaconState::genesis_state()
         };
         let block2 = BeaconBlock {
             slot: 1,
             parent_root: block1.header().root(),
             state_root: state2.root(),
             shard_headers: VariableList::from(signed_headers[SHARD_NUM as usize..].to_vec()),
+            proposer_slashings: VariableList::from(Vec::new()),
+            attester_slashings: VariableList::from(Vec::new()),
+            deposits: VariableList::from(Vec::new()),
+            voluntary_exits: VariableList::from(Vec::new()),
         };
         println!("block1: {}", block1.header().root());
         println!("block2: {}", block2.header().root());
@@ -346,12 +724,17 @@ mod tests {
             ),
             grandparent_epoch_confirmed_commitments: BeaconState::default_confirmed_commitments(),
             shard_gasprice: 0,
+            ..BeaconState::genesis_state()
         };
         let another_block2 = BeaconBlock {
             slot: 1,
             parent_root: block1.header().root(),
             state_root: state2.root(),
             shard_headers: VariableList::from(signed_headers[SHARD_NUM as usize..].to_vec()),
+            proposer_slashings: VariableList::from(Vec::new()),
+            attester_slashings: VariableList::from(Vec::new()),
+            deposits: VariableList::from(Vec::new()),
+            voluntary_exits: VariableList::from(Vec::new()),
         };
         assert_eq!(state2.root(), another_state2.root());
         assert_eq!(block2.header().root(), another_block2.header().root());
@@ -402,4 +785,79 @@ mod tests {
         // Dummy signature is deterministic.
         assert_eq!(signed_header1, signed_header2);
     }
+
+    #[test]
+    fn slashable_attestation_data() {
+        let checkpoint = |epoch: Epoch| Checkpoint {
+            epoch,
+            root: H256::repeat_byte(epoch as u8 + 1),
+        };
+        let data = AttestationData {
+            slot: compute_start_slot_at_epoch(2),
+            beacon_block_root: checkpoint(2).root,
+            source: checkpoint(1),
+            target: checkpoint(2),
+        };
+        assert!(!data.is_slashable_with(&data));
+        // Double vote.
+        let double_vote = AttestationData {
+            beacon_block_root: H256::zero(),
+            ..data.clone()
+        };
+        assert!(data.is_slashable_with(&double_vote));
+        // Surround vote.
+        let surrounding_vote = AttestationData {
+            slot: compute_start_slot_at_epoch(3),
+            beacon_block_root: checkpoint(3).root,
+            source: checkpoint(0),
+            target: checkpoint(3),
+        };
+        assert!(surrounding_vote.is_slashable_with(&data));
+        assert!(!data.is_slashable_with(&surrounding_vote));
+    }
+
+    #[test]
+    fn weigh_justification_and_finalization() {
+        let mut state = BeaconState::genesis_state();
+        let checkpoints: Vec<Checkpoint> = (0..4)
+            .map(|epoch| Checkpoint {
+                epoch,
+                root: H256::repeat_byte(epoch as u8 + 1),
+            })
+            .collect();
+        let total_balance = MAX_EFFECTIVE_BALANCE * GENESIS_VALIDATOR_COUNT;
+        // The minimum balance of 2/3 of the stake.
+        let supermajority = (total_balance * 2 + 2) / 3;
+
+        state.weigh_justification_and_finalization(0, &checkpoints, 0, total_balance);
+        assert_eq!(checkpoints[0], state.current_justified_checkpoint);
+        assert_eq!(
+            Checkpoint::genesis_finalized_checkpoint(),
+            state.finalized_checkpoint
+        );
+
+        // The 1st/2nd most recent epochs are justified, the 1st using the 2nd as source.
+        state.weigh_justification_and_finalization(1, &checkpoints, total_balance, total_balance);
+        assert_eq!([true, true, false, false], state.justification_bits);
+        assert_eq!(checkpoints[1], state.current_justified_checkpoint);
+        assert_eq!(checkpoints[0], state.finalized_checkpoint);
+
+        // Less than 2/3 of the stake attests to the current epoch.
+        state.weigh_justification_and_finalization(
+            2,
+            &checkpoints,
+            total_balance,
+            supermajority - 1,
+        );
+        assert_eq!([false, true, true, false], state.justification_bits);
+        assert_eq!(checkpoints[1], state.current_justified_checkpoint);
+        assert_eq!(checkpoints[0], state.finalized_checkpoint);
+
+        // The 2nd/3rd most recent epochs are justified, the 2nd using the 3rd as source.
+        state.weigh_justification_and_finalization(3, &checkpoints, supermajority, 0);
+        assert_eq!([false, true, true, true], state.justification_bits);
+        assert_eq!(checkpoints[1], state.previous_justified_checkpoint);
+        assert_eq!(checkpoints[2], state.current_justified_checkpoint);
+        assert_eq!(checkpoints[1], state.finalized_checkpoint);
+    }
 }
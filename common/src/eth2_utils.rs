@@ -1,6 +1,6 @@
 //! Utility functions in the Eth2 system
 use crate::eth2_config::{
-    GASPRICE_ADJUSTMENT_QUOTIENT, MAX_GASPRICE, MIN_GASPRICE, SLOTS_PER_EPOCH,
+    GASPRICE_ADJUSTMENT_QUOTIENT, MAX_GASPRICE, MAX_SEED_LOOKAHEAD, MIN_GASPRICE, SLOTS_PER_EPOCH,
     TARGET_SAMPLES_PER_BLOCK,
 };
 use crate::eth2_types::*;
@@ -18,6 +18,11 @@ pub fn compute_start_slot_at_epoch(epoch: Epoch) -> Slot {
     epoch * SLOTS_PER_EPOCH as Epoch
 }
 
+/// Compute the epoch when the activation or the exit initiated at `epoch` takes effect.
+pub fn compute_activation_exit_epoch(epoch: Epoch) -> Epoch {
+    epoch + 1 + MAX_SEED_LOOKAHEAD
+}
+
 /// Compute the updated gasprice.
 pub fn compute_updated_gasprice(prev_gasprice: Gwei, shard_block_length: u64) -> Gwei {
     if shard_block_length > TARGET_SAMPLES_PER_BLOCK {
@@ -0,0 +1,100 @@
+//! Wire types of the responses of the standard Beacon Node API, shared by the server and the client.
+//! Numbers are encoded as decimal strings as in the standard API.
+//! Ref: https://ethereum.github.io/eth2.0-APIs/
+use crate::eth2_types::*;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Data<T> {
+    pub data: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Genesis {
+    pub genesis_time: String,
+    pub genesis_validators_root: Root,
+    pub genesis_fork_version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BeaconBlockHeaderMessage {
+    pub slot: String,
+    pub proposer_index: String,
+    pub parent_root: Root,
+    pub state_root: Root,
+    pub body_root: Root,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedBeaconBlockHeaderMessage {
+    pub message: BeaconBlockHeaderMessage,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockHeaderResponse {
+    pub root: Root,
+    pub canonical: bool,
+    pub header: SignedBeaconBlockHeaderMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BeaconBlockBodyMessage {
+    pub shard_headers: Vec<SignedShardHeader>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BeaconBlockMessage {
+    pub slot: String,
+    pub proposer_index: String,
+    pub parent_root: Root,
+    pub state_root: Root,
+    pub body: BeaconBlockBodyMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedBeaconBlockMessage {
+    pub message: BeaconBlockMessage,
+    pub signature: String,
+}
+
+/// The response of `GET /eth/v2/beacon/blocks/{block_id}`, whose `version` is `sharding`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockResponse {
+    pub version: String,
+    pub data: SignedBeaconBlockMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointMessage {
+    pub epoch: String,
+    pub root: Root,
+}
+
+impl From<&Checkpoint> for CheckpointMessage {
+    fn from(checkpoint: &Checkpoint) -> Self {
+        Self {
+            epoch: checkpoint.epoch.to_string(),
+            root: checkpoint.root,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FinalityCheckpoints {
+    pub previous_justified: CheckpointMessage,
+    pub current_justified: CheckpointMessage,
+    pub finalized: CheckpointMessage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Version {
+    pub version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Syncing {
+    pub head_slot: String,
+    pub sync_distance: String,
+    pub is_syncing: bool,
+}
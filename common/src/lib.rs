@@ -4,6 +4,9 @@
 pub mod eth2_types;
 pub mod eth2_config;
 pub mod eth2_utils;
+pub mod merkle;
 pub mod bid;
+pub mod pagination;
+pub mod eth_api;
 #[macro_use]
 extern crate serde_big_array;
@@ -0,0 +1,512 @@
+//! Merkle trees and inclusion proofs.
+//! Trees are built in the same way as the SSZ merkleization, i.e., leaves are padded with zero
+//! to a power of two, the length of a list is mixed in, and the nodes are hashed with SHA-256.
+//! Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/ssz/simple-serialize.md#merkleization
+use crate::eth2_config::{JUSTIFICATION_BITS_LENGTH, SLOTS_PER_EPOCH};
+use crate::eth2_types::*;
+use serde_derive::{Deserialize, Serialize};
+
+/// SHA-256 hash of a pair of nodes.
+pub fn hash_pair(left: &Root, right: &Root) -> Root {
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(left.as_bytes());
+    bytes[32..].copy_from_slice(right.as_bytes());
+    Root::from_slice(&hashing::hash(&bytes))
+}
+
+/// The roots of the trees of each depth (from 0 to `depth`) whose leaves are all zero.
+fn zero_hashes(depth: usize) -> Vec<Root> {
+    let mut zero_hashes = vec![Root::zero()];
+    for i in 0..depth {
+        zero_hashes.push(hash_pair(&zero_hashes[i], &zero_hashes[i]));
+    }
+    zero_hashes
+}
+
+/// Hash the nodes in pairs. The nodes are padded with the zero hash if the number is odd.
+fn hash_layer(mut nodes: Vec<Root>, zero_hash: Root) -> Vec<Root> {
+    if nodes.len() % 2 == 1 {
+        nodes.push(zero_hash);
+    }
+    nodes
+        .chunks(2)
+        .map(|pair| hash_pair(&pair[0], &pair[1]))
+        .collect()
+}
+
+/// Compute the Merkle root of the leaves padded with zero to `2 ** depth` leaves.
+pub fn merkle_root(leaves: &[Root], depth: usize) -> Root {
+    assert!(leaves.len() <= 1 << depth);
+    let zero_hashes = zero_hashes(depth);
+    let mut nodes = leaves.to_vec();
+    for zero_hash in zero_hashes.iter().take(depth) {
+        nodes = hash_layer(nodes, *zero_hash);
+    }
+    nodes.first().copied().unwrap_or(zero_hashes[depth])
+}
+
+/// Compute the Merkle branch of the leaf at the index.
+/// The leaves are padded with zero to `2 ** depth` leaves.
+pub fn merkle_branch(leaves: &[Root], depth: usize, index: usize) -> Vec<Root> {
+    assert!(leaves.len() <= 1 << depth);
+    let zero_hashes = zero_hashes(depth);
+    let mut nodes = leaves.to_vec();
+    let mut index = index;
+    let mut branch = Vec::with_capacity(depth);
+    for zero_hash in zero_hashes.iter().take(depth) {
+        branch.push(nodes.get(index ^ 1).copied().unwrap_or(*zero_hash));
+        nodes = hash_layer(nodes, *zero_hash);
+        index /= 2;
+    }
+    branch
+}
+
+/// Check if the leaf at the index is included in the tree of the root.
+/// The depth of the tree is the length of the branch.
+/// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#is_valid_merkle_branch
+pub fn verify_merkle_branch(leaf: &Root, branch: &[Root], index: u64, root: &Root) -> bool {
+    let mut value = *leaf;
+    for (i, node) in branch.iter().enumerate() {
+        if (index >> i) & 1 == 1 {
+            value = hash_pair(node, &value);
+        } else {
+            value = hash_pair(&value, node);
+        }
+    }
+    value == *root
+}
+
+/// The leaf of the length of a list, which is mixed in the root of the list.
+pub fn length_leaf(length: usize) -> Root {
+    (length as u64).hash_tree_root()
+}
+
+/// Mix the length in the root of a list.
+pub fn mix_in_length(root: &Root, length: usize) -> Root {
+    hash_pair(root, &length_leaf(length))
+}
+
+/// Split the bytes into the chunks of 32 bytes. The last chunk is padded with zero.
+fn pack_bytes(bytes: &[u8]) -> Vec<Root> {
+    bytes
+        .chunks(32)
+        .map(|chunk| {
+            let mut leaf = [0u8; 32];
+            leaf[..chunk.len()].copy_from_slice(chunk);
+            Root::from(leaf)
+        })
+        .collect()
+}
+
+/// Pack the `uint64` values into the chunks of 32 bytes, 4 values per chunk.
+fn pack_u64s(values: &[u64]) -> Vec<Root> {
+    let mut bytes = Vec::with_capacity(values.len() * 8);
+    for value in values {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    pack_bytes(&bytes)
+}
+
+/// The depth of the tree of `leaf_num` leaves, which are padded to a power of two.
+fn depth_of(leaf_num: usize) -> usize {
+    leaf_num.next_power_of_two().trailing_zeros() as usize
+}
+
+/// The root of a container, whose leaves are the roots of its fields.
+fn container_root(field_roots: &[Root]) -> Root {
+    merkle_root(field_roots, depth_of(field_roots.len()))
+}
+
+/// SSZ `hash_tree_root` of the types in the beacon state.
+/// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/ssz/simple-serialize.md#merkleization
+pub trait HashTreeRoot {
+    fn hash_tree_root(&self) -> Root;
+}
+
+impl HashTreeRoot for u64 {
+    fn hash_tree_root(&self) -> Root {
+        pack_u64s(&[*self])[0]
+    }
+}
+
+impl HashTreeRoot for bool {
+    fn hash_tree_root(&self) -> Root {
+        pack_bytes(&[*self as u8])[0]
+    }
+}
+
+impl HashTreeRoot for Root {
+    fn hash_tree_root(&self) -> Root {
+        *self
+    }
+}
+
+/// `Bitvector[JUSTIFICATION_BITS_LENGTH]`, which fits in a chunk.
+impl HashTreeRoot for [bool; JUSTIFICATION_BITS_LENGTH as usize] {
+    fn hash_tree_root(&self) -> Root {
+        let mut bytes = vec![0u8; (self.len() + 7) / 8];
+        for (i, bit) in self.iter().enumerate() {
+            bytes[i / 8] |= (*bit as u8) << (i % 8);
+        }
+        pack_bytes(&bytes)[0]
+    }
+}
+
+impl HashTreeRoot for Checkpoint {
+    fn hash_tree_root(&self) -> Root {
+        container_root(&[self.epoch.hash_tree_root(), self.root])
+    }
+}
+
+impl HashTreeRoot for DataCommitment {
+    fn hash_tree_root(&self) -> Root {
+        container_root(&[
+            merkle_root(&pack_bytes(&self.point), 1),
+            self.length.hash_tree_root(),
+        ])
+    }
+}
+
+impl HashTreeRoot for PendingShardHeader {
+    fn hash_tree_root(&self) -> Root {
+        container_root(&[
+            self.slot.hash_tree_root(),
+            self.shard.hash_tree_root(),
+            self.commitment.hash_tree_root(),
+            self.root,
+            self.confirmed.hash_tree_root(),
+        ])
+    }
+}
+
+impl HashTreeRoot for Validator {
+    fn hash_tree_root(&self) -> Root {
+        container_root(&[
+            merkle_root(&pack_bytes(&self.pubkey), 1),
+            self.effective_balance.hash_tree_root(),
+            self.slashed.hash_tree_root(),
+            self.activation_eligibility_epoch.hash_tree_root(),
+            self.activation_epoch.hash_tree_root(),
+            self.exit_epoch.hash_tree_root(),
+            self.withdrawable_epoch.hash_tree_root(),
+        ])
+    }
+}
+
+/// Proof of the leaf at the index in a tree.
+/// The depth of the tree is the length of the branch.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct MerkleProof {
+    pub leaf: Root,
+    pub branch: Vec<Root>,
+    pub index: u64,
+}
+
+impl MerkleProof {
+    /// Create the proof of the leaf at the index.
+    pub fn new(leaves: &[Root], depth: usize, index: usize) -> Self {
+        Self {
+            leaf: leaves[index],
+            branch: merkle_branch(leaves, depth, index),
+            index: index as u64,
+        }
+    }
+
+    /// Check if the leaf is included in the tree of the root.
+    pub fn verify(&self, root: &Root) -> bool {
+        verify_merkle_branch(&self.leaf, &self.branch, self.index, root)
+    }
+
+    /// Extend the proof to the tree containing the root of this proof as a leaf.
+    /// `parent` is the proof of the root of this proof in the tree.
+    pub fn extend(mut self, parent: MerkleProof) -> Self {
+        self.index |= parent.index << self.branch.len();
+        self.branch.extend(parent.branch);
+        self
+    }
+}
+
+/// Proof that a data commitment is confirmed for the shard at the slot.
+/// The commitment is proved to be in the beacon state, and the state root to be in the block.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct CommitmentProof {
+    pub shard: Shard,
+    pub slot: Slot,
+    pub commitment: DataCommitment,
+    /// The pending shard header if the commitment is proved to be in
+    /// `previous_epoch_pending_shard_headers` or `current_epoch_pending_shard_headers`.
+    /// `None` if the commitment is proved to be in `grandparent_epoch_confirmed_commitments`.
+    pub pending_header: Option<PendingShardHeader>,
+    /// Proof of the commitment (or the pending shard header) in the beacon state.
+    pub state_proof: MerkleProof,
+    pub state_root: Root,
+    /// Proof of the state root in the beacon block header.
+    pub block_proof: MerkleProof,
+    pub block_root: Root,
+    pub block_slot: Slot,
+}
+
+impl CommitmentProof {
+    /// Verify the proof against the block root.
+    pub fn verify(&self) -> bool {
+        let state_field_index = self.state_proof.index
+            >> self
+                .state_proof
+                .branch
+                .len()
+                .saturating_sub(BEACON_STATE_FIELDS_DEPTH);
+        let valid_leaf = match &self.pending_header {
+            Some(header) => {
+                header.confirmed
+                    && (header.slot, header.shard) == (self.slot, self.shard)
+                    && header.commitment == self.commitment
+                    && self.state_proof.leaf == header.hash_tree_root()
+                    && self.state_proof.branch.len() == PENDING_SHARD_HEADERS_PROOF_DEPTH
+                    && (state_field_index == PREVIOUS_EPOCH_PENDING_SHARD_HEADERS_INDEX
+                        || state_field_index == CURRENT_EPOCH_PENDING_SHARD_HEADERS_INDEX)
+            }
+            None => {
+                self.state_proof.leaf == self.commitment.hash_tree_root()
+                    && self.state_proof.branch.len() == CONFIRMED_COMMITMENTS_PROOF_DEPTH
+                    && self.state_proof.index
+                        == BeaconState::confirmed_commitment_index(self.shard, self.slot)
+            }
+        };
+        valid_leaf
+            && self.state_proof.verify(&self.state_root)
+            && self.block_proof.leaf == self.state_root
+            && self.block_proof.index == BEACON_BLOCK_HEADER_STATE_ROOT_INDEX
+            && self.block_proof.branch.len() == BEACON_BLOCK_HEADER_FIELDS_DEPTH
+            && self.block_proof.verify(&self.block_root)
+    }
+}
+
+/// Depth of the tree of the fields of `BeaconState` (11 fields).
+pub const BEACON_STATE_FIELDS_DEPTH: usize = 4;
+pub const PREVIOUS_EPOCH_PENDING_SHARD_HEADERS_INDEX: u64 = 2;
+pub const CURRENT_EPOCH_PENDING_SHARD_HEADERS_INDEX: u64 = 3;
+pub const GRANDPARENT_EPOCH_CONFIRMED_COMMITMENTS_INDEX: u64 = 4;
+/// Depth of the tree of the pending shard headers (MAX_SHARD_HEADERS * SLOTS_PER_EPOCH = 2 ** 13).
+pub const PENDING_SHARD_HEADERS_DEPTH: usize = 13;
+/// Depth of the tree of the confirmed commitments (SHARD_NUM * SLOTS_PER_EPOCH = 2 ** 11).
+pub const CONFIRMED_COMMITMENTS_DEPTH: usize = 11;
+/// Depth of the tree of the validators (VALIDATOR_REGISTRY_LIMIT = 2 ** 40).
+pub const VALIDATOR_REGISTRY_DEPTH: usize = 40;
+/// Depth of the tree of the balances, which are packed 4 per chunk (VALIDATOR_REGISTRY_LIMIT / 4 = 2 ** 38).
+pub const BALANCES_DEPTH: usize = 38;
+/// Depth of the proof of a pending shard header in the beacon state, including the length mix-in.
+pub const PENDING_SHARD_HEADERS_PROOF_DEPTH: usize =
+    PENDING_SHARD_HEADERS_DEPTH + 1 + BEACON_STATE_FIELDS_DEPTH;
+/// Depth of the proof of a confirmed commitment in the beacon state.
+pub const CONFIRMED_COMMITMENTS_PROOF_DEPTH: usize =
+    CONFIRMED_COMMITMENTS_DEPTH + BEACON_STATE_FIELDS_DEPTH;
+/// Depth of the tree of the fields of `BeaconBlockHeader` (4 fields).
+pub const BEACON_BLOCK_HEADER_FIELDS_DEPTH: usize = 2;
+pub const BEACON_BLOCK_HEADER_STATE_ROOT_INDEX: u64 = 2;
+
+impl BeaconState {
+    /// The roots of the fields, which are the leaves of the tree of the state.
+    pub fn field_roots(&self) -> Vec<Root> {
+        let validators: Vec<Root> = self.validators.iter().map(|v| v.hash_tree_root()).collect();
+        vec![
+            self.slot.hash_tree_root(),
+            self.finalized_checkpoint.hash_tree_root(),
+            Self::pending_shard_headers_root(&self.previous_epoch_pending_shard_headers),
+            Self::pending_shard_headers_root(&self.current_epoch_pending_shard_headers),
+            merkle_root(
+                &self.confirmed_commitment_roots(),
+                CONFIRMED_COMMITMENTS_DEPTH,
+            ),
+            self.shard_gasprice.hash_tree_root(),
+            mix_in_length(
+                &merkle_root(&validators, VALIDATOR_REGISTRY_DEPTH),
+                validators.len(),
+            ),
+            mix_in_length(
+                &merkle_root(&pack_u64s(&self.balances), BALANCES_DEPTH),
+                self.balances.len(),
+            ),
+            self.justification_bits.hash_tree_root(),
+            self.previous_justified_checkpoint.hash_tree_root(),
+            self.current_justified_checkpoint.hash_tree_root(),
+        ]
+    }
+
+    fn pending_shard_headers_root(headers: &[PendingShardHeader]) -> Root {
+        let leaves: Vec<Root> = headers.iter().map(|h| h.hash_tree_root()).collect();
+        mix_in_length(
+            &merkle_root(&leaves, PENDING_SHARD_HEADERS_DEPTH),
+            leaves.len(),
+        )
+    }
+
+    /// The roots of the confirmed commitments in order by shard and then slot.
+    fn confirmed_commitment_roots(&self) -> Vec<Root> {
+        self.grandparent_epoch_confirmed_commitments
+            .iter()
+            .flat_map(|commitments| commitments.iter().map(|c| c.hash_tree_root()))
+            .collect()
+    }
+
+    /// The index of the confirmed commitment of the shard at the slot in the tree of the state.
+    pub fn confirmed_commitment_index(shard: Shard, slot: Slot) -> u64 {
+        (GRANDPARENT_EPOCH_CONFIRMED_COMMITMENTS_INDEX << CONFIRMED_COMMITMENTS_DEPTH)
+            | (shard * SLOTS_PER_EPOCH + slot % SLOTS_PER_EPOCH)
+    }
+
+    /// Get the proof of the pending shard header at the index of the list.
+    /// The list is `previous_epoch_pending_shard_headers` if `previous_epoch` is true,
+    /// otherwise `current_epoch_pending_shard_headers`.
+    pub fn pending_shard_header_proof(&self, previous_epoch: bool, index: usize) -> MerkleProof {
+        let (headers, field_index) = if previous_epoch {
+            (
+                &self.previous_epoch_pending_shard_headers,
+                PREVIOUS_EPOCH_PENDING_SHARD_HEADERS_INDEX,
+            )
+        } else {
+            (
+                &self.current_epoch_pending_shard_headers,
+                CURRENT_EPOCH_PENDING_SHARD_HEADERS_INDEX,
+            )
+        };
+        let leaves: Vec<Root> = headers.iter().map(|h| h.hash_tree_root()).collect();
+        let mut proof = MerkleProof::new(&leaves, PENDING_SHARD_HEADERS_DEPTH, index);
+        // The root of the items is the left child of the root of the list.
+        proof.branch.push(length_leaf(leaves.len()));
+        proof.extend(MerkleProof::new(
+            &self.field_roots(),
+            BEACON_STATE_FIELDS_DEPTH,
+            field_index as usize,
+        ))
+    }
+
+    /// Get the proof of the confirmed commitment of the shard at the slot.
+    pub fn confirmed_commitment_proof(&self, shard: Shard, slot: Slot) -> MerkleProof {
+        let index = (shard * SLOTS_PER_EPOCH + slot % SLOTS_PER_EPOCH) as usize;
+        MerkleProof::new(
+            &self.confirmed_commitment_roots(),
+            CONFIRMED_COMMITMENTS_DEPTH,
+            index,
+        )
+        .extend(MerkleProof::new(
+            &self.field_roots(),
+            BEACON_STATE_FIELDS_DEPTH,
+            GRANDPARENT_EPOCH_CONFIRMED_COMMITMENTS_INDEX as usize,
+        ))
+    }
+}
+
+impl BeaconBlockHeader {
+    /// The roots of the fields, which are the leaves of the tree of the header.
+    pub fn field_roots(&self) -> Vec<Root> {
+        vec![
+            self.slot.hash_tree_root(),
+            self.parent_root,
+            self.state_root,
+            self.body_root,
+        ]
+    }
+
+    /// Get the proof of the state root in the header.
+    pub fn state_root_proof(&self) -> MerkleProof {
+        MerkleProof::new(
+            &self.field_roots(),
+            BEACON_BLOCK_HEADER_FIELDS_DEPTH,
+            BEACON_BLOCK_HEADER_STATE_ROOT_INDEX as usize,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eth2_utils::root;
+
+    fn leaves(num: usize) -> Vec<Root> {
+        (0..num).map(|i| root(&i)).collect()
+    }
+
+    #[test]
+    fn sha256_hash_tree_root() {
+        assert_eq!(
+            Root::from_slice(&hex_to_bytes(
+                "f5a5fd42d16a20302798ef6ed309979b43003d2320d9f0e8ea9831a92759fb4b"
+            )),
+            hash_pair(&Root::zero(), &Root::zero())
+        );
+        let checkpoint = Checkpoint {
+            epoch: 3,
+            root: Root::repeat_byte(0x11),
+        };
+        assert_eq!(
+            Root::from_slice(&hex_to_bytes(
+                "8d7ec135ffb397a99e8b3794c3adf61271572d368226dc807636996c30776aa6"
+            )),
+            checkpoint.hash_tree_root()
+        );
+        let commitment = DataCommitment {
+            point: [0x22; 48],
+            length: 5,
+        };
+        assert_eq!(
+            Root::from_slice(&hex_to_bytes(
+                "df42a0a5eaf3a0c2cd079a49188e6252631f8be6cf7f794ffb3718142c968f52"
+            )),
+            commitment.hash_tree_root()
+        );
+        // 4 `uint64` values are packed in a chunk.
+        assert_eq!(1, pack_u64s(&[1, 2, 3, 4]).len());
+        assert_eq!(2, pack_u64s(&[1, 2, 3, 4, 5]).len());
+    }
+
+    fn hex_to_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn merkle_branch_and_root() {
+        for num in 0..9 {
+            let leaves = leaves(num);
+            let merkle_root = merkle_root(&leaves, 3);
+            for index in 0..num {
+                let branch = merkle_branch(&leaves, 3, index);
+                assert_eq!(3, branch.len());
+                assert!(verify_merkle_branch(
+                    &leaves[index],
+                    &branch,
+                    index as u64,
+                    &merkle_root
+                ));
+                // Wrong index.
+                assert!(!verify_merkle_branch(
+                    &leaves[index],
+                    &branch,
+                    (index as u64) ^ 1,
+                    &merkle_root
+                ));
+            }
+        }
+        // Padding with zero does not change the root.
+        let mut padded = leaves(5);
+        padded.push(Root::zero());
+        assert_eq!(merkle_root(&leaves(5), 4), merkle_root(&padded, 4));
+        assert_ne!(merkle_root(&leaves(5), 3), merkle_root(&leaves(5), 4));
+    }
+
+    #[test]
+    fn extend_proof() {
+        let lower_leaves = leaves(4);
+        let lower_root = merkle_root(&lower_leaves, 2);
+        let mut upper_leaves = leaves(8);
+        upper_leaves[5] = lower_root;
+        let upper_root = merkle_root(&upper_leaves, 3);
+        let proof =
+            MerkleProof::new(&lower_leaves, 2, 3).extend(MerkleProof::new(&upper_leaves, 3, 5));
+        assert_eq!(5, proof.branch.len());
+        assert_eq!((5 << 2) | 3, proof.index);
+        assert!(proof.verify(&upper_root));
+    }
+}
@@ -0,0 +1,43 @@
+//! Wire types of the paginated listings of the HTTP API, shared by the server and the client.
+//! Items are paginated by slot, so a page has `limit` items even if slots are skipped.
+use crate::eth2_types::Slot;
+use serde_derive::{Deserialize, Serialize};
+
+/// The number of items in a page if `limit` is not specified.
+pub const DEFAULT_PAGE_LIMIT: usize = 100;
+/// The maximum number of items in a page.
+pub const MAX_PAGE_LIMIT: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+/// Query parameters of the paginated listings.
+/// - `from_slot`, `to_slot`: The inclusive range of the slots of the items.
+/// - `limit`: The maximum number of items in a page. The default is `DEFAULT_PAGE_LIMIT`.
+/// - `order`: The order by slot. The default is `desc` (the latest items first).
+/// - `cursor`: The `next` of the previous page.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaginationParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_slot: Option<Slot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_slot: Option<Slot>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<Order>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<Slot>,
+}
+
+/// A page of the listing.
+/// `next` is the cursor of the next page, or `None` if this is the last page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub next: Option<Slot>,
+}
@@ -1,4 +1,4 @@
-import React, { useState, useEffect } from 'react';
+import React, { useState, useEffect, useRef } from 'react';
 import {
   Box,
   Container,
@@ -26,25 +26,34 @@ const BlockListView = () => {
 
   const [count, setCount] = useState(10);
   const [page, setPage] = useState(0);
+  const cursors = useRef([null]);
 
   const updateBlocks = (count, page) => {
     let endpoint = "http://localhost:" + process.env.REACT_APP_EMULATOR_PORT_NUMBER + "/beacon/blocks";
     let url = new URL(endpoint);
     let params = {
-      count: count,
-      page: page,
+      limit: count,
+      order: "desc",
     };
+    // The cursor of a page is the `next` of the previous page.
+    if (page === 0) {
+      cursors.current = [null];
+    } else if (cursors.current[page] == null) {
+      // There is no more page.
+      setBlocks([]);
+      return;
+    } else {
+      params.cursor = cursors.current[page];
+    }
 
     Object.keys(params).forEach(key => url.searchParams.append(key, params[key]));
     fetch(url, {
       method: "GET",
     })
       .then(response => response.json())
-      .then(new_blocks => {
-        if (blocks !== new_blocks) {
-          new_blocks.reverse();
-          setBlocks(new_blocks);
-        }
+      .then(new_page => {
+        cursors.current[page + 1] = new_page.next;
+        setBlocks(new_page.data);
       })
       .catch(error => console.error("Error:", error));
   }
@@ -61,7 +61,9 @@ const Results = ({ className, ...rest }) => {
   const handleCountChange = (event) => {
     let count = event.target.value;
     rest.setCount(count);
-    rest.updateBlocks(count, rest.page);
+    // The cursors depend on the count, so restart from the first page.
+    rest.setPage(0);
+    rest.updateBlocks(count, 0);
   };
 
   const handlePageChange = (event, newPage) => {
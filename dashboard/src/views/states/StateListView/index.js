@@ -1,4 +1,4 @@
-import React, { useState, useEffect } from 'react';
+import React, { useState, useEffect, useRef } from 'react';
 import {
   Box,
   Container,
@@ -26,25 +26,34 @@ const StateListView = () => {
 
   const [count, setCount] = useState(10);
   const [page, setPage] = useState(0);
+  const cursors = useRef([null]);
 
   const updateStates = (count, page) => {
     let endpoint = "http://localhost:" + process.env.REACT_APP_EMULATOR_PORT_NUMBER + "/beacon/states";
     let url = new URL(endpoint);
     let params = {
-      count: count,
-      page: page,
+      limit: count,
+      order: "desc",
     };
+    // The cursor of a page is the `next` of the previous page.
+    if (page === 0) {
+      cursors.current = [null];
+    } else if (cursors.current[page] == null) {
+      // There is no more page.
+      setStates([]);
+      return;
+    } else {
+      params.cursor = cursors.current[page];
+    }
 
     Object.keys(params).forEach(key => url.searchParams.append(key, params[key]));
     fetch(url, {
       method: "GET",
     })
       .then(response => response.json())
-      .then(new_states => {
-        if (states !== new_states) {
-          new_states.reverse();
-          setStates(new_states);
-        }
+      .then(new_page => {
+        cursors.current[page + 1] = new_page.next;
+        setStates(new_page.data);
       })
       .catch(error => console.error("Error:", error));
   }
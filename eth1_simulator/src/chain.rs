@@ -0,0 +1,198 @@
+use crate::fee_market::{BidId, FeeMarket};
+use crate::oracle::{BeaconOracle, OracleUpdate};
+use crate::*;
+use serde_derive::{Deserialize, Serialize};
+
+/// u64.
+pub type BlockNumber = u64;
+
+/// The chain ID of the simulated Eth1 chain, which is commonly used by local development chains.
+pub const CHAIN_ID: u64 = 1337;
+/// The default block time in seconds.
+/// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/validator.md#misc
+pub const SECONDS_PER_ETH1_BLOCK: u64 = 14;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+pub struct Eth1Block {
+    pub number: BlockNumber,
+    pub hash: H256,
+    pub parent_hash: H256,
+    /// Unix time in seconds.
+    pub timestamp: u64,
+    /// The updates of the beacon oracle delivered in this block.
+    /// They are processed before the transactions.
+    pub oracle_updates: Vec<OracleUpdate>,
+    /// The hashes of the transactions in this block.
+    pub transactions: Vec<H256>,
+}
+
+/// Transactions to the fee market contract.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Transaction {
+    Deposit { from: Address, amount: Gwei },
+    PlaceBid { from: Address, bid: Bid },
+    Withdraw { from: Address, amount: Gwei },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+pub struct TransactionReceipt {
+    pub transaction_hash: H256,
+    pub transaction: Transaction,
+    pub block_number: BlockNumber,
+    /// The error message if the transaction failed.
+    pub error: Option<String>,
+    /// The ID of the bid placed by `PlaceBid`.
+    pub bid_id: Option<BidId>,
+}
+
+/// Simulated Eth1 chain, whose blocks are mined by calling `mine_block()`.
+/// The submitted transactions are executed when the next block is mined.
+pub struct Eth1Chain {
+    blocks: Vec<Eth1Block>,
+    pub oracle: BeaconOracle,
+    pub fee_market: FeeMarket,
+    pending_transactions: Vec<(H256, Transaction)>,
+    receipts: HashMap<H256, TransactionReceipt>,
+    /// The number of the submitted transactions, which makes the transaction hashes unique.
+    transaction_count: u64,
+    /// The bids placed in the contract and not published to the shards yet.
+    placed_bids: Vec<Bid>,
+}
+
+impl Eth1Chain {
+    /// Create a chain with the genesis block.
+    /// `oracle_lag` is the lag of the beacon oracle in Eth1 blocks.
+    pub fn new(genesis_timestamp: u64, oracle_lag: BlockNumber) -> Self {
+        let parent_hash = H256::zero();
+        let genesis = Eth1Block {
+            number: 0,
+            hash: root(&(0, parent_hash, genesis_timestamp)),
+            parent_hash,
+            timestamp: genesis_timestamp,
+            oracle_updates: vec![],
+            transactions: vec![],
+        };
+        Self {
+            blocks: vec![genesis],
+            oracle: BeaconOracle::new(oracle_lag),
+            fee_market: FeeMarket::default(),
+            pending_transactions: vec![],
+            receipts: HashMap::new(),
+            transaction_count: 0,
+            placed_bids: vec![],
+        }
+    }
+
+    pub fn head(&self) -> &Eth1Block {
+        self.blocks.last().unwrap()
+    }
+
+    pub fn get_block(&self, number: BlockNumber) -> Option<&Eth1Block> {
+        self.blocks.get(number as usize)
+    }
+
+    pub fn get_block_by_hash(&self, hash: &H256) -> Option<&Eth1Block> {
+        self.blocks.iter().rev().find(|block| block.hash == *hash)
+    }
+
+    pub fn get_transaction_receipt(&self, hash: &H256) -> Option<&TransactionReceipt> {
+        self.receipts.get(hash)
+    }
+
+    /// Submit a transaction, which is executed in the next block.
+    pub fn submit_transaction(&mut self, transaction: Transaction) -> H256 {
+        let hash = root(&(self.transaction_count, &transaction));
+        self.transaction_count += 1;
+        self.pending_transactions.push((hash, transaction));
+        hash
+    }
+
+    /// Take the bids placed since the last call to publish them to the shards.
+    pub fn take_placed_bids(&mut self) -> Vec<Bid> {
+        std::mem::take(&mut self.placed_bids)
+    }
+
+    /// Receive an update of the beacon chain, which is delivered to the oracle after the lag.
+    pub fn receive_beacon_update(&mut self, update: OracleUpdate) {
+        let head = self.head().number;
+        self.oracle.receive(update, head);
+    }
+
+    /// Reset the beacon oracle and the fee market contract, when the beacon chain is reset.
+    /// The blocks and the receipts are kept, and the pending transactions are executed
+    /// against the reset contract in the next block.
+    pub fn reset_contracts(&mut self) {
+        self.oracle = BeaconOracle::new(self.oracle.lag);
+        self.fee_market = FeeMarket::default();
+        self.placed_bids.clear();
+    }
+
+    /// Mine a new block on top of the head.
+    /// The timestamp is bumped if it is not later than the parent's one.
+    pub fn mine_block(&mut self, timestamp: u64) -> &Eth1Block {
+        let parent = self.head();
+        let number = parent.number + 1;
+        let parent_hash = parent.hash;
+        let timestamp = timestamp.max(parent.timestamp + 1);
+        let oracle_updates = self.oracle.deliver(number);
+        for update in oracle_updates.iter() {
+            self.fee_market.settle(update, number);
+        }
+        let mut transactions = vec![];
+        for (transaction_hash, transaction) in std::mem::take(&mut self.pending_transactions) {
+            let receipt = self.execute_transaction(transaction_hash, transaction, number);
+            self.receipts.insert(transaction_hash, receipt);
+            transactions.push(transaction_hash);
+        }
+        let hash = root(&(
+            number,
+            parent_hash,
+            timestamp,
+            &oracle_updates,
+            &transactions,
+        ));
+        self.blocks.push(Eth1Block {
+            number,
+            hash,
+            parent_hash,
+            timestamp,
+            oracle_updates,
+            transactions,
+        });
+        self.head()
+    }
+
+    fn execute_transaction(
+        &mut self,
+        transaction_hash: H256,
+        transaction: Transaction,
+        block_number: BlockNumber,
+    ) -> TransactionReceipt {
+        let result = match &transaction {
+            Transaction::Deposit { from, amount } => {
+                self.fee_market.deposit(*from, *amount).map(|_| None)
+            }
+            Transaction::PlaceBid { from, bid } => {
+                self.fee_market.place_bid(*from, bid.clone()).map(|bid_id| {
+                    self.placed_bids.push(bid.clone());
+                    Some(bid_id)
+                })
+            }
+            Transaction::Withdraw { from, amount } => {
+                self.fee_market.withdraw(*from, *amount).map(|_| None)
+            }
+        };
+        let (bid_id, error) = match result {
+            Ok(bid_id) => (bid_id, None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        TransactionReceipt {
+            transaction_hash,
+            transaction,
+            block_number,
+            error,
+            bid_id,
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::*;
+use thiserror::Error;
+
+/// Errors of the JSON-RPC requests.
+/// Ref: https://www.jsonrpc.org/specification#error_object
+#[derive(Error, Debug, PartialEq)]
+pub enum RpcError {
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+    #[error("method not found: {method:?}")]
+    MethodNotFound { method: String },
+    #[error("invalid params: {0}")]
+    InvalidParams(String),
+}
+
+impl RpcError {
+    pub fn code(&self) -> i64 {
+        match self {
+            RpcError::InvalidRequest(_) => -32600,
+            RpcError::MethodNotFound { .. } => -32601,
+            RpcError::InvalidParams(_) => -32602,
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FeeMarketError {
+    #[error("zero amount")]
+    ZeroAmount,
+    #[error("insufficient balance (available {available:?}, required {required:?})")]
+    InsufficientBalance { available: Gwei, required: Gwei },
+}
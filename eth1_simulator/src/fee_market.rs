@@ -0,0 +1,163 @@
+//! Emulation of the fee market contract for Eth2 shards in Eth1.
+//! Bidders deposit ETH to the contract and place bids for shard blocks.
+//! The fee of a bid is locked until the beacon oracle tells whether the bid's commitment is confirmed.
+//! Ref: https://ethresear.ch/t/a-fee-market-contract-for-eth2-shards-in-eth1/8124
+use crate::chain::BlockNumber;
+use crate::errors::FeeMarketError;
+use crate::oracle::OracleUpdate;
+use crate::*;
+use serde_derive::{Deserialize, Serialize};
+
+/// u64.
+pub type BidId = u64;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Deserialize, Serialize)]
+pub struct Account {
+    /// The balance that can be withdrawn or used for bids.
+    pub balance: Gwei,
+    /// The balance locked for the bids not settled yet.
+    pub locked: Gwei,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementStatus {
+    /// The bid's commitment is confirmed and the fee is paid to the shard proposer.
+    Paid,
+    /// The bid's commitment is not confirmed and the fee is refunded to the bidder.
+    Refunded,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+pub struct SettlementReceipt {
+    pub bid_id: BidId,
+    pub bidder: Address,
+    pub bid: Bid,
+    pub status: SettlementStatus,
+    /// The number of the Eth1 block where the bid is settled.
+    pub block_number: BlockNumber,
+}
+
+#[derive(Debug, Clone)]
+struct PlacedBid {
+    id: BidId,
+    bidder: Address,
+    bid: Bid,
+}
+
+#[derive(Default)]
+pub struct FeeMarket {
+    accounts: HashMap<Address, Account>,
+    /// The bids not settled yet.
+    pending_bids: Vec<PlacedBid>,
+    receipts: Vec<SettlementReceipt>,
+    next_bid_id: BidId,
+    /// The total fees paid to the shard proposers.
+    pub collected_fees: Gwei,
+}
+
+impl FeeMarket {
+    pub fn get_account(&self, address: &Address) -> Account {
+        self.accounts.get(address).cloned().unwrap_or_default()
+    }
+
+    /// The settlement receipts of the bids placed by `bidder`.
+    pub fn get_receipts(&self, bidder: &Address) -> Vec<&SettlementReceipt> {
+        self.receipts
+            .iter()
+            .filter(|receipt| receipt.bidder == *bidder)
+            .collect()
+    }
+
+    pub fn deposit(&mut self, from: Address, amount: Gwei) -> Result<(), FeeMarketError> {
+        if amount == 0 {
+            return Err(FeeMarketError::ZeroAmount);
+        }
+        self.accounts.entry(from).or_default().balance += amount;
+        Ok(())
+    }
+
+    pub fn withdraw(&mut self, from: Address, amount: Gwei) -> Result<(), FeeMarketError> {
+        if amount == 0 {
+            return Err(FeeMarketError::ZeroAmount);
+        }
+        let account = self.accounts.entry(from).or_default();
+        if account.balance < amount {
+            return Err(FeeMarketError::InsufficientBalance {
+                available: account.balance,
+                required: amount,
+            });
+        }
+        account.balance -= amount;
+        Ok(())
+    }
+
+    /// Place a bid and lock its fee.
+    pub fn place_bid(&mut self, from: Address, bid: Bid) -> Result<BidId, FeeMarketError> {
+        let account = self.accounts.entry(from).or_default();
+        if account.balance < bid.fee {
+            return Err(FeeMarketError::InsufficientBalance {
+                available: account.balance,
+                required: bid.fee,
+            });
+        }
+        account.balance -= bid.fee;
+        account.locked += bid.fee;
+        let id = self.next_bid_id;
+        self.next_bid_id += 1;
+        self.pending_bids.push(PlacedBid {
+            id,
+            bidder: from,
+            bid,
+        });
+        Ok(id)
+    }
+
+    /// Settle the bids with an update of the beacon oracle.
+    /// - `ConfirmedCommitment`: Among the bids for the shard block, the bid with the confirmed commitment and the highest fee is paid,
+    ///   which is the one selected by the shard proposer. The others are refunded.
+    /// - `FinalizedCheckpoint`: The bids before the previous epoch of the checkpoint are refunded,
+    ///   since their shard headers are never confirmed after the finalization.
+    pub fn settle(&mut self, update: &OracleUpdate, block_number: BlockNumber) {
+        let (settled, pending): (Vec<PlacedBid>, Vec<PlacedBid>) = match update {
+            OracleUpdate::ConfirmedCommitment { shard, slot, .. } => self
+                .pending_bids
+                .drain(..)
+                .partition(|placed| placed.bid.shard == *shard && placed.bid.slot == *slot),
+            OracleUpdate::FinalizedCheckpoint { checkpoint } => {
+                let deadline = compute_start_slot_at_epoch(checkpoint.epoch.saturating_sub(1));
+                self.pending_bids
+                    .drain(..)
+                    .partition(|placed| placed.bid.slot < deadline)
+            }
+        };
+        self.pending_bids = pending;
+
+        let paid_id = match update {
+            OracleUpdate::ConfirmedCommitment { commitment, .. } => settled
+                .iter()
+                .filter(|placed| placed.bid.commitment == *commitment)
+                .max_by_key(|placed| placed.bid.fee)
+                .map(|placed| placed.id),
+            OracleUpdate::FinalizedCheckpoint { .. } => None,
+        };
+        for placed in settled {
+            let account = self.accounts.entry(placed.bidder).or_default();
+            account.locked -= placed.bid.fee;
+            let status = if Some(placed.id) == paid_id {
+                self.collected_fees += placed.bid.fee;
+                SettlementStatus::Paid
+            } else {
+                account.balance += placed.bid.fee;
+                SettlementStatus::Refunded
+            };
+            self.receipts.push(SettlementReceipt {
+                bid_id: placed.id,
+                bidder: placed.bidder,
+                bid: placed.bid,
+                status,
+                block_number,
+            });
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Simulated Eth1 chain for local testing of the applications settling on Eth1.
+pub use common::bid::*;
+pub use common::eth2_config::*;
+pub use common::eth2_types::*;
+pub use common::eth2_utils::*;
+pub use ethereum_types::Address;
+pub use std::collections::{HashMap, VecDeque};
+
+pub mod chain;
+pub mod errors;
+pub mod fee_market;
+pub mod oracle;
+pub mod rpc;
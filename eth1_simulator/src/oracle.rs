@@ -0,0 +1,85 @@
+use crate::chain::BlockNumber;
+use crate::*;
+use serde_derive::{Deserialize, Serialize};
+
+/// Updates of the beacon chain relayed to Eth1.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OracleUpdate {
+    /// A checkpoint is finalized in the beacon chain.
+    FinalizedCheckpoint { checkpoint: Checkpoint },
+    /// A shard header is confirmed in the beacon chain.
+    ConfirmedCommitment {
+        shard: Shard,
+        slot: Slot,
+        commitment: DataCommitment,
+    },
+}
+
+/// Stand-in of the beacon light client (bridge) contract on Eth1.
+/// The updates received from the beacon chain are delivered `lag` Eth1 blocks later.
+pub struct BeaconOracle {
+    /// The number of Eth1 blocks between the receipt and the delivery of an update.
+    pub lag: BlockNumber,
+    /// The received updates and the numbers of the blocks to deliver them.
+    pending_updates: VecDeque<(BlockNumber, OracleUpdate)>,
+    /// The latest finalized checkpoint delivered to Eth1.
+    pub finalized_checkpoint: Checkpoint,
+    /// The confirmed commitments delivered to Eth1.
+    confirmed_commitments: HashMap<(Shard, Slot), DataCommitment>,
+}
+
+impl BeaconOracle {
+    pub fn new(lag: BlockNumber) -> Self {
+        Self {
+            lag,
+            pending_updates: VecDeque::new(),
+            finalized_checkpoint: Checkpoint::genesis_finalized_checkpoint(),
+            confirmed_commitments: HashMap::new(),
+        }
+    }
+
+    /// Receive an update when the head of Eth1 is `head`.
+    /// The update is delivered in block `head + lag`, or in the next block if `lag` is 0.
+    pub fn receive(&mut self, update: OracleUpdate, head: BlockNumber) {
+        let deliver_at = head + self.lag.max(1);
+        self.pending_updates.push_back((deliver_at, update));
+    }
+
+    /// Deliver the updates due in block `number`.
+    pub fn deliver(&mut self, number: BlockNumber) -> Vec<OracleUpdate> {
+        let mut delivered = vec![];
+        while let Some((deliver_at, _)) = self.pending_updates.front() {
+            if number < *deliver_at {
+                break;
+            }
+            let (_, update) = self.pending_updates.pop_front().unwrap();
+            match &update {
+                OracleUpdate::FinalizedCheckpoint { checkpoint } => {
+                    if self.finalized_checkpoint.epoch < checkpoint.epoch {
+                        self.finalized_checkpoint = checkpoint.clone();
+                    }
+                }
+                OracleUpdate::ConfirmedCommitment {
+                    shard,
+                    slot,
+                    commitment,
+                } => {
+                    self.confirmed_commitments
+                        .insert((*shard, *slot), commitment.clone());
+                }
+            }
+            delivered.push(update);
+        }
+        delivered
+    }
+
+    /// The number of the updates not delivered yet.
+    pub fn pending_updates_count(&self) -> usize {
+        self.pending_updates.len()
+    }
+
+    pub fn get_confirmed_commitment(&self, shard: Shard, slot: Slot) -> Option<&DataCommitment> {
+        self.confirmed_commitments.get(&(shard, slot))
+    }
+}
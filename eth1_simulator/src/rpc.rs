@@ -0,0 +1,201 @@
+//! JSON-RPC interface of the simulated Eth1 chain.
+//! The standard `eth_*` methods for reading blocks and receipts, and the `mousse_*` methods for the beacon oracle
+//! and the fee market contract are supported.
+//! Ref: https://eth.wiki/json-rpc/API
+use crate::chain::{Eth1Block, Eth1Chain, Transaction, TransactionReceipt, CHAIN_ID};
+use crate::errors::RpcError;
+use crate::*;
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub const JSONRPC_VERSION: &str = "2.0";
+pub const CLIENT_VERSION: &str = "Mousse/v0.1.0/eth1";
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Value,
+    pub method: String,
+    #[serde(default)]
+    pub params: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JsonRpcErrorObject {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcErrorObject>,
+}
+
+impl JsonRpcResponse {
+    fn new(id: Value, result: Result<Value, RpcError>) -> Self {
+        match result {
+            Ok(result) => Self {
+                jsonrpc: JSONRPC_VERSION.into(),
+                id,
+                result: Some(result),
+                error: None,
+            },
+            Err(e) => Self {
+                jsonrpc: JSONRPC_VERSION.into(),
+                id,
+                result: None,
+                error: Some(JsonRpcErrorObject {
+                    code: e.code(),
+                    message: e.to_string(),
+                }),
+            },
+        }
+    }
+}
+
+/// Handle a JSON-RPC request to the chain.
+pub fn handle_request(chain: &mut Eth1Chain, request: JsonRpcRequest) -> JsonRpcResponse {
+    let result = if request.jsonrpc != JSONRPC_VERSION {
+        Err(RpcError::InvalidRequest(format!(
+            "jsonrpc must be {:?}, found {:?}",
+            JSONRPC_VERSION, request.jsonrpc
+        )))
+    } else {
+        call(chain, &request.method, &request.params)
+    };
+    JsonRpcResponse::new(request.id, result)
+}
+
+fn call(chain: &mut Eth1Chain, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+    match method {
+        "web3_clientVersion" => Ok(json!(CLIENT_VERSION)),
+        "net_version" => Ok(json!(CHAIN_ID.to_string())),
+        "eth_chainId" => Ok(json!(to_quantity(CHAIN_ID))),
+        "eth_blockNumber" => Ok(json!(to_quantity(chain.head().number))),
+        "eth_getBlockByNumber" => {
+            let number = match param(params, 0)? {
+                Value::String(tag) if tag == "latest" || tag == "pending" => chain.head().number,
+                Value::String(tag) if tag == "earliest" => 0,
+                value => parse_quantity(value)?,
+            };
+            Ok(chain.get_block(number).map_or(Value::Null, block_to_json))
+        }
+        "eth_getBlockByHash" => {
+            let hash: H256 = parse_param(params, 0)?;
+            Ok(chain
+                .get_block_by_hash(&hash)
+                .map_or(Value::Null, block_to_json))
+        }
+        "eth_getTransactionReceipt" => {
+            let hash: H256 = parse_param(params, 0)?;
+            Ok(chain
+                .get_transaction_receipt(&hash)
+                .map_or(Value::Null, receipt_to_json))
+        }
+        "mousse_getFinalizedCheckpoint" => Ok(json!(chain.oracle.finalized_checkpoint)),
+        "mousse_getConfirmedCommitment" => {
+            let shard = parse_quantity(param(params, 0)?)?;
+            let slot = parse_quantity(param(params, 1)?)?;
+            Ok(chain
+                .oracle
+                .get_confirmed_commitment(shard, slot)
+                .map_or(Value::Null, |commitment| json!(commitment)))
+        }
+        "mousse_deposit" => {
+            let transaction = Transaction::Deposit {
+                from: parse_param(params, 0)?,
+                amount: parse_quantity(param(params, 1)?)?,
+            };
+            Ok(json!(chain.submit_transaction(transaction)))
+        }
+        "mousse_placeBid" => {
+            let transaction = Transaction::PlaceBid {
+                from: parse_param(params, 0)?,
+                bid: parse_param(params, 1)?,
+            };
+            Ok(json!(chain.submit_transaction(transaction)))
+        }
+        "mousse_withdraw" => {
+            let transaction = Transaction::Withdraw {
+                from: parse_param(params, 0)?,
+                amount: parse_quantity(param(params, 1)?)?,
+            };
+            Ok(json!(chain.submit_transaction(transaction)))
+        }
+        "mousse_getAccount" => {
+            let address: Address = parse_param(params, 0)?;
+            Ok(json!(chain.fee_market.get_account(&address)))
+        }
+        "mousse_getSettlementReceipts" => {
+            let address: Address = parse_param(params, 0)?;
+            Ok(json!(chain.fee_market.get_receipts(&address)))
+        }
+        _ => Err(RpcError::MethodNotFound {
+            method: method.into(),
+        }),
+    }
+}
+
+fn param(params: &[Value], index: usize) -> Result<&Value, RpcError> {
+    params
+        .get(index)
+        .ok_or_else(|| RpcError::InvalidParams(format!("missing param at {}", index)))
+}
+
+fn parse_param<T: DeserializeOwned>(params: &[Value], index: usize) -> Result<T, RpcError> {
+    serde_json::from_value(param(params, index)?.clone())
+        .map_err(|e| RpcError::InvalidParams(format!("param at {}: {}", index, e)))
+}
+
+/// Encode a number as a hex string with the "0x" prefix.
+pub fn to_quantity(number: u64) -> String {
+    format!("{:#x}", number)
+}
+
+/// Parse a hex string with the "0x" prefix or a JSON number.
+pub fn parse_quantity(value: &Value) -> Result<u64, RpcError> {
+    match value {
+        Value::Number(number) => number
+            .as_u64()
+            .ok_or_else(|| RpcError::InvalidParams(format!("invalid quantity: {}", value))),
+        Value::String(hex) if hex.starts_with("0x") => u64::from_str_radix(&hex[2..], 16)
+            .map_err(|_| RpcError::InvalidParams(format!("invalid quantity: {}", value))),
+        _ => Err(RpcError::InvalidParams(format!(
+            "invalid quantity: {}",
+            value
+        ))),
+    }
+}
+
+/// Encode a block in the format of `eth_getBlockByNumber`.
+/// `transactions` are always the transaction hashes.
+fn block_to_json(block: &Eth1Block) -> Value {
+    json!({
+        "number": to_quantity(block.number),
+        "hash": block.hash,
+        "parentHash": block.parent_hash,
+        "timestamp": to_quantity(block.timestamp),
+        "transactions": block.transactions,
+        "oracleUpdates": block.oracle_updates,
+    })
+}
+
+/// Encode a receipt in the format of `eth_getTransactionReceipt`.
+/// `status` is `0x1` on success and `0x0` on failure as in Ethereum, and `error` is the reason of the failure.
+fn receipt_to_json(receipt: &TransactionReceipt) -> Value {
+    json!({
+        "transactionHash": receipt.transaction_hash,
+        "blockNumber": to_quantity(receipt.block_number),
+        "status": to_quantity(receipt.error.is_none() as u64),
+        "error": receipt.error,
+        "transaction": receipt.transaction,
+        "bidId": receipt.bid_id,
+    })
+}
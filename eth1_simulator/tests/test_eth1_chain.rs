@@ -0,0 +1,452 @@
+use chain::{Eth1Chain, Transaction, CHAIN_ID};
+use eth1_simulator::*;
+use eth2_simulator::simulator::{Event, Simulator};
+use fee_market::{Account, SettlementStatus};
+use oracle::OracleUpdate;
+use rpc::{handle_request, JsonRpcRequest};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+
+fn request(method: &str, params: Vec<Value>) -> JsonRpcRequest {
+    JsonRpcRequest {
+        jsonrpc: "2.0".into(),
+        id: json!(1),
+        method: method.into(),
+        params,
+    }
+}
+
+#[test]
+fn mine_blocks() {
+    let mut chain = Eth1Chain::new(100, 0);
+    let genesis = chain.head().clone();
+    assert_eq!(0, genesis.number);
+
+    chain.mine_block(112);
+    // The timestamp must be later than the parent's one.
+    chain.mine_block(112);
+    assert_eq!(2, chain.head().number);
+    assert_eq!(113, chain.head().timestamp);
+    let block = chain.get_block(1).unwrap().clone();
+    assert_eq!(genesis.hash, block.parent_hash);
+    assert_eq!(block.hash, chain.head().parent_hash);
+    assert_eq!(Some(&block), chain.get_block_by_hash(&block.hash));
+    assert_eq!(None, chain.get_block(3));
+}
+
+#[test]
+fn deliver_beacon_updates_after_lag() {
+    let lag = 3;
+    let mut chain = Eth1Chain::new(0, lag);
+    let checkpoint = Checkpoint {
+        epoch: 1,
+        root: root(&1),
+    };
+    let commitment = DataCommitment::dummy_from_bytes(&[1, 2, 3]);
+    chain.receive_beacon_update(OracleUpdate::FinalizedCheckpoint {
+        checkpoint: checkpoint.clone(),
+    });
+    chain.receive_beacon_update(OracleUpdate::ConfirmedCommitment {
+        shard: 0,
+        slot: 2,
+        commitment: commitment.clone(),
+    });
+
+    for timestamp in 1..lag {
+        chain.mine_block(timestamp);
+        assert!(chain.head().oracle_updates.is_empty());
+        assert_eq!(GENESIS_EPOCH, chain.oracle.finalized_checkpoint.epoch);
+        assert_eq!(None, chain.oracle.get_confirmed_commitment(0, 2));
+    }
+    chain.mine_block(lag);
+    assert_eq!(2, chain.head().oracle_updates.len());
+    assert_eq!(0, chain.oracle.pending_updates_count());
+    assert_eq!(checkpoint, chain.oracle.finalized_checkpoint);
+    assert_eq!(
+        Some(&commitment),
+        chain.oracle.get_confirmed_commitment(0, 2)
+    );
+
+    // An older checkpoint does not revert the finalized checkpoint.
+    chain.receive_beacon_update(OracleUpdate::FinalizedCheckpoint {
+        checkpoint: Checkpoint::genesis_finalized_checkpoint(),
+    });
+    for timestamp in 0..lag {
+        chain.mine_block(lag + 1 + timestamp);
+    }
+    assert_eq!(checkpoint, chain.oracle.finalized_checkpoint);
+}
+
+#[test]
+fn handle_json_rpc_requests() {
+    let mut chain = Eth1Chain::new(0, 0);
+    chain.receive_beacon_update(OracleUpdate::ConfirmedCommitment {
+        shard: 1,
+        slot: 5,
+        commitment: DataCommitment::dummy_from_bytes(&[1]),
+    });
+    chain.mine_block(1);
+
+    let response = handle_request(&mut chain, request("eth_chainId", vec![]));
+    assert_eq!(Some(json!(format!("{:#x}", CHAIN_ID))), response.result);
+    let response = handle_request(&mut chain, request("eth_blockNumber", vec![]));
+    assert_eq!(Some(json!("0x1")), response.result);
+
+    let response = handle_request(
+        &mut chain,
+        request("eth_getBlockByNumber", vec![json!("latest"), json!(false)]),
+    );
+    let block = response.result.unwrap();
+    assert_eq!(json!("0x1"), block["number"]);
+    assert_eq!(1, block["oracleUpdates"].as_array().unwrap().len());
+    let response = handle_request(
+        &mut chain,
+        request("eth_getBlockByHash", vec![block["parentHash"].clone()]),
+    );
+    assert_eq!(json!("0x0"), response.result.unwrap()["number"]);
+    let response = handle_request(
+        &mut chain,
+        request("eth_getBlockByNumber", vec![json!("0x2"), json!(false)]),
+    );
+    assert_eq!(Some(Value::Null), response.result);
+
+    let response = handle_request(
+        &mut chain,
+        request(
+            "mousse_getConfirmedCommitment",
+            vec![json!(1), json!("0x5")],
+        ),
+    );
+    assert_eq!(
+        Some(json!(DataCommitment::dummy_from_bytes(&[1]))),
+        response.result
+    );
+    let response = handle_request(
+        &mut chain,
+        request("mousse_getConfirmedCommitment", vec![json!(1)]),
+    );
+    assert_eq!(-32602, response.error.unwrap().code);
+    let response = handle_request(&mut chain, request("eth_sendTransaction", vec![]));
+    assert_eq!(-32601, response.error.unwrap().code);
+}
+
+fn bid(shard: Shard, slot: Slot, data: &[u8], fee: Gwei) -> Bid {
+    Bid {
+        shard,
+        slot,
+        commitment: DataCommitment::dummy_from_bytes(data),
+        fee,
+    }
+}
+
+#[test]
+fn deposit_bid_and_withdraw() {
+    let mut chain = Eth1Chain::new(0, 0);
+    let alice = Address::repeat_byte(1);
+    let deposit = chain.submit_transaction(Transaction::Deposit {
+        from: alice,
+        amount: 100,
+    });
+    let place_bid = chain.submit_transaction(Transaction::PlaceBid {
+        from: alice,
+        bid: bid(0, 2, &[1], 30),
+    });
+    let too_large_bid = chain.submit_transaction(Transaction::PlaceBid {
+        from: alice,
+        bid: bid(0, 3, &[1], 80),
+    });
+    let withdraw = chain.submit_transaction(Transaction::Withdraw {
+        from: alice,
+        amount: 50,
+    });
+    // Transactions are executed when a block is mined.
+    assert_eq!(None, chain.get_transaction_receipt(&deposit));
+    assert_eq!(Account::default(), chain.fee_market.get_account(&alice));
+
+    chain.mine_block(1);
+    assert_eq!(
+        vec![deposit, place_bid, too_large_bid, withdraw],
+        chain.head().transactions
+    );
+    assert_eq!(None, chain.get_transaction_receipt(&deposit).unwrap().error);
+    assert_eq!(
+        Some(0),
+        chain.get_transaction_receipt(&place_bid).unwrap().bid_id
+    );
+    assert!(chain
+        .get_transaction_receipt(&too_large_bid)
+        .unwrap()
+        .error
+        .is_some());
+    assert_eq!(
+        None,
+        chain.get_transaction_receipt(&withdraw).unwrap().error
+    );
+    assert_eq!(
+        Account {
+            balance: 20,
+            locked: 30
+        },
+        chain.fee_market.get_account(&alice)
+    );
+    assert_eq!(vec![bid(0, 2, &[1], 30)], chain.take_placed_bids());
+    assert!(chain.take_placed_bids().is_empty());
+
+    // The locked balance cannot be withdrawn.
+    let withdraw = chain.submit_transaction(Transaction::Withdraw {
+        from: alice,
+        amount: 21,
+    });
+    chain.mine_block(2);
+    assert!(chain
+        .get_transaction_receipt(&withdraw)
+        .unwrap()
+        .error
+        .is_some());
+}
+
+#[test]
+fn reset_contracts() {
+    let mut chain = Eth1Chain::new(0, 1);
+    let alice = Address::repeat_byte(1);
+    chain.submit_transaction(Transaction::Deposit {
+        from: alice,
+        amount: 100,
+    });
+    chain.submit_transaction(Transaction::PlaceBid {
+        from: alice,
+        bid: bid(0, 2, &[1], 30),
+    });
+    chain.mine_block(1);
+    chain.receive_beacon_update(OracleUpdate::FinalizedCheckpoint {
+        checkpoint: Checkpoint {
+            epoch: 1,
+            root: root(&1),
+        },
+    });
+
+    // The blocks are kept, and the oracle and the fee market start over.
+    chain.reset_contracts();
+    assert_eq!(1, chain.head().number);
+    assert_eq!(0, chain.oracle.pending_updates_count());
+    assert_eq!(Account::default(), chain.fee_market.get_account(&alice));
+    assert!(chain.take_placed_bids().is_empty());
+    chain.mine_block(2);
+    assert_eq!(GENESIS_EPOCH, chain.oracle.finalized_checkpoint.epoch);
+}
+
+#[test]
+fn settle_bids() {
+    let mut chain = Eth1Chain::new(0, 0);
+    let alice = Address::repeat_byte(1);
+    let bob = Address::repeat_byte(2);
+    for from in [alice, bob].iter() {
+        chain.submit_transaction(Transaction::Deposit {
+            from: *from,
+            amount: 100,
+        });
+    }
+    chain.submit_transaction(Transaction::PlaceBid {
+        from: alice,
+        bid: bid(0, 2, &[1], 30),
+    });
+    chain.submit_transaction(Transaction::PlaceBid {
+        from: bob,
+        bid: bid(0, 2, &[2], 20),
+    });
+    chain.submit_transaction(Transaction::PlaceBid {
+        from: bob,
+        bid: bid(1, 3, &[3], 10),
+    });
+    chain.mine_block(1);
+
+    // The bid with the confirmed commitment is paid and the other bid for the slot is refunded.
+    chain.receive_beacon_update(OracleUpdate::ConfirmedCommitment {
+        shard: 0,
+        slot: 2,
+        commitment: DataCommitment::dummy_from_bytes(&[1]),
+    });
+    chain.mine_block(2);
+    assert_eq!(
+        Account {
+            balance: 70,
+            locked: 0
+        },
+        chain.fee_market.get_account(&alice)
+    );
+    assert_eq!(
+        Account {
+            balance: 80,
+            locked: 10
+        },
+        chain.fee_market.get_account(&bob)
+    );
+    assert_eq!(30, chain.fee_market.collected_fees);
+    let receipts = chain.fee_market.get_receipts(&alice);
+    assert_eq!(1, receipts.len());
+    assert_eq!(SettlementStatus::Paid, receipts[0].status);
+    assert_eq!(2, receipts[0].block_number);
+    let receipts = chain.fee_market.get_receipts(&bob);
+    assert_eq!(1, receipts.len());
+    assert_eq!(SettlementStatus::Refunded, receipts[0].status);
+
+    // The bid whose header is never confirmed is refunded after the finalization.
+    chain.receive_beacon_update(OracleUpdate::FinalizedCheckpoint {
+        checkpoint: Checkpoint {
+            epoch: 1,
+            root: root(&1),
+        },
+    });
+    chain.mine_block(3);
+    assert_eq!(10, chain.fee_market.get_account(&bob).locked);
+    chain.receive_beacon_update(OracleUpdate::FinalizedCheckpoint {
+        checkpoint: Checkpoint {
+            epoch: 2,
+            root: root(&2),
+        },
+    });
+    chain.mine_block(4);
+    assert_eq!(
+        Account {
+            balance: 100,
+            locked: 0
+        },
+        chain.fee_market.get_account(&bob)
+    );
+    assert_eq!(2, chain.fee_market.get_receipts(&bob).len());
+    assert_eq!(30, chain.fee_market.collected_fees);
+}
+
+#[test]
+fn settle_bids_published_to_shards() {
+    let mut simulator = Simulator::new();
+    simulator.process_slots_happy(0).unwrap();
+    let updates = Arc::new(Mutex::new(vec![]));
+    let observed = updates.clone();
+    simulator.subscribe(Box::new(move |event: &Event| match event {
+        Event::CheckpointFinalized { checkpoint } => {
+            observed
+                .lock()
+                .unwrap()
+                .push(OracleUpdate::FinalizedCheckpoint {
+                    checkpoint: checkpoint.clone(),
+                })
+        }
+        Event::ShardHeaderConfirmed { header } => {
+            observed
+                .lock()
+                .unwrap()
+                .push(OracleUpdate::ConfirmedCommitment {
+                    shard: header.shard,
+                    slot: header.slot,
+                    commitment: header.commitment.clone(),
+                })
+        }
+        _ => (),
+    }));
+
+    let mut chain = Eth1Chain::new(0, 1);
+    let alice = Address::repeat_byte(1);
+    let bob = Address::repeat_byte(2);
+    for from in [alice, bob].iter() {
+        chain.submit_transaction(Transaction::Deposit {
+            from: *from,
+            amount: 100,
+        });
+    }
+    chain.submit_transaction(Transaction::PlaceBid {
+        from: alice,
+        bid: bid(0, 2, &[1], 30),
+    });
+    chain.submit_transaction(Transaction::PlaceBid {
+        from: bob,
+        bid: bid(0, 2, &[2], 20),
+    });
+    chain.mine_block(1);
+    for bid in chain.take_placed_bids() {
+        simulator.publish_bid(bid).unwrap();
+    }
+
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(4))
+        .unwrap();
+    for update in updates.lock().unwrap().drain(..) {
+        chain.receive_beacon_update(update);
+    }
+    chain.mine_block(2);
+    assert_eq!(
+        Account {
+            balance: 70,
+            locked: 0
+        },
+        chain.fee_market.get_account(&alice)
+    );
+    assert_eq!(
+        Account {
+            balance: 100,
+            locked: 0
+        },
+        chain.fee_market.get_account(&bob)
+    );
+    assert_eq!(
+        SettlementStatus::Paid,
+        chain.fee_market.get_receipts(&alice)[0].status
+    );
+    assert_eq!(
+        Some(&DataCommitment::dummy_from_bytes(&[1])),
+        chain.oracle.get_confirmed_commitment(0, 2)
+    );
+    assert_eq!(2, chain.oracle.finalized_checkpoint.epoch);
+}
+
+#[test]
+fn handle_fee_market_requests() {
+    let mut chain = Eth1Chain::new(0, 0);
+    let alice = json!(Address::repeat_byte(1));
+    let response = handle_request(
+        &mut chain,
+        request("mousse_deposit", vec![alice.clone(), json!("0x64")]),
+    );
+    let deposit = response.result.unwrap();
+    handle_request(
+        &mut chain,
+        request(
+            "mousse_placeBid",
+            vec![alice.clone(), json!(bid(0, 2, &[1], 30))],
+        ),
+    );
+    chain.mine_block(1);
+
+    let response = handle_request(
+        &mut chain,
+        request("eth_getTransactionReceipt", vec![deposit.clone()]),
+    );
+    let receipt = response.result.unwrap();
+    assert_eq!(deposit, receipt["transactionHash"]);
+    assert_eq!(json!("0x1"), receipt["blockNumber"]);
+    assert_eq!(json!("0x1"), receipt["status"]);
+    let response = handle_request(
+        &mut chain,
+        request("mousse_getAccount", vec![alice.clone()]),
+    );
+    assert_eq!(Some(json!({"balance": 70, "locked": 30})), response.result);
+
+    chain.receive_beacon_update(OracleUpdate::ConfirmedCommitment {
+        shard: 0,
+        slot: 2,
+        commitment: DataCommitment::dummy_from_bytes(&[1]),
+    });
+    chain.mine_block(2);
+    let response = handle_request(
+        &mut chain,
+        request("mousse_getSettlementReceipts", vec![alice.clone()]),
+    );
+    let receipts = response.result.unwrap();
+    assert_eq!(json!("paid"), receipts[0]["status"]);
+
+    let response = handle_request(
+        &mut chain,
+        request("mousse_placeBid", vec![alice, json!({"fee": 1})]),
+    );
+    assert_eq!(-32602, response.error.unwrap().code);
+}
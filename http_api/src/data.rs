@@ -0,0 +1,134 @@
+//! Decoding of the data uploaded with the bids and to the utils.
+//! The data can be a string in a JSON body (base64 or hex), a raw `application/octet-stream` body,
+//! or a part of a `multipart/form-data` body.
+use crate::*;
+use futures::TryStreamExt;
+use std::collections::HashMap;
+pub use warp::multipart::FormData;
+use warp::multipart::Part;
+use warp::Buf;
+
+/// The maximum size of the bodies with data.
+pub const MAX_DATA_BODY_SIZE: u64 = 1024 * 1024;
+
+/// Encoding of the data strings in the JSON bodies.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataEncoding {
+    Base64,
+    /// Hex with or without the `0x` prefix.
+    Hex,
+}
+
+impl Default for DataEncoding {
+    fn default() -> Self {
+        DataEncoding::Base64
+    }
+}
+
+/// Strictly decode the data string, which is never replaced by empty data.
+pub fn decode_data(data: &str, encoding: DataEncoding) -> Result<Vec<u8>, DataError> {
+    match encoding {
+        DataEncoding::Base64 => base64::decode(data).map_err(|e| DataError::InvalidBase64 {
+            reason: e.to_string(),
+        }),
+        DataEncoding::Hex => {
+            let data = data.strip_prefix("0x").unwrap_or(data);
+            hex::decode(data).map_err(|e| DataError::InvalidHex {
+                reason: e.to_string(),
+            })
+        }
+    }
+}
+
+/// The raw data of an `application/octet-stream` body.
+pub fn octet_stream_data() -> impl Filter<Extract = (Vec<u8>,), Error = warp::Rejection> + Clone {
+    warp::header::exact_ignore_case("content-type", "application/octet-stream")
+        .and(warp::body::content_length_limit(MAX_DATA_BODY_SIZE))
+        .and(warp::body::bytes())
+        .map(|bytes: warp::hyper::body::Bytes| bytes.to_vec())
+}
+
+/// A `multipart/form-data` body.
+pub fn multipart_form() -> impl Filter<Extract = (FormData,), Error = warp::Rejection> + Clone {
+    warp::multipart::form().max_length(MAX_DATA_BODY_SIZE)
+}
+
+/// The parts of a multipart form by name. The later parts of the same name are ignored.
+pub async fn read_multipart(form: FormData) -> Result<HashMap<String, Vec<u8>>, DataError> {
+    let parts: Vec<Part> = form
+        .try_collect()
+        .await
+        .map_err(|e| DataError::InvalidMultipart {
+            reason: e.to_string(),
+        })?;
+    let mut contents = HashMap::new();
+    for part in parts {
+        let name = part.name().to_string();
+        let content = part
+            .stream()
+            .try_fold(Vec::new(), |mut content, buf| async move {
+                content.extend_from_slice(buf.chunk());
+                Ok(content)
+            })
+            .await
+            .map_err(|e| DataError::InvalidMultipart {
+                reason: e.to_string(),
+            })?;
+        contents.entry(name).or_insert(content);
+    }
+    Ok(contents)
+}
+
+/// Take the part of the name out of the parts of a multipart form.
+pub fn take_part(parts: &mut HashMap<String, Vec<u8>>, name: &str) -> Result<Vec<u8>, DataError> {
+    parts.remove(name).ok_or_else(|| DataError::MissingPart {
+        name: name.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_base64() {
+        assert_eq!(
+            b"mousse".to_vec(),
+            decode_data("bW91c3Nl", DataEncoding::Base64).unwrap()
+        );
+        for invalid in ["bW91c3Nl!", "bW91c", "bW9 1c3Nl"].iter() {
+            assert!(matches!(
+                decode_data(invalid, DataEncoding::Base64),
+                Err(DataError::InvalidBase64 { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn decode_hex() {
+        assert_eq!(
+            b"mousse".to_vec(),
+            decode_data("6d6f75737365", DataEncoding::Hex).unwrap()
+        );
+        assert_eq!(
+            b"mousse".to_vec(),
+            decode_data("0x6d6f75737365", DataEncoding::Hex).unwrap()
+        );
+        // Invalid characters, odd length, and the prefix only once.
+        for invalid in ["6d6g", "6d6f7", "0x6d6f7", "0x0x6d6f", "bW91c3Nl"].iter() {
+            assert!(matches!(
+                decode_data(invalid, DataEncoding::Hex),
+                Err(DataError::InvalidHex { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn decode_empty_data() {
+        // Empty input is decoded to empty data, which is rejected by the bid validation.
+        assert!(decode_data("", DataEncoding::Base64).unwrap().is_empty());
+        assert!(decode_data("", DataEncoding::Hex).unwrap().is_empty());
+        assert!(decode_data("0x", DataEncoding::Hex).unwrap().is_empty());
+    }
+}
@@ -1,4 +1,6 @@
 pub use crate::*;
+use common::eth2_utils::compute_epoch_at_slot;
+use eth2_simulator::store::BlockSummary;
 
 /// GET /beacon/blocks
 pub fn beacon_blocks(
@@ -7,17 +9,17 @@ pub fn beacon_blocks(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::get()
         .and(warp::path!("beacon" / "blocks"))
-        .and(warp::query::<CountAndPageParams>())
+        .and(warp::query::<PaginationParams>())
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
         .and_then(get_beacon_blocks)
 }
 
 pub async fn get_beacon_blocks(
-    params: CountAndPageParams,
+    params: PaginationParams,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-) -> Result<impl warp::Reply, Infallible> {
+) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
         &mut request_logs,
@@ -27,24 +29,12 @@ pub async fn get_beacon_blocks(
         ),
     );
     let simulator = simulator.lock().await;
-    let count = params.count.unwrap_or(100);
-    let beacon_blocks = if simulator.beacon_chain.blocks.len() < count as usize {
-        simulator.beacon_chain.blocks.clone()
-    } else {
-        let page = params.page.unwrap_or(0);
-        let last_slot = simulator.beacon_chain.blocks.last().unwrap().slot;
-        simulator
-            .beacon_chain
-            .blocks
-            .iter()
-            .filter(|block| {
-                last_slot < block.slot + count * (page + 1) as Slot
-                    && block.slot + count * page as Slot <= last_slot
-            })
-            .cloned()
-            .collect::<Vec<_>>()
-    };
-    Ok(warp::reply::json(&beacon_blocks))
+    let page = params
+        .paginate(simulator.beacon_chain.block_summaries(), |summary| {
+            summary.slot
+        })
+        .map_err(invalid_pagination_params)?;
+    Ok(warp::reply::json(&load_blocks(&simulator, page)?))
 }
 
 /// GET /beacon/blocks/head
@@ -62,14 +52,89 @@ pub fn beacon_blocks_head(
 pub async fn get_beacon_blocks_head(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-) -> Result<impl warp::Reply, Infallible> {
+) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(&mut request_logs, String::from("GET /beacon/blocks/head"));
     let simulator = simulator.lock().await;
-    let head = simulator.beacon_chain.blocks.last();
+    let head = simulator
+        .beacon_chain
+        .get_head_block()
+        .map_err(store_error)?;
     Ok(warp::reply::json(&head))
 }
 
+/// GET /beacon/blocks/{slot|root}
+pub fn beacon_block(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("beacon" / "blocks" / String))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_beacon_block)
+}
+
+pub async fn get_beacon_block(
+    block_id: String,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!("GET /beacon/blocks/{}", block_id),
+    );
+    let id: BlockId = block_id.parse().map_err(id_parsing_error)?;
+    let simulator = simulator.lock().await;
+    match id.get_block(&simulator).map_err(store_error)? {
+        Some(block) => Ok(warp::reply::json(&block)),
+        None => Err(not_found_error(format!("block {}", block_id))),
+    }
+}
+
+#[derive(Serialize)]
+struct BeaconBlockHeaderWithRoot {
+    root: Root,
+    header: BeaconBlockHeader,
+}
+
+/// GET /beacon/headers/{slot|root}
+pub fn beacon_header(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("beacon" / "headers" / String))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_beacon_header)
+}
+
+pub async fn get_beacon_header(
+    block_id: String,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!("GET /beacon/headers/{}", block_id),
+    );
+    let id: BlockId = block_id.parse().map_err(id_parsing_error)?;
+    let simulator = simulator.lock().await;
+    match id.get_block(&simulator).map_err(store_error)? {
+        Some(block) => {
+            let header = block.header();
+            Ok(warp::reply::json(&BeaconBlockHeaderWithRoot {
+                root: header.root(),
+                header,
+            }))
+        }
+        None => Err(not_found_error(format!("block {}", block_id))),
+    }
+}
+
 /// GET /beacon/finalized_blocks
 pub fn beacon_finalized_blocks(
     simulator: SharedSimulator,
@@ -77,17 +142,17 @@ pub fn beacon_finalized_blocks(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::get()
         .and(warp::path!("beacon" / "finalized_blocks"))
-        .and(warp::query::<CountAndPageParams>())
+        .and(warp::query::<PaginationParams>())
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
         .and_then(get_beacon_finalized_blocks)
 }
 
 pub async fn get_beacon_finalized_blocks(
-    params: CountAndPageParams,
+    params: PaginationParams,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-) -> Result<impl warp::Reply, Infallible> {
+) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
         &mut request_logs,
@@ -97,23 +162,34 @@ pub async fn get_beacon_finalized_blocks(
         ),
     );
     let simulator = simulator.lock().await;
-    let count = params.count.unwrap_or(100);
-    let beacon_blocks = simulator.beacon_chain.get_finalized_blocks();
-    let beacon_blocks = if beacon_blocks.len() < count as usize {
-        beacon_blocks.clone()
-    } else {
-        let page = params.page.unwrap_or(0);
-        let last_slot = beacon_blocks.last().unwrap().slot;
-        beacon_blocks
-            .iter()
-            .filter(|block| {
-                last_slot < block.slot + count * (page + 1) as Slot
-                    && block.slot + count * page as Slot <= last_slot
-            })
-            .cloned()
-            .collect::<Vec<_>>()
-    };
-    Ok(warp::reply::json(&beacon_blocks))
+    let page = params
+        .paginate(
+            simulator.beacon_chain.finalized_block_summaries(),
+            |summary| summary.slot,
+        )
+        .map_err(invalid_pagination_params)?;
+    Ok(warp::reply::json(&load_blocks(&simulator, page)?))
+}
+
+/// Load the blocks of the summaries in the page from the store.
+fn load_blocks(
+    simulator: &Simulator,
+    page: Page<&BlockSummary>,
+) -> Result<Page<BeaconBlock>, warp::Rejection> {
+    let mut blocks = Vec::new();
+    for summary in page.data {
+        if let Some(block) = simulator
+            .beacon_chain
+            .get_block_by_slot(summary.slot)
+            .map_err(store_error)?
+        {
+            blocks.push(block);
+        }
+    }
+    Ok(Page {
+        data: blocks,
+        next: page.next,
+    })
 }
 
 /// GET /beacon/states
@@ -123,17 +199,17 @@ pub fn beacon_states(
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::get()
         .and(warp::path!("beacon" / "states"))
-        .and(warp::query::<CountAndPageParams>())
+        .and(warp::query::<PaginationParams>())
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
         .and_then(get_beacon_states)
 }
 
 pub async fn get_beacon_states(
-    params: CountAndPageParams,
+    params: PaginationParams,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-) -> Result<impl warp::Reply, Infallible> {
+) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
         &mut request_logs,
@@ -143,24 +219,58 @@ pub async fn get_beacon_states(
         ),
     );
     let simulator = simulator.lock().await;
-    let count = params.count.unwrap_or(100);
-    let beacon_states = if simulator.beacon_chain.states.len() < count as usize {
-        simulator.beacon_chain.states.clone()
-    } else {
-        let page = params.page.unwrap_or(0);
-        let last_slot = simulator.beacon_chain.states.last().unwrap().slot;
-        simulator
+    // Paginate the blocks and then load the states at the blocks in the page.
+    // Note: The pruned states are skipped, so the page can have less than `limit` states.
+    let page = params
+        .paginate(simulator.beacon_chain.block_summaries(), |summary| {
+            summary.slot
+        })
+        .map_err(invalid_pagination_params)?;
+    let mut beacon_states = Vec::new();
+    for summary in page.data {
+        if let Some(state) = simulator
             .beacon_chain
-            .states
-            .iter()
-            .filter(|state| {
-                last_slot < state.slot + count * (page + 1) as Slot
-                    && state.slot + count * page as Slot <= last_slot
-            })
-            .cloned()
-            .collect::<Vec<_>>()
-    };
-    Ok(warp::reply::json(&beacon_states))
+            .get_state(summary.slot)
+            .map_err(store_error)?
+        {
+            beacon_states.push(state);
+        }
+    }
+    Ok(warp::reply::json(&Page {
+        data: beacon_states,
+        next: page.next,
+    }))
+}
+
+/// GET /beacon/states/{slot|root}
+/// The root is the state root.
+pub fn beacon_state(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("beacon" / "states" / String))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_beacon_state)
+}
+
+pub async fn get_beacon_state(
+    state_id: String,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!("GET /beacon/states/{}", state_id),
+    );
+    let id: StateId = state_id.parse().map_err(id_parsing_error)?;
+    let simulator = simulator.lock().await;
+    match id.get_state(&simulator).map_err(store_error)? {
+        Some(state) => Ok(warp::reply::json(&state)),
+        None => Err(not_found_error(format!("state {}", state_id))),
+    }
 }
 
 /// GET /beacon/finalized_checkpoint
@@ -188,3 +298,276 @@ pub async fn get_finalized_checkpoint(
     let finalized_checkpoint = simulator.beacon_chain.finalized_checkpoint.clone();
     Ok(warp::reply::json(&finalized_checkpoint))
 }
+
+/// GET /beacon/justified_checkpoint
+pub fn beacon_justified_checkpoint(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("beacon" / "justified_checkpoint"))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_justified_checkpoint)
+}
+
+pub async fn get_justified_checkpoint(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        String::from("GET /beacon/justified_checkpoint"),
+    );
+    let simulator = simulator.lock().await;
+    let justified_checkpoint = simulator.beacon_chain.justified_checkpoint.clone();
+    Ok(warp::reply::json(&justified_checkpoint))
+}
+
+#[derive(Serialize)]
+struct FinalityStatus {
+    current_epoch: Epoch,
+    justified_checkpoint: Checkpoint,
+    finalized_checkpoint: Checkpoint,
+    /// The number of the epochs from the finalized checkpoint to the previous epoch in the state.
+    epochs_since_finality: Epoch,
+    inactivity_leak: bool,
+}
+
+/// GET /beacon/finality_status
+/// The offline validators are penalized while `inactivity_leak` is true.
+pub fn beacon_finality_status(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("beacon" / "finality_status"))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_finality_status)
+}
+
+pub async fn get_finality_status(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        String::from("GET /beacon/finality_status"),
+    );
+    let simulator = simulator.lock().await;
+    let state = &simulator.beacon_chain.state;
+    Ok(warp::reply::json(&FinalityStatus {
+        current_epoch: state.get_current_epoch(),
+        justified_checkpoint: simulator.beacon_chain.justified_checkpoint.clone(),
+        finalized_checkpoint: simulator.beacon_chain.finalized_checkpoint.clone(),
+        epochs_since_finality: state.get_finality_delay(),
+        inactivity_leak: state.is_in_inactivity_leak(),
+    }))
+}
+
+#[derive(Serialize)]
+struct HeaderPool<'a> {
+    previous_epoch: &'a [SignedShardHeader],
+    current_epoch: &'a [SignedShardHeader],
+}
+
+/// GET /beacon/header_pool
+/// Shard headers published but not included in the beacon chain yet.
+pub fn beacon_header_pool(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("beacon" / "header_pool"))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_header_pool)
+}
+
+pub async fn get_header_pool(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut request_logs = request_logs.lock().await;
+    log(&mut request_logs, String::from("GET /beacon/header_pool"));
+    let simulator = simulator.lock().await;
+    Ok(warp::reply::json(&HeaderPool {
+        previous_epoch: &simulator.beacon_chain.previous_epoch_shard_header_pool,
+        current_epoch: &simulator.beacon_chain.current_epoch_shard_header_pool,
+    }))
+}
+
+#[derive(Serialize)]
+struct ValidatorResponse<'a> {
+    index: ValidatorIndex,
+    balance: Gwei,
+    status: ValidatorStatus,
+    validator: &'a Validator,
+}
+
+/// GET /beacon/validators
+/// The validators in the registry of the head state with their status at the current epoch.
+pub fn beacon_validators(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("beacon" / "validators"))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_validators)
+}
+
+pub async fn get_validators(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut request_logs = request_logs.lock().await;
+    log(&mut request_logs, String::from("GET /beacon/validators"));
+    let simulator = simulator.lock().await;
+    let state = &simulator.beacon_chain.state;
+    let epoch = compute_epoch_at_slot(simulator.beacon_chain.slot);
+    let validators: Vec<ValidatorResponse> = state
+        .validators
+        .iter()
+        .zip(state.balances.iter())
+        .enumerate()
+        .map(|(index, (validator, balance))| ValidatorResponse {
+            index: index as ValidatorIndex,
+            balance: *balance,
+            status: validator.status(epoch),
+            validator,
+        })
+        .collect();
+    Ok(warp::reply::json(&validators))
+}
+
+/// POST /beacon/deposits
+/// The deposit is processed in the next beacon block.
+/// $ curl -X POST -d '{"pubkey":[0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1],"amount":32000000000}' -H 'Content-Type: application/json' http://localhost:3030/beacon/deposits
+pub fn beacon_deposits(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("beacon" / "deposits"))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(process_deposit)
+}
+
+pub async fn process_deposit(
+    deposit: Deposit,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(&mut request_logs, String::from("POST /beacon/deposits"));
+    let mut simulator = simulator.lock().await;
+    match simulator.process_deposit(deposit) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(deposit_error(e)),
+    }
+}
+
+/// POST /beacon/voluntary_exits
+/// The voluntary exit is processed in a beacon block from its epoch.
+/// $ curl -X POST -d '{"epoch":0,"validator_index":0}' -H 'Content-Type: application/json' http://localhost:3030/beacon/voluntary_exits
+pub fn beacon_voluntary_exits(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("beacon" / "voluntary_exits"))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(submit_voluntary_exit)
+}
+
+pub async fn submit_voluntary_exit(
+    exit: VoluntaryExit,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        String::from("POST /beacon/voluntary_exits"),
+    );
+    let mut simulator = simulator.lock().await;
+    match simulator.submit_voluntary_exit(exit) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(voluntary_exit_error(e)),
+    }
+}
+
+/// POST /beacon/proposer_slashings
+/// The proposer slashing is processed in the next beacon block.
+pub fn beacon_proposer_slashings(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("beacon" / "proposer_slashings"))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(submit_proposer_slashing)
+}
+
+pub async fn submit_proposer_slashing(
+    slashing: ProposerSlashing,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        String::from("POST /beacon/proposer_slashings"),
+    );
+    let mut simulator = simulator.lock().await;
+    match simulator.submit_proposer_slashing(slashing) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(slashing_error(e)),
+    }
+}
+
+/// POST /beacon/attester_slashings
+/// The attester slashing is processed in the next beacon block.
+pub fn beacon_attester_slashings(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("beacon" / "attester_slashings"))
+        .and(warp::body::content_length_limit(1024 * 64))
+        .and(warp::body::json())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(submit_attester_slashing)
+}
+
+pub async fn submit_attester_slashing(
+    slashing: AttesterSlashing,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        String::from("POST /beacon/attester_slashings"),
+    );
+    let mut simulator = simulator.lock().await;
+    match simulator.submit_attester_slashing(slashing) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(slashing_error(e)),
+    }
+}
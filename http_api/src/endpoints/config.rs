@@ -1,37 +1,63 @@
 pub use crate::*;
 
+#[derive(Serialize)]
+struct ConfigResponse {
+    #[serde(flatten)]
+    config: Config,
+    /// The offset in seconds from the start of a slot when the bid window for the next slot closes.
+    bid_deadline: Option<f64>,
+    propagation_model: PropagationModel,
+}
+
 /// GET /config
 pub fn config_get(
+    simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
     config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::get()
         .and(warp::path!("config"))
+        .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
         .and(with_config(config))
         .and_then(get_config)
 }
 
 pub async fn get_config(
+    simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
     config: SharedConfig,
 ) -> Result<impl warp::Reply, Infallible> {
     let mut request_logs = request_logs.lock().await;
     log(&mut request_logs, String::from("GET /config"));
+    let simulator = simulator.lock().await;
     let config = config.lock().await;
-    Ok(warp::reply::json(&config.config))
+    Ok(warp::reply::json(&ConfigResponse {
+        config: config.clone(),
+        bid_deadline: simulator
+            .bid_deadline_ms
+            .map(|bid_deadline_ms| bid_deadline_ms as f64 / MILLIS_PER_SECOND as f64),
+        propagation_model: simulator.propagation_model().clone(),
+    }))
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ConfigOptions {
     auto: Option<bool>,
-    slot_time: Option<u64>,
+    slot_time: Option<f64>,
     failure_rate: Option<f32>,
+    bid_deadline: Option<f64>,
+    failure_model: Option<FailureModel>,
+    propagation_model: Option<PropagationModel>,
 }
 
 /// POST /config
-/// $ curl -X POST -d '{"auto":true, "slot_time":1,"failure_rate":0}' -H 'Content-Type: application/json' http://localhost:3030/config
+/// $ curl -X POST -d '{"auto":true, "slot_time":0.25,"failure_rate":0}' -H 'Content-Type: application/json' http://localhost:3030/config
+/// $ curl -X POST -d '{"bid_deadline":8}' -H 'Content-Type: application/json' http://localhost:3030/config
+/// $ curl -X POST -d '{"propagation_model":{"shard_subnet":{"latency":{"type":"exponential","min_ms":100,"mean_extra_ms":300},"drop_rate":0.01}}}' -H 'Content-Type: application/json' http://localhost:3030/config
+/// $ curl -X POST -d '{"failure_model":{"shard":{"blob_proposal":0.1},"outages":[{"failure":{"type":"no_chain_finality"},"start_rate":0.05,"min_epochs":3,"max_epochs":10}]}}' -H 'Content-Type: application/json' http://localhost:3030/config
 pub fn config_set(
+    simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
     config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
@@ -39,39 +65,87 @@ pub fn config_set(
         .and(warp::path!("config"))
         .and(warp::body::content_length_limit(1024 * 1024))
         .and(warp::body::json())
+        .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
         .and(with_config(config))
         .and_then(set_config)
 }
 
-/// Note: Auto processing restarts when new config is set.
+/// Note: The slot clock is re-anchored so that the next slot starts now, when the slot time is changed
+/// or the auto mode is enabled after the clock has passed the next slot.
+/// The genesis time changes, which is notified as the `slot_clock` event.
 pub async fn set_config(
     config_options: ConfigOptions,
+    simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
     config: SharedConfig,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(&mut request_logs, String::from("POST /config"));
 
+    let mut simulator = simulator.lock().await;
     let mut config = config.lock().await;
+    let mut new_config = config.clone();
     if let Some(auto) = config_options.auto {
-        config.config.auto = auto;
+        new_config.auto = auto;
     }
     if let Some(slot_time) = config_options.slot_time {
-        config.config.slot_time = slot_time;
+        new_config.slot_time = slot_time;
     }
+    let slot_duration_ms = new_config.slot_duration_ms().map_err(config_set_error)?;
     if let Some(failure_rate) = config_options.failure_rate {
-        config.config.failure_rate = failure_rate;
         if !(0.0..=1.0).contains(&failure_rate) {
             return Err(config_set_error(ConfigError::InvalidFailureRate {
                 found: failure_rate,
             }));
         }
     }
-    if config.config.auto {
-        // Reset these time variables when new config is set.
-        config.restart_auto();
+    let bid_deadline_ms = match config_options.bid_deadline {
+        Some(bid_deadline) => {
+            let bid_deadline_ms = (bid_deadline * MILLIS_PER_SECOND as f64).round();
+            if bid_deadline_ms.is_nan() || bid_deadline_ms < 0.0 {
+                return Err(config_set_error(ConfigError::InvalidBidDeadline {
+                    found: bid_deadline,
+                }));
+            }
+            Some(bid_deadline_ms as u64)
+        }
+        None => simulator.bid_deadline_ms,
+    };
+    if let Some(failure_model) = config_options.failure_model {
+        failure_model
+            .validate()
+            .map_err(|e| config_set_error(ConfigError::InvalidFailureModel(e)))?;
+        new_config.failure_model = failure_model;
+    }
+    // `failure_rate` is an alias of `preset` of the failure model.
+    if let Some(failure_rate) = config_options.failure_rate {
+        new_config.failure_model.preset = failure_rate as f64;
     }
+    new_config.failure_rate = new_config.failure_model.preset as f32;
+    if let Some(propagation_model) = config_options.propagation_model {
+        simulator
+            .set_propagation_model(propagation_model)
+            .map_err(|e| config_set_error(ConfigError::InvalidPropagationModel(e)))?;
+    }
+
+    if new_config.failure_model != config.failure_model {
+        simulator.end_outages();
+    }
+    simulator.bid_deadline_ms = bid_deadline_ms;
 
+    let now_ms = simulator.now_ms();
+    // The auto mode would process the slots missed by the clock at once.
+    let missed_slots = simulator
+        .slot_clock
+        .slot_at(now_ms)
+        .map_or(false, |current_slot| current_slot > simulator.slot);
+    if (new_config.auto && !config.auto && missed_slots)
+        || slot_duration_ms != simulator.slot_clock.slot_duration_ms
+    {
+        let slot_clock = SlotClock::starting_at(simulator.slot, now_ms, slot_duration_ms);
+        simulator.set_slot_clock(slot_clock).map_err(store_error)?;
+    }
+    *config = new_config;
     Ok(StatusCode::OK)
 }
@@ -0,0 +1,93 @@
+pub use crate::*;
+use eth2_simulator::data_submission::{Manifest, Placement};
+
+/// The maximum size of the JSON body of a data submission.
+pub const MAX_DATA_SUBMISSION_BODY_SIZE: u64 = 16 * 1024 * 1024;
+
+#[derive(Deserialize)]
+pub struct DataSubmission {
+    data: String,
+    #[serde(default)]
+    encoding: DataEncoding,
+    fee: Gwei,
+    /// The shards of the pieces. Default: all the shards.
+    shards: Option<Vec<Shard>>,
+    /// The slots of the pieces. Default: the consecutive slots from the next slot.
+    slots: Option<Vec<Slot>>,
+}
+
+/// POST /data/submit
+/// Split the data into blob-sized pieces and publish their bids. Return the manifest of the pieces.
+/// The fee of each bid must be at least the shard gasprice.
+/// $ curl -X POST -d '{"data":"bW91c3Nl","fee":8,"shards":[0,1]}' -H 'Content-Type: application/json' http://localhost:3030/data/submit
+pub fn data_submit(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("data" / "submit"))
+        .and(warp::body::content_length_limit(
+            MAX_DATA_SUBMISSION_BODY_SIZE,
+        ))
+        .and(warp::body::json())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(submit_data)
+}
+
+pub async fn submit_data(
+    submission: DataSubmission,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(&mut request_logs, String::from("POST /data/submit"));
+    let data = decode_data(&submission.data, submission.encoding).map_err(data_decoding_error)?;
+    let mut simulator = simulator.lock().await;
+    let shards = submission
+        .shards
+        .unwrap_or_else(|| (0..SHARD_NUM as Shard).collect());
+    let placement = match submission.slots {
+        Some(slots) => Placement {
+            shards,
+            slots,
+            fee: submission.fee,
+        },
+        None => Placement::consecutive(shards, simulator.slot, data.len(), submission.fee),
+    };
+    match simulator.submit_data(&data, &placement) {
+        Ok(manifest) => Ok(warp::reply::json(&manifest)),
+        Err(e) => Err(data_submission_error(e)),
+    }
+}
+
+/// POST /data/reassemble
+/// Reassemble the data of the manifest returned by `/data/submit`, whose pieces must all be confirmed.
+/// The data is returned as `application/octet-stream`.
+/// $ curl -X POST -d @manifest.json -H 'Content-Type: application/json' http://localhost:3030/data/reassemble
+pub fn data_reassemble(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("data" / "reassemble"))
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(reassemble_data)
+}
+
+pub async fn reassemble_data(
+    manifest: Manifest,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(&mut request_logs, String::from("POST /data/reassemble"));
+    let simulator = simulator.lock().await;
+    match simulator.reassemble_data(&manifest) {
+        Ok(data) => Ok(data),
+        Err(e) => Err(data_reassembly_error(e)),
+    }
+}
@@ -0,0 +1,370 @@
+//! Compatibility layer of the standard Beacon Node API.
+//! Ref: https://ethereum.github.io/eth2.0-APIs/
+//! Numbers are encoded as decimal strings as in the standard API.
+pub use crate::*;
+use common::eth_api::*;
+use eth2_simulator::store::BlockSummary;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Block identifier in the path of the standard API.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockId {
+    Head,
+    Genesis,
+    Finalized,
+    Slot(Slot),
+    Root(Root),
+}
+
+impl FromStr for BlockId {
+    type Err = IdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "head" => Ok(BlockId::Head),
+            "genesis" => Ok(BlockId::Genesis),
+            "finalized" => Ok(BlockId::Finalized),
+            _ => {
+                if let Some(hex) = s.strip_prefix("0x") {
+                    match Root::from_str(hex) {
+                        Ok(root) if hex.len() == 64 => Ok(BlockId::Root(root)),
+                        _ => Err(IdError::InvalidBlockId {
+                            found: s.to_string(),
+                        }),
+                    }
+                } else {
+                    s.parse()
+                        .map(BlockId::Slot)
+                        .map_err(|_| IdError::InvalidBlockId {
+                            found: s.to_string(),
+                        })
+                }
+            }
+        }
+    }
+}
+
+impl BlockId {
+    /// Get the summary of the block of the identifier in the main chain.
+    pub fn get_summary<'a>(&self, simulator: &'a Simulator) -> Option<&'a BlockSummary> {
+        let beacon_chain = &simulator.beacon_chain;
+        match self {
+            BlockId::Head => beacon_chain.block_summaries().last(),
+            BlockId::Genesis => beacon_chain.get_block_summary_by_slot(GENESIS_SLOT),
+            BlockId::Finalized => {
+                // Regard the genesis block as finalized if no checkpoint is finalized.
+                if beacon_chain.finalized_checkpoint == Checkpoint::genesis_finalized_checkpoint() {
+                    beacon_chain.get_block_summary_by_slot(GENESIS_SLOT)
+                } else {
+                    beacon_chain.get_block_summary_by_root(&beacon_chain.finalized_checkpoint.root)
+                }
+            }
+            BlockId::Slot(slot) => beacon_chain.get_block_summary_by_slot(*slot),
+            BlockId::Root(root) => beacon_chain.get_block_summary_by_root(root),
+        }
+    }
+
+    /// Get the block of the identifier in the main chain.
+    pub fn get_block(
+        &self,
+        simulator: &Simulator,
+    ) -> Result<Option<BeaconBlock>, simulator::StoreError> {
+        match self.get_summary(simulator) {
+            Some(summary) => simulator.beacon_chain.get_block_by_slot(summary.slot),
+            None => Ok(None),
+        }
+    }
+}
+
+/// State identifier in the path of the standard API.
+/// The identifiers other than a state root are resolved to the state at the block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StateId {
+    Block(BlockId),
+    Root(Root),
+}
+
+impl FromStr for StateId {
+    type Err = IdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(BlockId::Root(root)) => Ok(StateId::Root(root)),
+            Ok(block_id) => Ok(StateId::Block(block_id)),
+            Err(_) => Err(IdError::InvalidStateId {
+                found: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl StateId {
+    /// Get the slot of the block whose state is identified.
+    pub fn get_slot(&self, simulator: &Simulator) -> Option<Slot> {
+        match self {
+            StateId::Block(block_id) => block_id.get_summary(simulator).map(|summary| summary.slot),
+            StateId::Root(root) => simulator
+                .beacon_chain
+                .get_block_summary_by_state_root(root)
+                .map(|summary| summary.slot),
+        }
+    }
+
+    /// Get the identified state.
+    /// Return `None` if there is no such state or the state is already pruned.
+    pub fn get_state(
+        &self,
+        simulator: &Simulator,
+    ) -> Result<Option<BeaconState>, simulator::StoreError> {
+        match self.get_slot(simulator) {
+            Some(slot) => simulator.beacon_chain.get_state(slot),
+            None => Ok(None),
+        }
+    }
+}
+
+fn dummy_signature() -> String {
+    format!("0x{}", "00".repeat(96))
+}
+
+/// GET /eth/v1/beacon/genesis
+pub fn eth_beacon_genesis(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("eth" / "v1" / "beacon" / "genesis"))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_eth_beacon_genesis)
+}
+
+pub async fn get_eth_beacon_genesis(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        String::from("GET /eth/v1/beacon/genesis"),
+    );
+    let simulator = simulator.lock().await;
+    // Note: The validators have no withdrawal credentials and no real keys, so the root is zero.
+    Ok(warp::reply::json(&Data {
+        data: Genesis {
+            genesis_time: simulator.slot_clock.genesis_time().to_string(),
+            genesis_validators_root: Root::zero(),
+            genesis_fork_version: String::from("0x00000000"),
+        },
+    }))
+}
+
+/// GET /eth/v1/beacon/headers/{block_id}
+pub fn eth_beacon_headers(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("eth" / "v1" / "beacon" / "headers" / String))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_eth_beacon_header)
+}
+
+pub async fn get_eth_beacon_header(
+    block_id: String,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!("GET /eth/v1/beacon/headers/{}", block_id),
+    );
+    let id: BlockId = block_id.parse().map_err(id_parsing_error)?;
+    let simulator = simulator.lock().await;
+    let header = match id.get_block(&simulator).map_err(store_error)? {
+        Some(block) => block.header(),
+        None => return Err(not_found_error(format!("block {}", block_id))),
+    };
+    Ok(warp::reply::json(&Data {
+        data: BlockHeaderResponse {
+            root: header.root(),
+            // Note: There are no forks in the simulation.
+            canonical: true,
+            header: SignedBeaconBlockHeaderMessage {
+                message: BeaconBlockHeaderMessage {
+                    slot: header.slot.to_string(),
+                    proposer_index: String::from("0"),
+                    parent_root: header.parent_root,
+                    state_root: header.state_root,
+                    body_root: header.body_root,
+                },
+                signature: dummy_signature(),
+            },
+        },
+    }))
+}
+
+/// GET /eth/v2/beacon/blocks/{block_id}
+pub fn eth_beacon_blocks(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("eth" / "v2" / "beacon" / "blocks" / String))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_eth_beacon_block)
+}
+
+pub async fn get_eth_beacon_block(
+    block_id: String,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!("GET /eth/v2/beacon/blocks/{}", block_id),
+    );
+    let id: BlockId = block_id.parse().map_err(id_parsing_error)?;
+    let simulator = simulator.lock().await;
+    let block = match id.get_block(&simulator).map_err(store_error)? {
+        Some(block) => block,
+        None => return Err(not_found_error(format!("block {}", block_id))),
+    };
+    Ok(warp::reply::json(&BlockResponse {
+        version: String::from("sharding"),
+        data: SignedBeaconBlockMessage {
+            message: BeaconBlockMessage {
+                slot: block.slot.to_string(),
+                proposer_index: String::from("0"),
+                parent_root: block.parent_root,
+                state_root: block.state_root,
+                body: BeaconBlockBodyMessage {
+                    shard_headers: block.shard_headers.to_vec(),
+                },
+            },
+            signature: dummy_signature(),
+        },
+    }))
+}
+
+/// GET /eth/v1/beacon/states/{state_id}/finality_checkpoints
+pub fn eth_beacon_states_finality_checkpoints(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!(
+            "eth" / "v1" / "beacon" / "states" / String / "finality_checkpoints"
+        ))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_eth_beacon_state_finality_checkpoints)
+}
+
+pub async fn get_eth_beacon_state_finality_checkpoints(
+    state_id: String,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!(
+            "GET /eth/v1/beacon/states/{}/finality_checkpoints",
+            state_id
+        ),
+    );
+    let id: StateId = state_id.parse().map_err(id_parsing_error)?;
+    let simulator = simulator.lock().await;
+    let state = match id.get_state(&simulator).map_err(store_error)? {
+        Some(state) => state,
+        None => return Err(not_found_error(format!("state {}", state_id))),
+    };
+    Ok(warp::reply::json(&Data {
+        data: FinalityCheckpoints {
+            previous_justified: (&state.previous_justified_checkpoint).into(),
+            current_justified: (&state.current_justified_checkpoint).into(),
+            finalized: (&state.finalized_checkpoint).into(),
+        },
+    }))
+}
+
+/// GET /eth/v1/node/version
+pub fn eth_node_version(
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("eth" / "v1" / "node" / "version"))
+        .and(with_request_logs(request_logs))
+        .and_then(get_eth_node_version)
+}
+
+pub async fn get_eth_node_version(
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut request_logs = request_logs.lock().await;
+    log(&mut request_logs, String::from("GET /eth/v1/node/version"));
+    Ok(warp::reply::json(&Data {
+        data: Version {
+            version: format!("Mousse/v{}", env!("CARGO_PKG_VERSION")),
+        },
+    }))
+}
+
+/// GET /eth/v1/node/syncing
+pub fn eth_node_syncing(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("eth" / "v1" / "node" / "syncing"))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_eth_node_syncing)
+}
+
+pub async fn get_eth_node_syncing(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut request_logs = request_logs.lock().await;
+    log(&mut request_logs, String::from("GET /eth/v1/node/syncing"));
+    let simulator = simulator.lock().await;
+    let head_slot = simulator
+        .beacon_chain
+        .block_summaries()
+        .last()
+        .map_or(GENESIS_SLOT, |summary| summary.slot);
+    // The simulator always has the whole chain.
+    Ok(warp::reply::json(&Data {
+        data: Syncing {
+            head_slot: head_slot.to_string(),
+            sync_distance: String::from("0"),
+            is_syncing: false,
+        },
+    }))
+}
+
+/// GET /eth/v1/config/spec
+pub fn eth_config_spec(
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("eth" / "v1" / "config" / "spec"))
+        .and(with_request_logs(request_logs))
+        .and_then(get_eth_config_spec)
+}
+
+pub async fn get_eth_config_spec(
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut request_logs = request_logs.lock().await;
+    log(&mut request_logs, String::from("GET /eth/v1/config/spec"));
+    let spec: BTreeMap<&'static str, String> = spec();
+    Ok(warp::reply::json(&Data { data: spec }))
+}
@@ -0,0 +1,24 @@
+pub use crate::*;
+use eth1_simulator::rpc::{handle_request, JsonRpcRequest};
+
+/// POST / on the Eth1 JSON-RPC port
+/// JSON-RPC interface of the simulated Eth1 chain.
+pub fn eth1_json_rpc(
+    eth1_chain: SharedEth1Chain,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path::end())
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_eth1_chain(eth1_chain))
+        .and_then(eth1_json_rpc_handler)
+}
+
+pub async fn eth1_json_rpc_handler(
+    request: JsonRpcRequest,
+    eth1_chain: SharedEth1Chain,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut eth1_chain = eth1_chain.lock().await;
+    let response = handle_request(&mut eth1_chain, request);
+    Ok(warp::reply::json(&response))
+}
@@ -0,0 +1,215 @@
+pub use crate::*;
+use futures::StreamExt;
+use simulator::Event;
+use std::str::FromStr;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use warp::sse;
+
+/// Topics of the event stream.
+/// Ref: https://ethereum.github.io/eth2.0-APIs/#/Events/eventstream
+#[derive(Clone, Copy, PartialEq)]
+pub enum Topic {
+    Head,
+    Block,
+    JustifiedCheckpoint,
+    FinalizedCheckpoint,
+    ProposerSlashing,
+    AttesterSlashing,
+    ShardHeader,
+    ConfirmedShardHeader,
+    Bid,
+    MissedBid,
+    SlotClock,
+}
+
+impl Topic {
+    fn name(&self) -> &'static str {
+        match self {
+            Topic::Head => "head",
+            Topic::Block => "block",
+            Topic::JustifiedCheckpoint => "justified_checkpoint",
+            Topic::FinalizedCheckpoint => "finalized_checkpoint",
+            Topic::ProposerSlashing => "proposer_slashing",
+            Topic::AttesterSlashing => "attester_slashing",
+            Topic::ShardHeader => "shard_header",
+            Topic::ConfirmedShardHeader => "confirmed_shard_header",
+            Topic::Bid => "bid",
+            Topic::MissedBid => "missed_bid",
+            Topic::SlotClock => "slot_clock",
+        }
+    }
+}
+
+impl FromStr for Topic {
+    type Err = EventsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "head" => Ok(Topic::Head),
+            "block" => Ok(Topic::Block),
+            "justified_checkpoint" => Ok(Topic::JustifiedCheckpoint),
+            "finalized_checkpoint" => Ok(Topic::FinalizedCheckpoint),
+            "proposer_slashing" => Ok(Topic::ProposerSlashing),
+            "attester_slashing" => Ok(Topic::AttesterSlashing),
+            "shard_header" => Ok(Topic::ShardHeader),
+            "confirmed_shard_header" => Ok(Topic::ConfirmedShardHeader),
+            "bid" => Ok(Topic::Bid),
+            "missed_bid" => Ok(Topic::MissedBid),
+            "slot_clock" => Ok(Topic::SlotClock),
+            _ => Err(EventsError::UnknownTopic {
+                found: s.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HeadEvent {
+    slot: Slot,
+    block: Root,
+    state: Root,
+}
+
+#[derive(Serialize)]
+struct BlockEvent {
+    slot: Slot,
+    block: Root,
+}
+
+/// The event of the justified or finalized checkpoint.
+#[derive(Serialize)]
+struct CheckpointEvent {
+    block: Root,
+    epoch: Epoch,
+}
+
+/// The event of the changed slot clock.
+#[derive(Serialize)]
+struct SlotClockEvent {
+    /// Unix time of the genesis in seconds.
+    genesis_time: u64,
+    slot_time_ms: u64,
+}
+
+/// The event of the skipped events when the subscriber lags behind.
+#[derive(Serialize)]
+struct LaggedEvent {
+    skipped: u64,
+}
+
+/// Convert a simulation event to the server-sent events of the topics.
+fn to_sse_events(event: &Event, topics: &[Topic]) -> Vec<Result<sse::Event, warp::Error>> {
+    let mut sse_events = Vec::new();
+    for topic in topics.iter() {
+        let sse_event = sse::Event::default().event(topic.name());
+        match (topic, event) {
+            (Topic::Head, Event::BlockAppended { root, block }) => {
+                sse_events.push(sse_event.json_data(&HeadEvent {
+                    slot: block.slot,
+                    block: *root,
+                    state: block.state_root,
+                }));
+            }
+            (Topic::Block, Event::BlockAppended { root, block }) => {
+                sse_events.push(sse_event.json_data(&BlockEvent {
+                    slot: block.slot,
+                    block: *root,
+                }));
+            }
+            (Topic::JustifiedCheckpoint, Event::CheckpointJustified { checkpoint }) => {
+                sse_events.push(sse_event.json_data(&CheckpointEvent {
+                    block: checkpoint.root,
+                    epoch: checkpoint.epoch,
+                }));
+            }
+            (Topic::FinalizedCheckpoint, Event::CheckpointFinalized { checkpoint }) => {
+                sse_events.push(sse_event.json_data(&CheckpointEvent {
+                    block: checkpoint.root,
+                    epoch: checkpoint.epoch,
+                }));
+            }
+            (Topic::ProposerSlashing, Event::ProposerSlashingProcessed { slashing }) => {
+                sse_events.push(sse_event.json_data(slashing));
+            }
+            (Topic::AttesterSlashing, Event::AttesterSlashingProcessed { slashing }) => {
+                sse_events.push(sse_event.json_data(slashing));
+            }
+            (Topic::ShardHeader, Event::ShardHeaderPublished { header }) => {
+                sse_events.push(sse_event.json_data(header));
+            }
+            (Topic::ConfirmedShardHeader, Event::ShardHeaderConfirmed { header }) => {
+                sse_events.push(sse_event.json_data(header));
+            }
+            (Topic::Bid, Event::BidSelected { bid }) => {
+                sse_events.push(sse_event.json_data(bid));
+            }
+            (Topic::MissedBid, Event::BidMissed { bid }) => {
+                sse_events.push(sse_event.json_data(bid));
+            }
+            (Topic::SlotClock, Event::SlotClockChanged { current, .. }) => {
+                sse_events.push(sse_event.json_data(&SlotClockEvent {
+                    genesis_time: current.genesis_time(),
+                    slot_time_ms: current.slot_duration_ms,
+                }));
+            }
+            _ => (),
+        }
+    }
+    sse_events
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EventsParams {
+    topics: String,
+}
+
+/// GET /events?topics={topics}
+/// $ curl -N http://localhost:3030/events?topics=head,finalized_checkpoint
+pub fn events(
+    event_sender: EventSender,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("events"))
+        .and(warp::query::<EventsParams>())
+        .and(with_event_sender(event_sender))
+        .and(with_request_logs(request_logs))
+        .and_then(subscribe_events)
+}
+
+pub async fn subscribe_events(
+    params: EventsParams,
+    event_sender: EventSender,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!("GET /events?topics={}", params.topics),
+    );
+    let mut topics: Vec<Topic> = Vec::new();
+    for topic in params.topics.split(',') {
+        let topic = topic.parse().map_err(events_subscription_error)?;
+        if !topics.contains(&topic) {
+            topics.push(topic);
+        }
+    }
+
+    // Note: Events are skipped if the subscriber lags behind, which is notified as the `lagged` event.
+    let stream = BroadcastStream::new(event_sender.subscribe()).flat_map(move |event| {
+        let sse_events = match event {
+            Ok(event) => to_sse_events(&event, &topics),
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => vec![sse::Event::default()
+                .event("lagged")
+                .json_data(&LaggedEvent { skipped })],
+        };
+        futures::stream::iter(
+            sse_events
+                .into_iter()
+                .filter_map(|sse_event| sse_event.ok()),
+        )
+        .map(Ok::<sse::Event, Infallible>)
+    });
+    Ok(sse::reply(sse::keep_alive().stream(stream)))
+}
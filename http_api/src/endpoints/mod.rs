@@ -1,10 +1,20 @@
 pub mod beacon;
 pub mod config;
+pub mod data;
+pub mod eth;
+pub mod eth1;
+pub mod events;
+pub mod proofs;
 pub mod shards;
 pub mod simulator;
 pub mod utils;
 pub use beacon::*;
 pub use config::*;
+pub use data::*;
+pub use eth::*;
+pub use eth1::*;
+pub use events::*;
+pub use proofs::*;
 pub use shards::*;
 pub use simulator::*;
 pub use utils::*;
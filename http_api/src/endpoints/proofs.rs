@@ -0,0 +1,43 @@
+pub use crate::*;
+
+/// GET /proofs/commitment/{shard}/{slot}
+/// Proof that a data commitment is confirmed for the shard at the slot,
+/// against the state root and then the block root.
+/// The roots are the SSZ `hash_tree_root` with SHA-256, so the proof can be verified by
+/// `is_valid_merkle_branch` of an Eth1 contract.
+/// $ curl http://localhost:3030/proofs/commitment/0/1
+pub fn proofs_commitment(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("proofs" / "commitment" / Shard / Slot))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_commitment_proof)
+}
+
+pub async fn get_commitment_proof(
+    shard: Shard,
+    slot: Slot,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!("GET /proofs/commitment/{}/{}", shard, slot),
+    );
+    let simulator = simulator.lock().await;
+    match simulator
+        .beacon_chain
+        .get_commitment_proof(shard, slot)
+        .map_err(store_error)?
+    {
+        Some(proof) => Ok(warp::reply::json(&proof)),
+        None => Err(not_found_error(format!(
+            "confirmed commitment of shard {} at slot {}",
+            shard, slot
+        ))),
+    }
+}
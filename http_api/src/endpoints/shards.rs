@@ -1,7 +1,10 @@
 pub use crate::*;
+use futures::TryStreamExt;
+use std::convert::TryFrom;
+use warp::Buf;
 
 /// POST /shards/{shard}/bid
-/// $ curl -X POST -d '{"shard":0,"slot":1,"commitment":{"point":[138,242,160,225,209,236,53,174,172,15,28,234,190,70,242,28,171,189,72,181,73,85,194,175,243,3,178,236,97,160,135,229,227,245,224,250,13,243,208,141,120,70,177,2,18,36,183,67],"length":1},"fee":0}' -H 'Content-Type: application/json' http://localhost:3030/shards/0/bid
+/// $ curl -X POST -d '{"shard":0,"slot":1,"commitment":{"point":[138,242,160,225,209,236,53,174,172,15,28,234,190,70,242,28,171,189,72,181,73,85,194,175,243,3,178,236,97,160,135,229,227,245,224,250,13,243,208,141,120,70,177,2,18,36,183,67],"length":1},"fee":8}' -H 'Content-Type: application/json' http://localhost:3030/shards/0/bid
 pub fn shards_bid(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
@@ -34,34 +37,65 @@ pub async fn publish_bid(
     }
 
     let mut simulator = simulator.lock().await;
-    simulator.publish_bid(bid.clone());
-    Ok(StatusCode::OK)
+    match simulator.publish_bid(bid) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(bid_publication_error(e)),
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct BidWithData {
     bid: Bid,
     data: String,
+    #[serde(default)]
+    encoding: DataEncoding,
 }
 
 /// POST /shards/{shard}/bid_with_data
-/// $ curl -X POST -d '{"bid":{"shard":0,"slot":1,"commitment":{"point":[138,242,160,225,209,236,53,174,172,15,28,234,190,70,242,28,171,189,72,181,73,85,194,175,243,3,178,236,97,160,135,229,227,245,224,250,13,243,208,141,120,70,177,2,18,36,183,67],"length":1},"fee":0},"data":"bW91c3Nl"}' -H 'Content-Type: application/json' http://localhost:3030/shards/0/bid_with_data
+/// The body is JSON with the data in base64 (or hex with `"encoding":"hex"`),
+/// or multipart with the part `bid` in JSON and the part `data` in raw bytes.
+/// $ curl -X POST -d '{"bid":{"shard":0,"slot":1,"commitment":{"point":[138,242,160,225,209,236,53,174,172,15,28,234,190,70,242,28,171,189,72,181,73,85,194,175,243,3,178,236,97,160,135,229,227,245,224,250,13,243,208,141,120,70,177,2,18,36,183,67],"length":1},"fee":8},"data":"bW91c3Nl"}' -H 'Content-Type: application/json' http://localhost:3030/shards/0/bid_with_data
+/// $ curl -X POST -F 'bid={"shard":0,"slot":1,"commitment":{"point":[...],"length":1},"fee":8}' -F 'data=@blob.bin' http://localhost:3030/shards/0/bid_with_data
 pub fn shards_bid_with_data(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!("shards" / Shard / "bid_with_data"))
-        .and(warp::body::content_length_limit(1024 * 1024))
-        .and(warp::body::json())
+        .and(bid_with_data_body())
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
         .and_then(publish_bid_with_data)
 }
 
+/// The bid and the decoded data of a JSON or multipart body.
+fn bid_with_data_body() -> impl Filter<Extract = ((Bid, Vec<u8>),), Error = warp::Rejection> + Clone
+{
+    let json = warp::body::content_length_limit(MAX_DATA_BODY_SIZE)
+        .and(warp::body::json())
+        .and_then(|body: BidWithData| async move {
+            decode_data(&body.data, body.encoding)
+                .map(|data| (body.bid, data))
+                .map_err(data_decoding_error)
+        });
+    let multipart = multipart_form().and_then(|form: FormData| async move {
+        let mut parts = read_multipart(form).await.map_err(data_decoding_error)?;
+        let bid = take_part(&mut parts, "bid").map_err(data_decoding_error)?;
+        let bid: Bid = serde_json::from_slice(&bid).map_err(|e| {
+            data_decoding_error(DataError::InvalidPart {
+                name: String::from("bid"),
+                reason: e.to_string(),
+            })
+        })?;
+        let data = take_part(&mut parts, "data").map_err(data_decoding_error)?;
+        Ok::<_, warp::Rejection>((bid, data))
+    });
+    json.or(multipart).unify()
+}
+
 pub async fn publish_bid_with_data(
     shard: Shard,
-    bid_with_data: BidWithData,
+    (bid, data): (Bid, Vec<u8>),
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
 ) -> Result<impl warp::Reply, warp::Rejection> {
@@ -70,18 +104,241 @@ pub async fn publish_bid_with_data(
         &mut request_logs,
         String::from("POST /shards/{shard}/bid_with_data"),
     );
-    if shard != bid_with_data.bid.shard {
+    if shard != bid.shard {
         return Err(bid_publication_error(
             simulator::BidPublicationError::InvalidShard {
                 expect: shard,
-                found: bid_with_data.bid.shard,
+                found: bid.shard,
             },
         ));
     }
     let mut simulator = simulator.lock().await;
-    let data = base64::decode(&bid_with_data.data).unwrap_or_default();
-    match simulator.publish_bid_with_data(bid_with_data.bid, &data) {
+    match simulator.publish_bid_with_data(bid, &data) {
         Ok(_) => Ok(StatusCode::OK),
         Err(e) => Err(bid_publication_error(e)),
     }
 }
+
+/// Query parameters of the bid of a blob upload. `point` is the commitment point in hex.
+#[derive(Deserialize)]
+pub struct BlobParams {
+    slot: Slot,
+    fee: Gwei,
+    point: String,
+    length: u64,
+}
+
+impl BlobParams {
+    fn bid(&self, shard: Shard) -> Result<Bid, DataError> {
+        let point = decode_data(&self.point, DataEncoding::Hex)?;
+        let point = BlsCommitment::try_from(point.as_slice()).map_err(|_| {
+            DataError::InvalidPointLength {
+                expect: std::mem::size_of::<BlsCommitment>(),
+                found: point.len(),
+            }
+        })?;
+        Ok(Bid {
+            shard,
+            slot: self.slot,
+            commitment: DataCommitment {
+                point,
+                length: self.length,
+            },
+            fee: self.fee,
+        })
+    }
+}
+
+/// PUT /shards/{shard}/blobs
+/// Publish a bid with the raw data streamed as the body, which is up to `length` points.
+/// $ curl -X PUT --data-binary @blob.bin -H 'Content-Type: application/octet-stream' 'http://localhost:3030/shards/0/blobs?slot=1&fee=8&point=0x8af2...&length=1'
+pub fn shards_blobs(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::put()
+        .and(warp::path!("shards" / Shard / "blobs"))
+        .and(warp::query::<BlobParams>())
+        .and(warp::body::stream())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(put_blob)
+}
+
+pub async fn put_blob<S, B>(
+    shard: Shard,
+    params: BlobParams,
+    body: S,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection>
+where
+    S: futures::Stream<Item = Result<B, warp::Error>>,
+    B: Buf,
+{
+    // Note: The request logs are unlocked before the body is streamed.
+    {
+        let mut request_logs = request_logs.lock().await;
+        log(&mut request_logs, format!("PUT /shards/{}/blobs", shard));
+    }
+    let bid = params.bid(shard).map_err(data_decoding_error)?;
+    if bid.commitment.length > MAX_POINTS_PER_BLOCK {
+        return Err(bid_publication_error(
+            simulator::BidPublicationError::TooLargeData {
+                found: bid.commitment.length,
+            },
+        ));
+    }
+
+    // Stop reading the body as soon as it is longer than the commitment.
+    let max_len = bid.commitment.length as usize * BYTES_PER_POINT;
+    let mut data = Vec::with_capacity(max_len);
+    futures::pin_mut!(body);
+    while let Some(buf) = body.try_next().await.map_err(|e| {
+        data_decoding_error(DataError::InvalidBody {
+            reason: e.to_string(),
+        })
+    })? {
+        let chunk = buf.chunk();
+        if data.len() + chunk.len() > max_len {
+            return Err(bid_publication_error(
+                simulator::BidPublicationError::DataLengthMismatch {
+                    expect: bid.commitment.length,
+                    found: DataCommitment::length_of_bytes(data.len() + chunk.len()),
+                },
+            ));
+        }
+        data.extend_from_slice(chunk);
+    }
+
+    let mut simulator = simulator.lock().await;
+    match simulator.publish_bid_with_data(bid, &data) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(bid_publication_error(e)),
+    }
+}
+
+#[derive(Serialize)]
+struct ShardHeaderStatusResponse {
+    slot: Slot,
+    shard: Shard,
+    header: Option<SignedShardHeader>,
+    proposed: bool,
+    included_block_slot: Option<Slot>,
+    included_block_root: Option<Root>,
+    /// `null` if it is unknown since the state is already pruned.
+    confirmed: Option<bool>,
+    finalized: bool,
+}
+
+impl From<simulator::ShardHeaderStatus> for ShardHeaderStatusResponse {
+    fn from(status: simulator::ShardHeaderStatus) -> Self {
+        Self {
+            slot: status.slot,
+            shard: status.shard,
+            proposed: status.header.is_some(),
+            header: status.header,
+            included_block_slot: status.included_block.as_ref().map(|block| block.slot),
+            included_block_root: status
+                .included_block
+                .as_ref()
+                .map(|block| block.header().root()),
+            confirmed: status.confirmed,
+            finalized: status.finalized,
+        }
+    }
+}
+
+/// GET /shards/{shard}/headers
+/// Only the slots where shard blobs are proposed are listed.
+/// $ curl http://localhost:3030/shards/0/headers?from_slot=0&to_slot=31&order=asc
+pub fn shards_headers(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("shards" / Shard / "headers"))
+        .and(warp::query::<PaginationParams>())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_shard_headers)
+}
+
+pub async fn get_shard_headers(
+    shard: Shard,
+    params: PaginationParams,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!(
+            "GET /shards/{}/headers?{}",
+            shard,
+            serde_qs::to_string(&params).unwrap()
+        ),
+    );
+    let simulator = simulator.lock().await;
+    if shard >= SHARD_NUM {
+        return Err(not_found_error(format!("shard {}", shard)));
+    }
+    let headers: Vec<&SignedShardHeader> = simulator.shards[shard as usize]
+        .proposed_headers
+        .iter()
+        .flatten()
+        .collect();
+    let page = params
+        .paginate(&headers, |header| header.message.slot)
+        .map_err(invalid_pagination_params)?;
+    let mut statuses: Vec<ShardHeaderStatusResponse> = Vec::new();
+    for header in page.data {
+        if let Some(status) = simulator
+            .get_shard_header_status(shard, header.message.slot)
+            .map_err(store_error)?
+        {
+            statuses.push(status.into());
+        }
+    }
+    Ok(warp::reply::json(&Page {
+        data: statuses,
+        next: page.next,
+    }))
+}
+
+/// GET /shards/{shard}/headers/{slot}
+/// $ curl http://localhost:3030/shards/0/headers/1
+pub fn shards_header(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("shards" / Shard / "headers" / Slot))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_shard_header)
+}
+
+pub async fn get_shard_header(
+    shard: Shard,
+    slot: Slot,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!("GET /shards/{}/headers/{}", shard, slot),
+    );
+    let simulator = simulator.lock().await;
+    match simulator
+        .get_shard_header_status(shard, slot)
+        .map_err(store_error)?
+    {
+        Some(status) => Ok(warp::reply::json(&ShardHeaderStatusResponse::from(status))),
+        None => Err(not_found_error(format!(
+            "shard header of shard {} at slot {}",
+            shard, slot
+        ))),
+    }
+}
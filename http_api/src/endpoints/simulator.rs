@@ -1,34 +1,39 @@
 pub use crate::*;
 
 /// POST /simulator/init
+/// The observers are notified of the reset, so the simulated Eth1 chain resets its contracts.
 /// $ curl -X POST http://localhost:3030/simulator/init
 pub fn simulator_init(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!("simulator" / "init"))
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
-        .and(with_config(config))
         .and_then(init_simulator)
 }
 
 pub async fn init_simulator(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
-) -> Result<impl warp::Reply, Infallible> {
+) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(&mut request_logs, String::from("POST /simulator/init"));
     let mut simulator = simulator.lock().await;
-    *simulator = Simulator::new();
+    simulator.reset().map_err(store_error)?;
+    // The genesis is now.
+    let slot_clock = SlotClock::starting_at(
+        GENESIS_SLOT,
+        simulator.now_ms(),
+        simulator.slot_clock.slot_duration_ms,
+    );
+    simulator.set_slot_clock(slot_clock).map_err(store_error)?;
     // Process the genesis slot.
-    simulator.process_slots_happy(0);
+    simulator
+        .process_slots_happy(0)
+        .map_err(slot_processing_error)?;
     println!("Simulator initiated. Slot 0 is automatically processed.");
-    let mut config = config.lock().await;
-    config.restart_auto();
     Ok(StatusCode::OK)
 }
 
@@ -37,13 +42,11 @@ pub async fn init_simulator(
 pub fn simulator_slot_process(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!("simulator" / "slot" / "process" / Slot))
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
-        .and(with_config(config))
         .and_then(process_slots)
 }
 
@@ -51,7 +54,6 @@ pub async fn process_slots(
     slot: Slot,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
@@ -59,8 +61,6 @@ pub async fn process_slots(
         format!("POST /simulator/slot/process/{}", slot),
     );
     let mut simulator = simulator.lock().await;
-    let mut config = config.lock().await;
-    config.restart_auto();
     match simulator.process_slots_happy(slot) {
         Ok(_) => Ok(StatusCode::OK),
         Err(e) => Err(slot_processing_error(e)),
@@ -72,7 +72,6 @@ pub async fn process_slots(
 pub fn simulator_slot_process_without_shard_data_inclusion(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!(
@@ -80,7 +79,6 @@ pub fn simulator_slot_process_without_shard_data_inclusion(
         ))
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
-        .and(with_config(config))
         .and_then(process_slots_without_shard_data_inclusion)
 }
 
@@ -88,7 +86,6 @@ pub async fn process_slots_without_shard_data_inclusion(
     slot: Slot,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
@@ -99,8 +96,6 @@ pub async fn process_slots_without_shard_data_inclusion(
         ),
     );
     let mut simulator = simulator.lock().await;
-    let mut config = config.lock().await;
-    config.restart_auto();
     match simulator.process_slots_without_shard_data_inclusion(slot) {
         Ok(_) => Ok(StatusCode::OK),
         Err(e) => Err(slot_processing_error(e)),
@@ -112,7 +107,6 @@ pub async fn process_slots_without_shard_data_inclusion(
 pub fn simulator_slot_process_without_shard_blob_proposal(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!(
@@ -120,7 +114,6 @@ pub fn simulator_slot_process_without_shard_blob_proposal(
         ))
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
-        .and(with_config(config))
         .and_then(process_slots_without_shard_blob_proposal)
 }
 
@@ -128,7 +121,6 @@ pub async fn process_slots_without_shard_blob_proposal(
     slot: Slot,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
@@ -139,8 +131,6 @@ pub async fn process_slots_without_shard_blob_proposal(
         ),
     );
     let mut simulator = simulator.lock().await;
-    let mut config = config.lock().await;
-    config.restart_auto();
     match simulator.process_slots_without_shard_blob_proposal(slot) {
         Ok(_) => Ok(StatusCode::OK),
         Err(e) => Err(slot_processing_error(e)),
@@ -152,7 +142,6 @@ pub async fn process_slots_without_shard_blob_proposal(
 pub fn simulator_slot_process_without_shard_header_inclusion(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!(
@@ -160,7 +149,6 @@ pub fn simulator_slot_process_without_shard_header_inclusion(
         ))
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
-        .and(with_config(config))
         .and_then(process_slots_without_shard_header_inclusion)
 }
 
@@ -168,7 +156,6 @@ pub async fn process_slots_without_shard_header_inclusion(
     slot: Slot,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
@@ -179,8 +166,6 @@ pub async fn process_slots_without_shard_header_inclusion(
         ),
     );
     let mut simulator = simulator.lock().await;
-    let mut config = config.lock().await;
-    config.restart_auto();
     match simulator.process_slots_without_shard_header_inclusion(slot) {
         Ok(_) => Ok(StatusCode::OK),
         Err(e) => Err(slot_processing_error(e)),
@@ -192,7 +177,6 @@ pub async fn process_slots_without_shard_header_inclusion(
 pub fn simulator_slot_process_without_shard_header_confirmation(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!(
@@ -200,7 +184,6 @@ pub fn simulator_slot_process_without_shard_header_confirmation(
         ))
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
-        .and(with_config(config))
         .and_then(process_slots_without_shard_header_inclusion)
 }
 
@@ -208,7 +191,6 @@ pub async fn process_slots_without_shard_header_confirmation(
     slot: Slot,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
@@ -219,8 +201,6 @@ pub async fn process_slots_without_shard_header_confirmation(
         ),
     );
     let mut simulator = simulator.lock().await;
-    let mut config = config.lock().await;
-    config.restart_auto();
     match simulator.process_slots_without_shard_header_confirmation(slot) {
         Ok(_) => Ok(StatusCode::OK),
         Err(e) => Err(slot_processing_error(e)),
@@ -232,7 +212,6 @@ pub async fn process_slots_without_shard_header_confirmation(
 pub fn simulator_slot_process_without_beacon_chain_finality(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!(
@@ -240,7 +219,6 @@ pub fn simulator_slot_process_without_beacon_chain_finality(
         ))
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
-        .and(with_config(config))
         .and_then(process_slots_without_beacon_chain_finality)
 }
 
@@ -248,7 +226,6 @@ pub async fn process_slots_without_beacon_chain_finality(
     slot: Slot,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
@@ -259,8 +236,6 @@ pub async fn process_slots_without_beacon_chain_finality(
         ),
     );
     let mut simulator = simulator.lock().await;
-    let mut config = config.lock().await;
-    config.restart_auto();
     match simulator.process_slots_without_beacon_chain_finality(slot) {
         Ok(_) => Ok(StatusCode::OK),
         Err(e) => Err(slot_processing_error(e)),
@@ -272,7 +247,6 @@ pub async fn process_slots_without_beacon_chain_finality(
 pub fn simulator_slot_process_without_beacon_block_proposal(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!(
@@ -280,7 +254,6 @@ pub fn simulator_slot_process_without_beacon_block_proposal(
         ))
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
-        .and(with_config(config))
         .and_then(process_slots_without_beacon_block_proposal)
 }
 
@@ -288,7 +261,6 @@ pub async fn process_slots_without_beacon_block_proposal(
     slot: Slot,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
@@ -299,26 +271,70 @@ pub async fn process_slots_without_beacon_block_proposal(
         ),
     );
     let mut simulator = simulator.lock().await;
-    let mut config = config.lock().await;
-    config.restart_auto();
     match simulator.process_slots_without_beacon_block_proposal(slot) {
         Ok(_) => Ok(StatusCode::OK),
         Err(e) => Err(slot_processing_error(e)),
     }
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct OfflineValidatorsParams {
+    rate: f32,
+}
+
+/// POST /simulator/slot/process_with_offline_validators/{slot_num}?rate={rate}
+/// The validators of the rate of the active stake are offline. Finality stalls if the rate exceeds 1/3.
+/// $ curl -X POST http://localhost:3030/simulator/slot/process_with_offline_validators/1?rate=0.35
+pub fn simulator_slot_process_with_offline_validators(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!(
+            "simulator" / "slot" / "process_with_offline_validators" / Slot
+        ))
+        .and(warp::query::<OfflineValidatorsParams>())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(process_slots_with_offline_validators)
+}
+
+pub async fn process_slots_with_offline_validators(
+    slot: Slot,
+    params: OfflineValidatorsParams,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!(
+            "POST /simulator/slot/process_with_offline_validators/{}?rate={}",
+            slot, params.rate
+        ),
+    );
+    if !(0.0..=1.0).contains(&params.rate) {
+        return Err(invalid_slot_params(SlotParamsError::InvalidOfflineRate {
+            found: params.rate,
+        }));
+    }
+    let mut simulator = simulator.lock().await;
+    match simulator.process_slots_with_offline_validators(slot, params.rate) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(slot_processing_error(e)),
+    }
+}
+
 /// POST /simulator/slot/process_random/{slot_num}
 /// $ curl -X POST http://localhost:3030/simulator/slot/process_random/1
 pub fn simulator_slot_process_random(
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!("simulator" / "slot" / "process_random" / Slot))
         .and(with_simulator(simulator))
         .and(with_request_logs(request_logs))
-        .and(with_config(config))
         .and_then(process_slots_random)
 }
 
@@ -326,7 +342,6 @@ pub async fn process_slots_random(
     slot: Slot,
     simulator: SharedSimulator,
     request_logs: SharedRequestLogs,
-    config: SharedConfig,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let mut request_logs = request_logs.lock().await;
     log(
@@ -334,10 +349,205 @@ pub async fn process_slots_random(
         format!("POST /simulator/slot/process_random/{}", slot),
     );
     let mut simulator = simulator.lock().await;
-    let mut config = config.lock().await;
-    config.restart_auto();
     match simulator.process_slots_random(slot) {
         Ok(_) => Ok(StatusCode::OK),
         Err(e) => Err(slot_processing_error(e)),
     }
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct EquivocationsParams {
+    #[serde(default)]
+    proposer_indices: Vec<ValidatorIndex>,
+    #[serde(default)]
+    attester_indices: Vec<ValidatorIndex>,
+}
+
+/// POST /simulator/equivocations
+/// The proposers propose two conflicting blocks and the attesters make a double vote at the next slot,
+/// and they are slashed in the next beacon block.
+/// $ curl -X POST -d '{"proposer_indices":[0],"attester_indices":[1,2]}' -H 'Content-Type: application/json' http://localhost:3030/simulator/equivocations
+pub fn simulator_equivocations(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("simulator" / "equivocations"))
+        .and(warp::body::content_length_limit(1024 * 16))
+        .and(warp::body::json())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(simulate_equivocations)
+}
+
+pub async fn simulate_equivocations(
+    params: EquivocationsParams,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        String::from("POST /simulator/equivocations"),
+    );
+    let mut simulator = simulator.lock().await;
+    match simulator.simulate_equivocations(&params.proposer_indices, &params.attester_indices) {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => Err(slashing_error(e)),
+    }
+}
+
+#[derive(Serialize)]
+struct ClockResponse {
+    /// The current Unix time in milliseconds in the simulation.
+    time_ms: u64,
+    /// Whether the time is the virtual time instead of the wall-clock time.
+    virtual_time: bool,
+    /// Unix time of the genesis in seconds.
+    genesis_time: u64,
+    slot_time_ms: u64,
+    /// The slot of the clock, which can differ from the next slot to be processed.
+    current_slot: Option<Slot>,
+    /// The next slot to be processed.
+    next_slot: Slot,
+    /// The phase within the current slot of the clock.
+    phase: Option<SlotPhase>,
+}
+
+/// GET /simulator/clock
+/// The slot at a time is `(time - genesis_time) / slot_time`.
+/// $ curl http://localhost:3030/simulator/clock
+pub fn simulator_clock(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get()
+        .and(warp::path!("simulator" / "clock"))
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and_then(get_clock)
+}
+
+pub async fn get_clock(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+) -> Result<impl warp::Reply, Infallible> {
+    let mut request_logs = request_logs.lock().await;
+    log(&mut request_logs, String::from("GET /simulator/clock"));
+    let simulator = simulator.lock().await;
+    let time_ms = simulator.now_ms();
+    Ok(warp::reply::json(&ClockResponse {
+        time_ms,
+        virtual_time: simulator.virtual_time_ms.is_some(),
+        genesis_time: simulator.slot_clock.genesis_time(),
+        slot_time_ms: simulator.slot_clock.slot_duration_ms,
+        current_slot: simulator.slot_clock.slot_at(time_ms),
+        next_slot: simulator.slot,
+        phase: simulator.slot_phase(),
+    }))
+}
+
+/// Seconds to milliseconds, which can be less than a second (e.g. 0.25).
+fn seconds_to_ms(seconds: f64) -> Result<u64, simulator::TimeError> {
+    let ms = (seconds * MILLIS_PER_SECOND as f64).round();
+    if ms.is_nan() || ms < 0.0 || ms > u64::MAX as f64 {
+        return Err(simulator::TimeError::InvalidSeconds { found: seconds });
+    }
+    Ok(ms as u64)
+}
+
+/// Process the slots started by the clock in the auto mode, so the chain has caught up with the
+/// time when the request returns.
+fn process_started_slots(
+    simulator: &mut Simulator,
+    config: &Config,
+) -> Result<(), warp::Rejection> {
+    while process_started_slot(simulator, config).map_err(slot_processing_error)? {}
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TimeAdvanceParams {
+    seconds: f64,
+}
+
+/// POST /simulator/time/advance?seconds={seconds}
+/// Advance the virtual time, switching from the wall-clock time if it is used.
+/// In the auto mode, the slots started by the time are processed before the response.
+/// $ curl -X POST http://localhost:3030/simulator/time/advance?seconds=120
+pub fn simulator_time_advance(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+    config: SharedConfig,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("simulator" / "time" / "advance"))
+        .and(warp::query::<TimeAdvanceParams>())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and(with_config(config))
+        .and_then(advance_time)
+}
+
+pub async fn advance_time(
+    params: TimeAdvanceParams,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+    config: SharedConfig,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!("POST /simulator/time/advance?seconds={}", params.seconds),
+    );
+    let duration_ms = seconds_to_ms(params.seconds).map_err(time_error)?;
+    let mut simulator = simulator.lock().await;
+    let config = config.lock().await.clone();
+    simulator.advance_time(duration_ms);
+    process_started_slots(&mut simulator, &config)?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TimeSetParams {
+    /// Unix time in seconds.
+    time: f64,
+}
+
+/// POST /simulator/time/set?time={unix_time}
+/// Set the virtual time, switching from the wall-clock time if it is used.
+/// The virtual time can not go backwards.
+/// In the auto mode, the slots started by the time are processed before the response.
+/// $ curl -X POST http://localhost:3030/simulator/time/set?time=1700000000
+pub fn simulator_time_set(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+    config: SharedConfig,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::post()
+        .and(warp::path!("simulator" / "time" / "set"))
+        .and(warp::query::<TimeSetParams>())
+        .and(with_simulator(simulator))
+        .and(with_request_logs(request_logs))
+        .and(with_config(config))
+        .and_then(set_time)
+}
+
+pub async fn set_time(
+    params: TimeSetParams,
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+    config: SharedConfig,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let mut request_logs = request_logs.lock().await;
+    log(
+        &mut request_logs,
+        format!("POST /simulator/time/set?time={}", params.time),
+    );
+    let time_ms = seconds_to_ms(params.time).map_err(time_error)?;
+    let mut simulator = simulator.lock().await;
+    let config = config.lock().await.clone();
+    simulator.set_time(time_ms).map_err(time_error)?;
+    process_started_slots(&mut simulator, &config)?;
+    Ok(StatusCode::OK)
+}
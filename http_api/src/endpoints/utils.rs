@@ -16,6 +16,8 @@ pub fn utils_current_status_for_polling(
 #[derive(Serialize)]
 struct CurrentStatusForPolling {
     slot: Option<Slot>,
+    /// The phase within the current slot of the clock.
+    phase: Option<SlotPhase>,
     config: Config,
 }
 
@@ -32,18 +34,22 @@ pub async fn get_current_status_for_polling(
     };
     Ok(warp::reply::json(&CurrentStatusForPolling {
         slot,
-        config: config.config.clone(),
+        phase: simulator.slot_phase(),
+        config: config.clone(),
     }))
 }
 
 /// POST /utils/data_commitment
+/// The body is JSON with the data in base64 (or hex with `"encoding":"hex"`),
+/// raw bytes of `application/octet-stream`, or multipart with the part `data`.
+/// $ curl -X POST -d '{"data":"bW91c3Nl"}' -H 'Content-Type: application/json' http://localhost:3030/utils/data_commitment
+/// $ curl -X POST --data-binary @blob.bin -H 'Content-Type: application/octet-stream' http://localhost:3030/utils/data_commitment
 pub fn utils_data_commitment(
     request_logs: SharedRequestLogs,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
     warp::post()
         .and(warp::path!("utils" / "data_commitment"))
-        // .and(warp::body::content_length_limit(1024 * 1024))
-        .and(warp::body::json())
+        .and(data_commitment_body())
         .and(with_request_logs(request_logs))
         .and_then(calc_data_commitment)
 }
@@ -51,10 +57,26 @@ pub fn utils_data_commitment(
 #[derive(Deserialize)]
 pub struct UtilsDataCommitmentBody {
     data: String,
+    #[serde(default)]
+    encoding: DataEncoding,
+}
+
+/// The decoded data of a JSON, octet-stream or multipart body.
+fn data_commitment_body() -> impl Filter<Extract = (Vec<u8>,), Error = warp::Rejection> + Clone {
+    let json = warp::body::content_length_limit(MAX_DATA_BODY_SIZE)
+        .and(warp::body::json())
+        .and_then(|body: UtilsDataCommitmentBody| async move {
+            decode_data(&body.data, body.encoding).map_err(data_decoding_error)
+        });
+    let multipart = multipart_form().and_then(|form: FormData| async move {
+        let mut parts = read_multipart(form).await.map_err(data_decoding_error)?;
+        take_part(&mut parts, "data").map_err(data_decoding_error)
+    });
+    json.or(octet_stream_data()).unify().or(multipart).unify()
 }
 
 pub async fn calc_data_commitment(
-    body: UtilsDataCommitmentBody,
+    bytes: Vec<u8>,
     request_logs: SharedRequestLogs,
 ) -> Result<impl warp::Reply, Infallible> {
     let mut request_logs = request_logs.lock().await;
@@ -62,8 +84,6 @@ pub async fn calc_data_commitment(
         &mut request_logs,
         String::from("POST /utils/data_commitment"),
     );
-    let base64string = body.data;
-    let bytes = base64::decode(base64string).unwrap_or_default();
     let dummy = DataCommitment::dummy_from_bytes(&bytes);
     Ok(warp::reply::json(&dummy))
 }
@@ -1,6 +1,9 @@
+use common::eth2_config::{MAX_POINTS_PER_BLOCK, MIN_DEPOSIT_AMOUNT};
 use eth2_simulator::simulator;
+use serde_json::{json, Value};
 use thiserror::Error;
 use warp;
+use warp::http::StatusCode;
 
 #[derive(Debug)]
 pub struct SlotProcessingError(pub simulator::SlotProcessingError);
@@ -20,10 +23,93 @@ pub fn bid_publication_error(e: simulator::BidPublicationError) -> warp::reject:
     warp::reject::custom(BidPublicationError(e))
 }
 
+#[derive(Debug)]
+pub struct DepositError(pub simulator::DepositError);
+
+impl warp::reject::Reject for DepositError {}
+
+pub fn deposit_error(e: simulator::DepositError) -> warp::reject::Rejection {
+    warp::reject::custom(DepositError(e))
+}
+
+#[derive(Debug)]
+pub struct VoluntaryExitError(pub simulator::VoluntaryExitError);
+
+impl warp::reject::Reject for VoluntaryExitError {}
+
+pub fn voluntary_exit_error(e: simulator::VoluntaryExitError) -> warp::reject::Rejection {
+    warp::reject::custom(VoluntaryExitError(e))
+}
+
+#[derive(Debug)]
+pub struct SlashingError(pub simulator::SlashingError);
+
+impl warp::reject::Reject for SlashingError {}
+
+pub fn slashing_error(e: simulator::SlashingError) -> warp::reject::Rejection {
+    warp::reject::custom(SlashingError(e))
+}
+
+#[derive(Debug)]
+pub struct TimeError(pub simulator::TimeError);
+
+impl warp::reject::Reject for TimeError {}
+
+pub fn time_error(e: simulator::TimeError) -> warp::reject::Rejection {
+    warp::reject::custom(TimeError(e))
+}
+
+#[derive(Debug)]
+pub struct DataSubmissionError(pub simulator::DataSubmissionError);
+
+impl warp::reject::Reject for DataSubmissionError {}
+
+pub fn data_submission_error(e: simulator::DataSubmissionError) -> warp::reject::Rejection {
+    warp::reject::custom(DataSubmissionError(e))
+}
+
+#[derive(Debug)]
+pub struct DataReassemblyError(pub simulator::DataReassemblyError);
+
+impl warp::reject::Reject for DataReassemblyError {}
+
+pub fn data_reassembly_error(e: simulator::DataReassemblyError) -> warp::reject::Rejection {
+    warp::reject::custom(DataReassemblyError(e))
+}
+
+#[derive(Debug)]
+pub struct StoreError(pub simulator::StoreError);
+
+impl warp::reject::Reject for StoreError {}
+
+pub fn store_error(e: simulator::StoreError) -> warp::reject::Rejection {
+    warp::reject::custom(StoreError(e))
+}
+
+/// The requested resource does not exist.
+/// Note: Use this instead of `warp::reject::not_found()` in handlers,
+/// since the not found rejection has lower priority than the rejections of the other routes.
+#[derive(Debug)]
+pub struct NotFoundError(pub String);
+
+impl warp::reject::Reject for NotFoundError {}
+
+pub fn not_found_error(resource: String) -> warp::reject::Rejection {
+    warp::reject::custom(NotFoundError(resource))
+}
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
     #[error("Failure rate must be a positive integer <= 1.0 (found {found:?})")]
     InvalidFailureRate { found: f32 },
+    #[error("Slot time must be at least 0.001 seconds (found {found:?})")]
+    InvalidSlotTime { found: f64 },
+    #[error("Bid deadline must be at least 0 seconds (found {found:?})")]
+    InvalidBidDeadline { found: f64 },
+    #[error("Invalid failure model: {0}")]
+    InvalidFailureModel(simulator::FailureModelError),
+    #[error("Invalid propagation model: {0}")]
+    InvalidPropagationModel(simulator::PropagationModelError),
 }
 #[derive(Debug)]
 pub struct ConfigSetError(pub ConfigError);
@@ -33,3 +119,496 @@ impl warp::reject::Reject for ConfigSetError {}
 pub fn config_set_error(e: ConfigError) -> warp::reject::Rejection {
     warp::reject::custom(ConfigSetError(e))
 }
+
+#[derive(Error, Debug)]
+pub enum EventsError {
+    #[error("Unknown event topic (found {found:?})")]
+    UnknownTopic { found: String },
+}
+#[derive(Debug)]
+pub struct EventsSubscriptionError(pub EventsError);
+
+impl warp::reject::Reject for EventsSubscriptionError {}
+
+pub fn events_subscription_error(e: EventsError) -> warp::reject::Rejection {
+    warp::reject::custom(EventsSubscriptionError(e))
+}
+
+#[derive(Error, Debug)]
+pub enum IdError {
+    #[error("Invalid block ID (found {found:?})")]
+    InvalidBlockId { found: String },
+    #[error("Invalid state ID (found {found:?})")]
+    InvalidStateId { found: String },
+}
+#[derive(Debug)]
+pub struct IdParsingError(pub IdError);
+
+impl warp::reject::Reject for IdParsingError {}
+
+pub fn id_parsing_error(e: IdError) -> warp::reject::Rejection {
+    warp::reject::custom(IdParsingError(e))
+}
+
+#[derive(Error, Debug)]
+pub enum PaginationError {
+    #[error("Limit must be a positive integer <= MAX_PAGE_LIMIT (found {found:?})")]
+    InvalidLimit { found: usize },
+}
+#[derive(Debug)]
+pub struct InvalidPaginationParams(pub PaginationError);
+
+impl warp::reject::Reject for InvalidPaginationParams {}
+
+pub fn invalid_pagination_params(e: PaginationError) -> warp::reject::Rejection {
+    warp::reject::custom(InvalidPaginationParams(e))
+}
+
+#[derive(Error, Debug)]
+pub enum SlotParamsError {
+    #[error("Offline rate must be a number in [0.0, 1.0] (found {found:?})")]
+    InvalidOfflineRate { found: f32 },
+}
+#[derive(Debug)]
+pub struct InvalidSlotParams(pub SlotParamsError);
+
+impl warp::reject::Reject for InvalidSlotParams {}
+
+pub fn invalid_slot_params(e: SlotParamsError) -> warp::reject::Rejection {
+    warp::reject::custom(InvalidSlotParams(e))
+}
+
+#[derive(Error, Debug)]
+pub enum DataError {
+    #[error("Invalid base64 data: {reason}")]
+    InvalidBase64 { reason: String },
+    #[error("Invalid hex data: {reason}")]
+    InvalidHex { reason: String },
+    #[error("Invalid multipart body: {reason}")]
+    InvalidMultipart { reason: String },
+    #[error("Missing part of multipart body (name {name:?})")]
+    MissingPart { name: String },
+    #[error("Invalid JSON in part of multipart body (name {name:?}): {reason}")]
+    InvalidPart { name: String, reason: String },
+    #[error("Invalid body: {reason}")]
+    InvalidBody { reason: String },
+    #[error("Invalid length of commitment point (expect {expect:?} bytes, found {found:?} bytes)")]
+    InvalidPointLength { expect: usize, found: usize },
+}
+#[derive(Debug)]
+pub struct DataDecodingError(pub DataError);
+
+impl warp::reject::Reject for DataDecodingError {}
+
+pub fn data_decoding_error(e: DataError) -> warp::reject::Rejection {
+    warp::reject::custom(DataDecodingError(e))
+}
+
+/// An error in the error responses, which has a stable code and structured fields
+/// so that the clients can branch on the error without parsing the message.
+pub trait ApiError: std::fmt::Display {
+    /// The stable code of the error (e.g. `BID_PAST_SLOT`).
+    fn code(&self) -> &'static str;
+
+    /// The structured fields of the error as a JSON object (e.g. `{"next": 12, "found": 10}`).
+    fn fields(&self) -> Value {
+        json!({})
+    }
+
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+/// The error causing another error, which is nested in the fields.
+fn cause(e: &dyn ApiError) -> Value {
+    let mut cause = json!({
+        "code": e.code(),
+        "message": e.to_string(),
+    });
+    if let (Some(cause), Value::Object(fields)) = (cause.as_object_mut(), e.fields()) {
+        cause.extend(fields);
+    }
+    cause
+}
+
+impl ApiError for simulator::SlotProcessingError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::PastSlot { .. } => "SLOT_PAST_SLOT",
+            Self::Store(e) => e.code(),
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::PastSlot { next, found } => json!({ "next": next, "found": found }),
+            Self::Store(e) => e.fields(),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::PastSlot { .. } => StatusCode::BAD_REQUEST,
+            Self::Store(e) => e.status(),
+        }
+    }
+}
+
+impl ApiError for simulator::BidPublicationError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::UnknownShard { .. } => "BID_UNKNOWN_SHARD",
+            Self::PastSlot { .. } => "BID_PAST_SLOT",
+            Self::TooLate { .. } => "BID_TOO_LATE",
+            Self::TooLargeData { .. } => "BID_TOO_LARGE_DATA",
+            Self::EmptyData => "BID_EMPTY_DATA",
+            Self::TooLowFee { .. } => "BID_TOO_LOW_FEE",
+            Self::DataLengthMismatch { .. } => "BID_DATA_LENGTH_MISMATCH",
+            Self::InvalidCommitment { .. } => "BID_INVALID_COMMITMENT",
+            Self::InvalidShard { .. } => "BID_INVALID_SHARD",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::UnknownShard { found } => json!({ "found": found }),
+            Self::PastSlot { next, found } => json!({ "next": next, "found": found }),
+            Self::TooLate {
+                slot,
+                deadline_ms,
+                now_ms,
+            } => json!({ "slot": slot, "deadline_ms": deadline_ms, "now_ms": now_ms }),
+            Self::TooLargeData { found } => json!({ "max": MAX_POINTS_PER_BLOCK, "found": found }),
+            Self::EmptyData => json!({}),
+            Self::TooLowFee { gasprice, found } => json!({ "gasprice": gasprice, "found": found }),
+            Self::DataLengthMismatch { expect, found } => {
+                json!({ "expect": expect, "found": found })
+            }
+            Self::InvalidCommitment { expect, found } => {
+                json!({ "expect": expect, "found": found })
+            }
+            Self::InvalidShard { expect, found } => json!({ "expect": expect, "found": found }),
+        }
+    }
+}
+
+impl ApiError for simulator::DepositError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::TooSmallAmount { .. } => "DEPOSIT_TOO_SMALL_AMOUNT",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::TooSmallAmount { found } => json!({ "min": MIN_DEPOSIT_AMOUNT, "found": found }),
+        }
+    }
+}
+
+impl ApiError for simulator::VoluntaryExitError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::UnknownValidator { .. } => "EXIT_UNKNOWN_VALIDATOR",
+            Self::InactiveValidator { .. } => "EXIT_INACTIVE_VALIDATOR",
+            Self::AlreadyExiting { .. } => "EXIT_ALREADY_EXITING",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::UnknownValidator { found } => json!({ "found": found }),
+            Self::InactiveValidator { index } => json!({ "index": index }),
+            Self::AlreadyExiting { index, exit_epoch } => {
+                json!({ "index": index, "exit_epoch": exit_epoch })
+            }
+        }
+    }
+}
+
+impl ApiError for simulator::SlashingError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::UnknownValidator { .. } => "SLASHING_UNKNOWN_VALIDATOR",
+            Self::UnslashableValidator { .. } => "SLASHING_UNSLASHABLE_VALIDATOR",
+            Self::DifferentSlots { .. } => "SLASHING_DIFFERENT_SLOTS",
+            Self::IdenticalHeaders => "SLASHING_IDENTICAL_HEADERS",
+            Self::InvalidAttestingIndices => "SLASHING_INVALID_ATTESTING_INDICES",
+            Self::UnslashableAttestationData => "SLASHING_UNSLASHABLE_ATTESTATION_DATA",
+            Self::NoSlashableValidators => "SLASHING_NO_SLASHABLE_VALIDATORS",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::UnknownValidator { found } => json!({ "found": found }),
+            Self::UnslashableValidator { index } => json!({ "index": index }),
+            Self::DifferentSlots { slot_1, slot_2 } => {
+                json!({ "slot_1": slot_1, "slot_2": slot_2 })
+            }
+            _ => json!({}),
+        }
+    }
+}
+
+impl ApiError for simulator::FailureModelError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidRate { .. } => "FAILURE_MODEL_INVALID_RATE",
+            Self::UnknownShard { .. } => "FAILURE_MODEL_UNKNOWN_SHARD",
+            Self::InvalidOutageDuration { .. } => "FAILURE_MODEL_INVALID_OUTAGE_DURATION",
+            Self::InvalidIncidentSlots { .. } => "FAILURE_MODEL_INVALID_INCIDENT_SLOTS",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::InvalidRate { found } => json!({ "found": found }),
+            Self::UnknownShard { found } => json!({ "found": found }),
+            Self::InvalidOutageDuration {
+                min_epochs,
+                max_epochs,
+            } => json!({ "min_epochs": min_epochs, "max_epochs": max_epochs }),
+            Self::InvalidIncidentSlots {
+                start_slot,
+                end_slot,
+            } => json!({ "start_slot": start_slot, "end_slot": end_slot }),
+        }
+    }
+}
+
+impl ApiError for simulator::PropagationModelError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidDropRate { .. } => "PROPAGATION_MODEL_INVALID_DROP_RATE",
+            Self::InvalidLatency { .. } => "PROPAGATION_MODEL_INVALID_LATENCY",
+            Self::UnknownShard { .. } => "PROPAGATION_MODEL_UNKNOWN_SHARD",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::InvalidDropRate { found } => json!({ "found": found }),
+            Self::InvalidLatency { min_ms, max_ms } => {
+                json!({ "min_ms": min_ms, "max_ms": max_ms })
+            }
+            Self::UnknownShard { found } => json!({ "found": found }),
+        }
+    }
+}
+
+impl ApiError for simulator::TimeError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::PastTime { .. } => "TIME_PAST_TIME",
+            Self::InvalidSeconds { .. } => "TIME_INVALID_SECONDS",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::PastTime { now_ms, found } => json!({ "now_ms": now_ms, "found": found }),
+            Self::InvalidSeconds { found } => json!({ "found": found }),
+        }
+    }
+}
+
+impl ApiError for simulator::DataSubmissionError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::EmptyData => "SUBMISSION_EMPTY_DATA",
+            Self::NoShards => "SUBMISSION_NO_SHARDS",
+            Self::UnknownShard { .. } => "SUBMISSION_UNKNOWN_SHARD",
+            Self::DuplicateShard { .. } => "SUBMISSION_DUPLICATE_SHARD",
+            Self::DuplicateSlot { .. } => "SUBMISSION_DUPLICATE_SLOT",
+            Self::TooManyPieces { .. } => "SUBMISSION_TOO_MANY_PIECES",
+            Self::Bid { .. } => "SUBMISSION_BID_REJECTED",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::EmptyData | Self::NoShards => json!({}),
+            Self::UnknownShard { found }
+            | Self::DuplicateShard { found }
+            | Self::DuplicateSlot { found } => json!({ "found": found }),
+            Self::TooManyPieces { pieces, capacity } => {
+                json!({ "pieces": pieces, "capacity": capacity })
+            }
+            Self::Bid { index, error } => json!({ "index": index, "cause": cause(error) }),
+        }
+    }
+}
+
+impl ApiError for simulator::DataReassemblyError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NotConfirmed { .. } => "REASSEMBLY_NOT_CONFIRMED",
+            Self::UnknownPiece { .. } => "REASSEMBLY_UNKNOWN_PIECE",
+            Self::InvalidLength { .. } => "REASSEMBLY_INVALID_LENGTH",
+            Self::Store(e) => e.code(),
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::NotConfirmed { index, shard, slot } => {
+                json!({ "index": index, "shard": shard, "slot": slot })
+            }
+            Self::UnknownPiece { index } => json!({ "index": index }),
+            Self::InvalidLength { expect, found } => json!({ "expect": expect, "found": found }),
+            Self::Store(e) => e.fields(),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Store(e) => e.status(),
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl ApiError for simulator::StoreError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "STORE_IO",
+            Self::BrokenData(_) => "STORE_BROKEN_DATA",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+impl ApiError for NotFoundError {
+    fn code(&self) -> &'static str {
+        "NOT_FOUND"
+    }
+
+    fn fields(&self) -> Value {
+        json!({ "resource": self.0 })
+    }
+
+    fn status(&self) -> StatusCode {
+        StatusCode::NOT_FOUND
+    }
+}
+
+impl std::fmt::Display for NotFoundError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} not found", self.0)
+    }
+}
+
+impl ApiError for ConfigError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidFailureRate { .. } => "CONFIG_INVALID_FAILURE_RATE",
+            Self::InvalidSlotTime { .. } => "CONFIG_INVALID_SLOT_TIME",
+            Self::InvalidBidDeadline { .. } => "CONFIG_INVALID_BID_DEADLINE",
+            Self::InvalidFailureModel(_) => "CONFIG_INVALID_FAILURE_MODEL",
+            Self::InvalidPropagationModel(_) => "CONFIG_INVALID_PROPAGATION_MODEL",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::InvalidFailureRate { found } => json!({ "found": found }),
+            Self::InvalidSlotTime { found } => json!({ "found": found }),
+            Self::InvalidBidDeadline { found } => json!({ "found": found }),
+            Self::InvalidFailureModel(e) => json!({ "cause": cause(e) }),
+            Self::InvalidPropagationModel(e) => json!({ "cause": cause(e) }),
+        }
+    }
+}
+
+impl ApiError for EventsError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::UnknownTopic { .. } => "EVENTS_UNKNOWN_TOPIC",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::UnknownTopic { found } => json!({ "found": found }),
+        }
+    }
+}
+
+impl ApiError for IdError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidBlockId { .. } => "ID_INVALID_BLOCK_ID",
+            Self::InvalidStateId { .. } => "ID_INVALID_STATE_ID",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::InvalidBlockId { found } => json!({ "found": found }),
+            Self::InvalidStateId { found } => json!({ "found": found }),
+        }
+    }
+}
+
+impl ApiError for PaginationError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidLimit { .. } => "PAGINATION_INVALID_LIMIT",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::InvalidLimit { found } => json!({ "found": found }),
+        }
+    }
+}
+
+impl ApiError for SlotParamsError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidOfflineRate { .. } => "SLOT_INVALID_OFFLINE_RATE",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::InvalidOfflineRate { found } => json!({ "found": found }),
+        }
+    }
+}
+
+impl ApiError for DataError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidBase64 { .. } => "DATA_INVALID_BASE64",
+            Self::InvalidHex { .. } => "DATA_INVALID_HEX",
+            Self::InvalidMultipart { .. } => "DATA_INVALID_MULTIPART",
+            Self::MissingPart { .. } => "DATA_MISSING_PART",
+            Self::InvalidPart { .. } => "DATA_INVALID_PART",
+            Self::InvalidBody { .. } => "DATA_INVALID_BODY",
+            Self::InvalidPointLength { .. } => "DATA_INVALID_POINT_LENGTH",
+        }
+    }
+
+    fn fields(&self) -> Value {
+        match self {
+            Self::InvalidBase64 { .. }
+            | Self::InvalidHex { .. }
+            | Self::InvalidMultipart { .. }
+            | Self::InvalidBody { .. } => {
+                json!({})
+            }
+            Self::MissingPart { name } => json!({ "name": name }),
+            Self::InvalidPart { name, .. } => json!({ "name": name }),
+            Self::InvalidPointLength { expect, found } => {
+                json!({ "expect": expect, "found": found })
+            }
+        }
+    }
+}
@@ -0,0 +1,595 @@
+//! HTTP API of the Ethereum 2.0 emulator. The server is started by the `http_api` binary.
+use chrono::prelude::*;
+use eth1_simulator::chain::Eth1Chain;
+use eth1_simulator::oracle::OracleUpdate;
+use eth2_simulator::failure_model::FailureModel;
+use eth2_simulator::propagation_model::PropagationModel;
+use eth2_simulator::simulator;
+use eth2_simulator::simulator::{Event, Simulator};
+use eth2_simulator::slot_clock::{SlotClock, SlotPhase, MILLIS_PER_SECOND};
+use serde_derive::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, Mutex};
+use warp::{http::StatusCode, reject, Filter};
+
+pub use common::bid::Bid;
+pub use common::eth2_config::*;
+pub use common::eth2_types::*;
+
+pub mod errors;
+pub use errors::*;
+pub mod endpoints;
+pub use endpoints::*;
+pub mod pagination;
+pub use pagination::*;
+pub mod data;
+pub use data::*;
+
+pub type SharedSimulator = Arc<Mutex<Simulator>>;
+pub type SharedEth1Chain = Arc<Mutex<Eth1Chain>>;
+
+/// Sender of the simulation events to the event stream subscribers.
+pub type EventSender = broadcast::Sender<Event>;
+/// The number of the events buffered for each subscriber of the event stream.
+pub const EVENT_CHANNEL_CAPACITY: usize = 16384;
+
+/// Config for the auto mode.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub auto: bool,
+    /// Slot time in seconds, which can be less than a second (e.g. 0.25).
+    pub slot_time: f64,
+    /// Deprecated: The same as `preset` of `failure_model`.
+    pub failure_rate: f32,
+    /// Failure model of the slots.
+    pub failure_model: FailureModel,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            auto: false,
+            slot_time: SECONDS_PER_SLOT as f64,
+            failure_rate: 0.0,
+            failure_model: FailureModel::default(),
+        }
+    }
+}
+
+impl Config {
+    /// The slot time in milliseconds.
+    pub fn slot_duration_ms(&self) -> Result<u64, ConfigError> {
+        let slot_duration_ms = (self.slot_time * MILLIS_PER_SECOND as f64).round();
+        if slot_duration_ms.is_nan() || slot_duration_ms < 1.0 {
+            return Err(ConfigError::InvalidSlotTime {
+                found: self.slot_time,
+            });
+        }
+        Ok(slot_duration_ms as u64)
+    }
+}
+
+pub type SharedConfig = Arc<Mutex<Config>>;
+
+#[derive(Serialize, Clone)]
+pub struct RequestLog {
+    log_id: usize,
+    date: String,
+    endpoint: String,
+    // request_body: String,
+    // response_body: String,
+}
+pub type SharedRequestLogs = Arc<Mutex<Vec<RequestLog>>>;
+
+/// The maximum number of the slots missed by the stored slot clock that are caught up after a restart.
+/// The slot clock is re-anchored if the simulator is further behind.
+pub const MAX_CATCH_UP_SLOTS: u64 = SLOTS_PER_EPOCH;
+
+/// Whether or not the simulator at the slot can catch up the slot clock at the time.
+pub fn can_catch_up(slot_clock: &SlotClock, slot: Slot, now_ms: u64) -> bool {
+    slot_clock.slot_at(now_ms).map_or(true, |current_slot| {
+        current_slot <= slot + MAX_CATCH_UP_SLOTS
+    })
+}
+
+/// The maximum milliseconds the auto mode waits for the next slot before checking again,
+/// since the config, the slot clock and the virtual time can be changed meanwhile.
+pub const MAX_AUTO_WAIT_MS: u64 = 10;
+/// The maximum milliseconds the auto mode backs off after failing to process a slot.
+pub const MAX_AUTO_BACKOFF_MS: u64 = 10_000;
+
+/// Process the slots as the slot clock ticks in the auto mode.
+/// If the simulator is behind the clock, it catches up slot by slot.
+/// If the slots are processed ahead of the clock manually, it waits for the clock.
+/// If the processing fails, it retries after backing off exponentially up to `MAX_AUTO_BACKOFF_MS`.
+pub async fn process_auto(simulator: SharedSimulator, config: SharedConfig) {
+    let mut backoff_ms = 0;
+    loop {
+        let config = config.lock().await.clone();
+        let wait_ms = if !config.auto {
+            backoff_ms = 0;
+            MAX_AUTO_WAIT_MS
+        } else {
+            let mut simulator = simulator.lock().await;
+            match process_started_slot(&mut simulator, &config) {
+                Ok(true) => {
+                    backoff_ms = 0;
+                    continue;
+                }
+                // Wait until the next slot starts.
+                Ok(false) => {
+                    backoff_ms = 0;
+                    let start_ms = simulator.slot_clock.slot_start_time_ms(simulator.slot);
+                    start_ms
+                        .saturating_sub(simulator.now_ms())
+                        .min(MAX_AUTO_WAIT_MS)
+                }
+                Err(e) => {
+                    backoff_ms = (backoff_ms * 2)
+                        .max(MAX_AUTO_WAIT_MS)
+                        .min(MAX_AUTO_BACKOFF_MS);
+                    println!(
+                        "Auto processing failed. Slot {}: {}. Retrying in {} ms.",
+                        simulator.slot, e, backoff_ms
+                    );
+                    backoff_ms
+                }
+            }
+        };
+        tokio::time::sleep(time::Duration::from_millis(wait_ms)).await;
+    }
+}
+
+/// Process the next slot in the auto mode if the slot has started by the clock.
+/// Returns `true` if the slot is processed, and `false` if the auto mode is off or the slot is not started.
+fn process_started_slot(
+    simulator: &mut Simulator,
+    config: &Config,
+) -> Result<bool, simulator::SlotProcessingError> {
+    let slot = simulator.slot;
+    let current_slot = simulator.slot_clock.slot_at(simulator.now_ms());
+    if !config.auto || current_slot.map_or(true, |current_slot| current_slot < slot) {
+        return Ok(false);
+    }
+    println!("Auto processing. Slot {}", slot);
+    simulator.process_slots_with_failure_model(slot, &config.failure_model)?;
+    Ok(true)
+}
+
+/// The maximum number of the Eth1 blocks mined at once after the time is advanced.
+pub const MAX_ETH1_BLOCKS_PER_TICK: u64 = 64;
+
+/// Mine a block of the simulated Eth1 chain every `block_time` seconds of the simulation time.
+/// If the time is advanced by more than `block_time` (e.g. the virtual time), the blocks are
+/// mined at once with the timestamps they would have had, but only the last
+/// `MAX_ETH1_BLOCKS_PER_TICK` of them are mined after a long jump.
+/// The bids placed in the fee market contract are published to the shards.
+pub async fn mine_eth1_blocks(
+    eth1_chain: SharedEth1Chain,
+    simulator: SharedSimulator,
+    block_time: u64,
+) {
+    let ten_millis = time::Duration::from_millis(10);
+    loop {
+        // Wait 0.01 seconds.
+        tokio::time::sleep(ten_millis).await;
+        let now = simulator.lock().await.now_ms() / MILLIS_PER_SECOND;
+        let bids = {
+            let mut eth1_chain = eth1_chain.lock().await;
+            mine_due_eth1_blocks(&mut eth1_chain, now, block_time);
+            eth1_chain.take_placed_bids()
+        };
+        if bids.is_empty() {
+            continue;
+        }
+        let mut simulator = simulator.lock().await;
+        for bid in bids {
+            // Note: The fee of a bid not published is refunded after the finalization.
+            if let Err(e) = simulator.publish_bid(bid) {
+                eprintln!("Failed to publish a bid placed in Eth1: {}", e);
+            }
+        }
+    }
+}
+
+/// Mine the Eth1 blocks due by `now`, up to `MAX_ETH1_BLOCKS_PER_TICK` blocks.
+fn mine_due_eth1_blocks(eth1_chain: &mut Eth1Chain, now: u64, block_time: u64) {
+    let head_timestamp = eth1_chain.head().timestamp;
+    if now < head_timestamp + block_time {
+        return;
+    }
+    let due = (now - head_timestamp) / block_time;
+    let skipped = due.saturating_sub(MAX_ETH1_BLOCKS_PER_TICK);
+    for i in skipped + 1..=due {
+        eth1_chain.mine_block(head_timestamp + i * block_time);
+    }
+}
+
+/// Relay the finalized checkpoints and the confirmed commitments to the beacon oracle on Eth1.
+/// The beacon oracle and the fee market contract are reset when the simulator is reset.
+pub async fn feed_beacon_oracle(
+    eth1_chain: SharedEth1Chain,
+    mut receiver: broadcast::Receiver<Event>,
+) {
+    loop {
+        let update = match receiver.recv().await {
+            Ok(Event::CheckpointFinalized { checkpoint }) => {
+                OracleUpdate::FinalizedCheckpoint { checkpoint }
+            }
+            Ok(Event::ShardHeaderConfirmed { header }) => OracleUpdate::ConfirmedCommitment {
+                shard: header.shard,
+                slot: header.slot,
+                commitment: header.commitment,
+            },
+            Ok(Event::SimulatorReset) => {
+                eth1_chain.lock().await.reset_contracts();
+                continue;
+            }
+            Ok(_) => continue,
+            Err(RecvError::Lagged(count)) => {
+                eprintln!("Beacon oracle missed {} events.", count);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        eth1_chain.lock().await.receive_beacon_update(update);
+    }
+}
+
+/// The routes of the server with the error responses and CORS.
+pub fn routes(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+    config: SharedConfig,
+    event_sender: EventSender,
+) -> impl Filter<Extract = impl warp::Reply, Error = Infallible> + Clone {
+    filters(simulator, request_logs, config, event_sender)
+        .recover(handle_rejection)
+        .with(cors())
+}
+
+pub fn filters(
+    simulator: SharedSimulator,
+    request_logs: SharedRequestLogs,
+    config: SharedConfig,
+    event_sender: EventSender,
+) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    root()
+        .or(beacon_blocks(simulator.clone(), request_logs.clone()))
+        .or(beacon_blocks_head(simulator.clone(), request_logs.clone()))
+        .or(beacon_block(simulator.clone(), request_logs.clone()))
+        .or(beacon_header(simulator.clone(), request_logs.clone()))
+        .or(beacon_finalized_blocks(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(beacon_states(simulator.clone(), request_logs.clone()))
+        .or(beacon_state(simulator.clone(), request_logs.clone()))
+        .or(beacon_finalized_checkpoint(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(beacon_justified_checkpoint(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(beacon_finality_status(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(beacon_header_pool(simulator.clone(), request_logs.clone()))
+        .or(beacon_validators(simulator.clone(), request_logs.clone()))
+        .or(beacon_deposits(simulator.clone(), request_logs.clone()))
+        .or(beacon_voluntary_exits(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(beacon_proposer_slashings(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(beacon_attester_slashings(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(shards_bid(simulator.clone(), request_logs.clone()))
+        .or(shards_bid_with_data(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(shards_blobs(simulator.clone(), request_logs.clone()))
+        .or(data_submit(simulator.clone(), request_logs.clone()))
+        .or(data_reassemble(simulator.clone(), request_logs.clone()))
+        .or(shards_headers(simulator.clone(), request_logs.clone()))
+        .or(shards_header(simulator.clone(), request_logs.clone()))
+        .or(proofs_commitment(simulator.clone(), request_logs.clone()))
+        .or(config_get(
+            simulator.clone(),
+            request_logs.clone(),
+            config.clone(),
+        ))
+        .or(config_set(
+            simulator.clone(),
+            request_logs.clone(),
+            config.clone(),
+        ))
+        .or(simulator_init(simulator.clone(), request_logs.clone()))
+        .or(simulator_slot_process(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(simulator_slot_process_without_shard_data_inclusion(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(simulator_slot_process_without_shard_blob_proposal(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(simulator_slot_process_without_shard_header_inclusion(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(simulator_slot_process_without_shard_header_confirmation(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(simulator_slot_process_without_beacon_chain_finality(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(simulator_slot_process_without_beacon_block_proposal(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(simulator_slot_process_with_offline_validators(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(simulator_slot_process_random(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(simulator_equivocations(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(simulator_clock(simulator.clone(), request_logs.clone()))
+        .or(simulator_time_advance(
+            simulator.clone(),
+            request_logs.clone(),
+            config.clone(),
+        ))
+        .or(simulator_time_set(
+            simulator.clone(),
+            request_logs.clone(),
+            config.clone(),
+        ))
+        .or(utils_current_status_for_polling(simulator.clone(), config))
+        .or(utils_data_commitment(request_logs.clone()))
+        .or(events(event_sender, request_logs.clone()))
+        .or(eth_beacon_genesis(simulator.clone(), request_logs.clone()))
+        .or(eth_beacon_headers(simulator.clone(), request_logs.clone()))
+        .or(eth_beacon_blocks(simulator.clone(), request_logs.clone()))
+        .or(eth_beacon_states_finality_checkpoints(
+            simulator.clone(),
+            request_logs.clone(),
+        ))
+        .or(eth_node_version(request_logs.clone()))
+        .or(eth_node_syncing(simulator.clone(), request_logs.clone()))
+        .or(eth_config_spec(request_logs.clone()))
+        .or(utils_request_logs(request_logs))
+}
+
+fn with_simulator(
+    simulator: SharedSimulator,
+) -> impl Filter<Extract = (SharedSimulator,), Error = Infallible> + Clone {
+    warp::any().map(move || simulator.clone())
+}
+
+fn with_eth1_chain(
+    eth1_chain: SharedEth1Chain,
+) -> impl Filter<Extract = (SharedEth1Chain,), Error = Infallible> + Clone {
+    warp::any().map(move || eth1_chain.clone())
+}
+
+fn with_request_logs(
+    request_logs: SharedRequestLogs,
+) -> impl Filter<Extract = (SharedRequestLogs,), Error = Infallible> + Clone {
+    warp::any().map(move || request_logs.clone())
+}
+
+fn with_config(
+    config: SharedConfig,
+) -> impl Filter<Extract = (SharedConfig,), Error = Infallible> + Clone {
+    warp::any().map(move || config.clone())
+}
+
+fn with_event_sender(
+    event_sender: EventSender,
+) -> impl Filter<Extract = (EventSender,), Error = Infallible> + Clone {
+    warp::any().map(move || event_sender.clone())
+}
+
+pub fn cors() -> warp::cors::Builder {
+    warp::cors()
+        .allow_any_origin()
+        .allow_credentials(true)
+        .allow_headers(vec!["Content-Type"])
+        .allow_methods(vec!["GET", "POST", "PUT", "DELETE"])
+}
+
+fn log(request_logs: &mut tokio::sync::MutexGuard<Vec<RequestLog>>, endpoint: String) {
+    let log_id = request_logs.len();
+    request_logs.push(RequestLog {
+        log_id,
+        date: Local::now().to_string(),
+        endpoint,
+    });
+}
+
+/// The body of the error responses, e.g.
+/// `{"status": 400, "code": "BID_PAST_SLOT", "message": "...", "next": 12, "found": 10}`.
+#[derive(Serialize)]
+struct ErrorMessage {
+    status: u16,
+    code: &'static str,
+    message: String,
+    #[serde(flatten)]
+    fields: serde_json::Value,
+}
+
+/// The error of the custom rejections.
+fn find_api_error(err: &reject::Rejection) -> Option<&dyn ApiError> {
+    if let Some(e) = err.find::<NotFoundError>() {
+        Some(e)
+    } else if let Some(e) = err.find::<SlotProcessingError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<BidPublicationError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<DepositError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<VoluntaryExitError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<SlashingError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<ConfigSetError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<EventsSubscriptionError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<InvalidPaginationParams>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<IdParsingError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<InvalidSlotParams>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<TimeError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<StoreError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<DataDecodingError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<DataSubmissionError>() {
+        Some(&e.0)
+    } else if let Some(e) = err.find::<DataReassemblyError>() {
+        Some(&e.0)
+    } else {
+        None
+    }
+}
+
+pub async fn handle_rejection(err: reject::Rejection) -> Result<impl warp::Reply, Infallible> {
+    let status;
+    let code;
+    let message;
+    let mut fields = serde_json::json!({});
+
+    if let Some(e) = find_api_error(&err) {
+        status = e.status();
+        code = e.code();
+        message = e.to_string();
+        fields = e.fields();
+    } else if err.is_not_found() {
+        status = StatusCode::NOT_FOUND;
+        code = "NOT_FOUND";
+        message = "not found".into();
+    } else if let Some(e) = err.find::<warp::filters::body::BodyDeserializeError>() {
+        status = StatusCode::BAD_REQUEST;
+        code = "INVALID_BODY";
+        message = e.to_string();
+    } else if let Some(e) = err.find::<reject::InvalidQuery>() {
+        status = StatusCode::BAD_REQUEST;
+        code = "INVALID_QUERY";
+        message = e.to_string();
+    } else if let Some(e) = err.find::<reject::MissingHeader>() {
+        status = StatusCode::BAD_REQUEST;
+        code = "MISSING_HEADER";
+        message = e.to_string();
+        fields = serde_json::json!({ "header": e.name() });
+    } else if let Some(e) = err.find::<reject::InvalidHeader>() {
+        status = StatusCode::BAD_REQUEST;
+        code = "INVALID_HEADER";
+        message = e.to_string();
+        fields = serde_json::json!({ "header": e.name() });
+    } else if let Some(e) = err.find::<reject::LengthRequired>() {
+        status = StatusCode::LENGTH_REQUIRED;
+        code = "LENGTH_REQUIRED";
+        message = e.to_string();
+    } else if let Some(e) = err.find::<reject::PayloadTooLarge>() {
+        status = StatusCode::PAYLOAD_TOO_LARGE;
+        code = "PAYLOAD_TOO_LARGE";
+        message = e.to_string();
+    } else if let Some(e) = err.find::<reject::UnsupportedMediaType>() {
+        status = StatusCode::UNSUPPORTED_MEDIA_TYPE;
+        code = "UNSUPPORTED_MEDIA_TYPE";
+        message = e.to_string();
+    } else if let Some(e) = err.find::<reject::MethodNotAllowed>() {
+        status = StatusCode::METHOD_NOT_ALLOWED;
+        code = "METHOD_NOT_ALLOWED";
+        message = e.to_string();
+    } else {
+        eprintln!("unhandled rejection: {:?}", err);
+        status = StatusCode::INTERNAL_SERVER_ERROR;
+        code = "UNHANDLED_REJECTION";
+        message = "unhandled rejection".into();
+    }
+
+    let json = warp::reply::json(&ErrorMessage {
+        status: status.as_u16(),
+        code,
+        message,
+        fields,
+    });
+
+    Ok(warp::reply::with_status(json, status))
+}
+
+/// GET /
+/// For ping
+pub fn root() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::get().and(warp::path::end().map(|| "root"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mine_due_eth1_blocks_after_time_jump() {
+        let mut eth1_chain = Eth1Chain::new(0, 1);
+        mine_due_eth1_blocks(&mut eth1_chain, 13, 14);
+        assert_eq!(0, eth1_chain.head().number);
+        mine_due_eth1_blocks(&mut eth1_chain, 30, 14);
+        assert_eq!(2, eth1_chain.head().number);
+        assert_eq!(28, eth1_chain.head().timestamp);
+
+        // Only the last blocks are mined after a long jump.
+        let now = 28 + 14 * (MAX_ETH1_BLOCKS_PER_TICK + 100);
+        mine_due_eth1_blocks(&mut eth1_chain, now, 14);
+        assert_eq!(2 + MAX_ETH1_BLOCKS_PER_TICK, eth1_chain.head().number);
+        assert_eq!(now, eth1_chain.head().timestamp);
+        let first = eth1_chain.get_block(3).unwrap();
+        assert_eq!(now - 14 * (MAX_ETH1_BLOCKS_PER_TICK - 1), first.timestamp);
+    }
+
+    #[test]
+    fn process_started_slot_in_auto_mode() {
+        let mut simulator = Simulator::new();
+        simulator.set_time(10_000).unwrap();
+        simulator
+            .set_slot_clock(SlotClock::starting_at(GENESIS_SLOT, 10_000, 1_000))
+            .unwrap();
+        let mut config = Config::default();
+        assert_eq!(Ok(false), process_started_slot(&mut simulator, &config));
+        assert_eq!(GENESIS_SLOT, simulator.slot);
+
+        config.auto = true;
+        assert_eq!(Ok(true), process_started_slot(&mut simulator, &config));
+        assert_eq!(GENESIS_SLOT + 1, simulator.slot);
+        // The next slot has not started yet.
+        assert_eq!(Ok(false), process_started_slot(&mut simulator, &config));
+    }
+}
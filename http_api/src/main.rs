@@ -1,84 +1,12 @@
-#![allow(unused_must_use)]
-use chrono::prelude::*;
 use clap::{load_yaml, App};
-use eth2_simulator::simulator;
-use eth2_simulator::simulator::Simulator;
-use rand::prelude::*;
-use serde_derive::{Deserialize, Serialize};
-use std::convert::Infallible;
+use eth1_simulator::chain::{Eth1Chain, SECONDS_PER_ETH1_BLOCK};
+use eth2_simulator::simulator::{Event, Simulator};
+use eth2_simulator::slot_clock::{SlotClock, MILLIS_PER_SECOND};
+use eth2_simulator::store::DiskStore;
+use http_api::*;
 use std::sync::Arc;
-use std::{thread, time};
-use tokio::sync::Mutex;
-use warp::{http::StatusCode, reject, Filter};
-
-pub use common::bid::Bid;
-pub use common::eth2_config::*;
-pub use common::eth2_types::*;
-
-pub mod errors;
-pub use errors::*;
-pub mod endpoints;
-pub use endpoints::*;
-
-pub type SharedSimulator = Arc<Mutex<Simulator>>;
-
-/// Config for the auto mode.
-#[derive(Clone, Serialize, Deserialize)]
-pub struct Config {
-    auto: bool,
-    /// Slot time in seconds.
-    slot_time: u64,
-    /// Failure rate in the simulation.
-    failure_rate: f32,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            auto: false,
-            slot_time: SECONDS_PER_SLOT,
-            failure_rate: 0.0,
-        }
-    }
-}
-/// Extended config for the auto mode.
-#[derive(Clone)]
-pub struct ExtendedConfig {
-    config: Config,
-    /// The time when the auto mode started.
-    start_time: time::Instant,
-    /// The number of slots processed after the auto mode started.
-    processed_slot: u32,
-}
-
-impl Default for ExtendedConfig {
-    fn default() -> Self {
-        Self {
-            config: Config::default(),
-            start_time: time::Instant::now(),
-            processed_slot: 0,
-        }
-    }
-}
-
-impl ExtendedConfig {
-    fn restart_auto(&mut self) {
-        self.start_time = time::Instant::now();
-        self.processed_slot = 0;
-    }
-}
-
-pub type SharedConfig = Arc<Mutex<ExtendedConfig>>;
-
-#[derive(Serialize, Clone)]
-pub struct RequestLog {
-    log_id: usize,
-    date: String,
-    endpoint: String,
-    // request_body: String,
-    // response_body: String,
-}
-pub type SharedRequestLogs = Arc<Mutex<Vec<RequestLog>>>;
+use tokio::sync::{broadcast, Mutex};
+use warp::Filter;
 
 #[tokio::main]
 async fn main() {
@@ -89,12 +17,12 @@ async fn main() {
     let yaml = load_yaml!("cli.yaml");
     let matches = App::from(yaml).get_matches();
 
-    let mut config = ExtendedConfig::default();
+    let mut config = Config::default();
     if matches.is_present("auto") {
-        config.config.auto = true;
+        config.auto = true;
 
         if let Some(val) = matches.value_of("slot-time") {
-            config.config.slot_time = val.parse().expect("SLOT_TIME must be `u64`.");
+            config.slot_time = val.parse().expect("SLOT_TIME must be `f64`.");
         }
 
         if let Some(val) = matches.value_of("failure-rate") {
@@ -103,21 +31,119 @@ async fn main() {
                 (0.0..=1.0).contains(&failure_rate),
                 "FAILURE_RATE must be a positive float <= 1.0."
             );
-            config.config.failure_rate = failure_rate;
+            config.failure_rate = failure_rate;
+            config.failure_model.preset = failure_rate as f64;
         }
         println!("Simulator started in auto mode.");
     } else {
         println!("Simulator started in manual mode.");
     };
 
-    let mut simulator = Simulator::new();
-    // Process the genesis slot.
-    simulator.process_slots_happy(0);
-    println!("Slot 0 is automatically processed.");
+    let mut simulator = if let Some(datadir) = matches.value_of("datadir") {
+        let store = DiskStore::open(datadir).expect("Failed to open DATADIR.");
+        Simulator::with_store(Box::new(store)).expect("Failed to load the chain in DATADIR.")
+    } else {
+        Simulator::new()
+    };
+    if matches.is_present("prune-states") {
+        simulator.beacon_chain.state_pruning = true;
+    }
+    if matches.is_present("virtual-time") {
+        // The virtual time starts from the wall-clock time and stands still until it is advanced.
+        simulator.advance_time(0);
+        println!("Simulator uses the virtual time.");
+    }
+    // Resume with the slot clock stored in DATADIR to keep the genesis time.
+    let stored_slot_clock = simulator.stored_slot_clock();
+    if let Some(stored_slot_clock) = stored_slot_clock {
+        if !matches.is_present("slot-time") {
+            config.slot_time = stored_slot_clock.slot_duration_ms as f64 / MILLIS_PER_SECOND as f64;
+        }
+    }
+    let slot_duration_ms = config
+        .slot_duration_ms()
+        .expect("SLOT_TIME must be at least 0.001.");
+    let now_ms = simulator.now_ms();
+    let slot_clock = match stored_slot_clock {
+        Some(stored_slot_clock)
+            if !matches.is_present("reanchor-clock")
+                && stored_slot_clock.slot_duration_ms == slot_duration_ms
+                && can_catch_up(&stored_slot_clock, simulator.slot, now_ms) =>
+        {
+            stored_slot_clock
+        }
+        // The next slot starts now.
+        _ => SlotClock::starting_at(simulator.slot, now_ms, slot_duration_ms),
+    };
+    if stored_slot_clock.map_or(false, |stored_slot_clock| stored_slot_clock != slot_clock) {
+        println!("Slot clock is re-anchored.");
+    }
+    simulator
+        .set_slot_clock(slot_clock)
+        .expect("Failed to store the slot clock in DATADIR.");
+    println!("Genesis time is {}.", simulator.slot_clock.genesis_time());
+    if simulator.slot == GENESIS_SLOT {
+        // Process the genesis slot.
+        simulator
+            .process_slots_happy(0)
+            .expect("Failed to process the genesis slot.");
+        println!("Slot 0 is automatically processed.");
+    } else {
+        println!(
+            "Simulator resumed from the stored chain. Slot {} is the next slot.",
+            simulator.slot
+        );
+    }
+
+    let (event_sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+    let sender = event_sender.clone();
+    simulator.subscribe(Box::new(move |event: &Event| {
+        // Note: Sending fails only if there is no subscriber.
+        let _ = sender.send(event.clone());
+    }));
 
     let shared_simulator = Arc::new(Mutex::new(simulator));
     let shared_config = Arc::new(Mutex::new(config));
 
+    if matches.is_present("eth1") {
+        let block_time = if let Some(val) = matches.value_of("eth1-block-time") {
+            val.parse().expect("ETH1_BLOCK_TIME must be `u64`.")
+        } else {
+            SECONDS_PER_ETH1_BLOCK
+        };
+        assert!(
+            0 < block_time,
+            "ETH1_BLOCK_TIME must be a positive integer."
+        );
+        let oracle_lag = if let Some(val) = matches.value_of("eth1-oracle-lag") {
+            val.parse().expect("ETH1_ORACLE_LAG must be `u64`.")
+        } else {
+            1
+        };
+        let eth1_port: u16 = if let Some(port) = matches.value_of("eth1-port") {
+            port.parse()
+                .expect("`eth1-port` must be a positive integer")
+        } else {
+            8545
+        };
+        let genesis_timestamp = shared_simulator.lock().await.now_ms() / MILLIS_PER_SECOND;
+        let eth1_chain = Arc::new(Mutex::new(Eth1Chain::new(genesis_timestamp, oracle_lag)));
+        tokio::spawn(mine_eth1_blocks(
+            eth1_chain.clone(),
+            shared_simulator.clone(),
+            block_time,
+        ));
+        tokio::spawn(feed_beacon_oracle(
+            eth1_chain.clone(),
+            event_sender.subscribe(),
+        ));
+        let eth1_routes = eth1_json_rpc(eth1_chain)
+            .recover(handle_rejection)
+            .with(cors());
+        tokio::spawn(warp::serve(eth1_routes).run(([127, 0, 0, 1], eth1_port)));
+        println!("Eth1 JSON-RPC server started on port {}.", eth1_port);
+    }
+
     let simulator = shared_simulator.clone();
     let config = shared_config.clone();
     tokio::spawn(async move {
@@ -126,9 +152,12 @@ async fn main() {
 
     let shared_request_logs = Arc::new(Mutex::new(Vec::<RequestLog>::new()));
 
-    let routes = filters(shared_simulator, shared_request_logs, shared_config)
-        .recover(handle_rejection)
-        .with(cors());
+    let routes = routes(
+        shared_simulator,
+        shared_request_logs,
+        shared_config,
+        event_sender,
+    );
 
     let port = if let Some(port) = matches.value_of("port") {
         port.parse().expect("`port` must be a positive integer")
@@ -138,188 +167,3 @@ async fn main() {
 
     warp::serve(routes).run(([127, 0, 0, 1], port)).await;
 }
-
-async fn process_auto(simulator: SharedSimulator, config: SharedConfig) {
-    let ten_millis = time::Duration::from_millis(10);
-    loop {
-        let mut config = config.lock().await;
-        let next_slot_time = config.start_time
-            + time::Duration::from_secs(config.config.slot_time) * config.processed_slot;
-        if !config.config.auto || time::Instant::now() < next_slot_time {
-            // Wait 0.01 seconds.
-            thread::sleep(ten_millis);
-            continue;
-        }
-        let mut simulator = simulator.lock().await;
-        let slot = simulator.slot;
-        println!("Auto processing. Slot {}", slot);
-        let mut rng = rand::thread_rng();
-        if rng.gen_range(0.0..1.0) < config.config.failure_rate {
-            // TODO: Remove happy case from `process_random`.
-            simulator.process_slots_random(slot);
-        } else {
-            simulator.process_slots_happy(slot);
-        };
-        config.processed_slot += 1;
-    }
-}
-
-pub fn filters(
-    simulator: SharedSimulator,
-    request_logs: SharedRequestLogs,
-    config: SharedConfig,
-) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
-    root()
-        .or(beacon_blocks(simulator.clone(), request_logs.clone()))
-        .or(beacon_blocks_head(simulator.clone(), request_logs.clone()))
-        .or(beacon_finalized_blocks(
-            simulator.clone(),
-            request_logs.clone(),
-        ))
-        .or(beacon_states(simulator.clone(), request_logs.clone()))
-        .or(beacon_finalized_checkpoint(
-            simulator.clone(),
-            request_logs.clone(),
-        ))
-        .or(shards_bid(simulator.clone(), request_logs.clone()))
-        .or(shards_bid_with_data(
-            simulator.clone(),
-            request_logs.clone(),
-        ))
-        .or(config_get(request_logs.clone(), config.clone()))
-        .or(config_set(request_logs.clone(), config.clone()))
-        .or(simulator_init(
-            simulator.clone(),
-            request_logs.clone(),
-            config.clone(),
-        ))
-        .or(simulator_slot_process(
-            simulator.clone(),
-            request_logs.clone(),
-            config.clone(),
-        ))
-        .or(simulator_slot_process_without_shard_data_inclusion(
-            simulator.clone(),
-            request_logs.clone(),
-            config.clone(),
-        ))
-        .or(simulator_slot_process_without_shard_blob_proposal(
-            simulator.clone(),
-            request_logs.clone(),
-            config.clone(),
-        ))
-        .or(simulator_slot_process_without_shard_header_inclusion(
-            simulator.clone(),
-            request_logs.clone(),
-            config.clone(),
-        ))
-        .or(simulator_slot_process_without_shard_header_confirmation(
-            simulator.clone(),
-            request_logs.clone(),
-            config.clone(),
-        ))
-        .or(simulator_slot_process_without_beacon_chain_finality(
-            simulator.clone(),
-            request_logs.clone(),
-            config.clone(),
-        ))
-        .or(simulator_slot_process_without_beacon_block_proposal(
-            simulator.clone(),
-            request_logs.clone(),
-            config.clone(),
-        ))
-        .or(simulator_slot_process_random(
-            simulator.clone(),
-            request_logs.clone(),
-            config.clone(),
-        ))
-        .or(utils_current_status_for_polling(simulator, config))
-        .or(utils_data_commitment(request_logs.clone()))
-        .or(utils_request_logs(request_logs))
-}
-
-fn with_simulator(
-    simulator: SharedSimulator,
-) -> impl Filter<Extract = (SharedSimulator,), Error = Infallible> + Clone {
-    warp::any().map(move || simulator.clone())
-}
-
-fn with_request_logs(
-    request_logs: SharedRequestLogs,
-) -> impl Filter<Extract = (SharedRequestLogs,), Error = Infallible> + Clone {
-    warp::any().map(move || request_logs.clone())
-}
-
-fn with_config(
-    config: SharedConfig,
-) -> impl Filter<Extract = (SharedConfig,), Error = Infallible> + Clone {
-    warp::any().map(move || config.clone())
-}
-
-fn cors() -> warp::cors::Builder {
-    warp::cors()
-        .allow_any_origin()
-        .allow_credentials(true)
-        .allow_headers(vec!["Content-Type"])
-        .allow_methods(vec!["GET", "POST", "PUT", "DELETE"])
-}
-
-fn log(request_logs: &mut tokio::sync::MutexGuard<Vec<RequestLog>>, endpoint: String) {
-    let log_id = request_logs.len();
-    request_logs.push(RequestLog {
-        log_id,
-        date: Local::now().to_string(),
-        endpoint,
-    });
-}
-
-#[derive(Serialize)]
-struct ErrorMessage {
-    code: u16,
-    message: String,
-}
-
-async fn handle_rejection(err: reject::Rejection) -> Result<impl warp::Reply, Infallible> {
-    let code;
-    let message;
-
-    if err.is_not_found() {
-        code = StatusCode::NOT_FOUND;
-        message = "NOT_FOUND".into();
-    } else if let Some(e) = err.find::<SlotProcessingError>() {
-        code = StatusCode::BAD_REQUEST;
-        message = format!("BAD_REQUEST: {:?}", e);
-    } else if let Some(e) = err.find::<BidPublicationError>() {
-        code = StatusCode::BAD_REQUEST;
-        message = format!("BAD_REQUEST: {:?}", e);
-    } else if let Some(e) = err.find::<ConfigSetError>() {
-        code = StatusCode::BAD_REQUEST;
-        message = format!("BAD_REQUEST: {:?}", e);
-    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
-        code = StatusCode::METHOD_NOT_ALLOWED;
-        message = "METHOD_NOT_ALLOWED".into();
-    } else {
-        eprintln!("unhandled rejection: {:?}", err);
-        code = StatusCode::INTERNAL_SERVER_ERROR;
-        message = "UNHANDLED_REJECTION".into();
-    }
-
-    let json = warp::reply::json(&ErrorMessage {
-        code: code.as_u16(),
-        message,
-    });
-
-    Ok(warp::reply::with_status(json, code))
-}
-
-/// GET /
-/// For ping
-pub fn root() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
-    warp::get().and(warp::path::end().map(|| "root"))
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct CountAndPageParams {
-    count: Option<Slot>,
-    page: Option<usize>,
-}
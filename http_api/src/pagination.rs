@@ -0,0 +1,213 @@
+//! Pagination of the listings of blocks and states.
+//! Items are paginated by slot, so a page has `limit` items even if slots are skipped.
+use crate::*;
+pub use common::pagination::*;
+use std::cmp::Ordering;
+
+/// Selection of the page of the listing by the query parameters.
+pub trait Paginate {
+    /// Select the page from the items sorted in ascending order by slot.
+    /// The items in the page are sorted in the requested order.
+    fn paginate<'a, T, F>(
+        &self,
+        items: &'a [T],
+        slot_of: F,
+    ) -> Result<Page<&'a T>, PaginationError>
+    where
+        F: Fn(&T) -> Slot;
+}
+
+impl Paginate for PaginationParams {
+    fn paginate<'a, T, F>(&self, items: &'a [T], slot_of: F) -> Result<Page<&'a T>, PaginationError>
+    where
+        F: Fn(&T) -> Slot,
+    {
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        if limit == 0 || MAX_PAGE_LIMIT < limit {
+            return Err(PaginationError::InvalidLimit { found: limit });
+        }
+        let order = self.order.unwrap_or(Order::Desc);
+        let mut from_slot = self.from_slot.unwrap_or(GENESIS_SLOT);
+        let mut to_slot = self.to_slot.unwrap_or(Slot::MAX);
+        if let Some(cursor) = self.cursor {
+            match order {
+                Order::Asc => from_slot = from_slot.max(cursor),
+                Order::Desc => to_slot = to_slot.min(cursor),
+            }
+        }
+
+        let start = count_before(items, &slot_of, from_slot);
+        let end = if to_slot == Slot::MAX {
+            items.len()
+        } else {
+            count_before(items, &slot_of, to_slot + 1)
+        };
+        let items: &'a [T] = if start < end { &items[start..end] } else { &[] };
+        let page = match order {
+            Order::Asc => Page {
+                data: items.iter().take(limit).collect(),
+                next: items.get(limit).map(&slot_of),
+            },
+            Order::Desc => Page {
+                data: items.iter().rev().take(limit).collect(),
+                next: items.iter().rev().nth(limit).map(&slot_of),
+            },
+        };
+        Ok(page)
+    }
+}
+
+/// The number of the items before the slot.
+fn count_before<T, F>(items: &[T], slot_of: &F, slot: Slot) -> usize
+where
+    F: Fn(&T) -> Slot,
+{
+    match items.binary_search_by(|item| {
+        if slot_of(item) < slot {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    }) {
+        Ok(index) | Err(index) => index,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Slots 2, 5 and 6 are skipped.
+    const SLOTS: [Slot; 7] = [0, 1, 3, 4, 7, 8, 9];
+
+    fn params(
+        from_slot: Option<Slot>,
+        to_slot: Option<Slot>,
+        limit: usize,
+        order: Order,
+        cursor: Option<Slot>,
+    ) -> PaginationParams {
+        PaginationParams {
+            from_slot,
+            to_slot,
+            limit: Some(limit),
+            order: Some(order),
+            cursor,
+        }
+    }
+
+    fn paginate(params: &PaginationParams) -> (Vec<Slot>, Option<Slot>) {
+        let page = params.paginate(&SLOTS, |slot| *slot).unwrap();
+        (page.data.into_iter().copied().collect(), page.next)
+    }
+
+    #[test]
+    fn paginate_skipped_slots_asc() {
+        assert_eq!(
+            (vec![0, 1, 3], Some(4)),
+            paginate(&params(None, None, 3, Order::Asc, None))
+        );
+        assert_eq!(
+            (vec![4, 7, 8], Some(9)),
+            paginate(&params(None, None, 3, Order::Asc, Some(4)))
+        );
+        assert_eq!(
+            (vec![9], None),
+            paginate(&params(None, None, 3, Order::Asc, Some(9)))
+        );
+        // A cursor at a skipped slot starts from the next item.
+        assert_eq!(
+            (vec![7, 8, 9], None),
+            paginate(&params(None, None, 3, Order::Asc, Some(5)))
+        );
+    }
+
+    #[test]
+    fn paginate_skipped_slots_desc() {
+        assert_eq!(
+            (vec![9, 8, 7], Some(4)),
+            paginate(&params(None, None, 3, Order::Desc, None))
+        );
+        assert_eq!(
+            (vec![4, 3, 1], Some(0)),
+            paginate(&params(None, None, 3, Order::Desc, Some(4)))
+        );
+        assert_eq!(
+            (vec![0], None),
+            paginate(&params(None, None, 3, Order::Desc, Some(0)))
+        );
+        // A cursor at a skipped slot starts from the previous item.
+        assert_eq!(
+            (vec![4, 3, 1], Some(0)),
+            paginate(&params(None, None, 3, Order::Desc, Some(6)))
+        );
+    }
+
+    #[test]
+    fn paginate_next_at_end() {
+        // The page ends exactly at the last item.
+        assert_eq!(
+            (SLOTS.to_vec(), None),
+            paginate(&params(None, None, SLOTS.len(), Order::Asc, None))
+        );
+        assert_eq!(
+            (SLOTS.iter().rev().copied().collect(), None),
+            paginate(&params(None, None, SLOTS.len(), Order::Desc, None))
+        );
+        // One item is left.
+        assert_eq!(
+            (SLOTS[..6].to_vec(), Some(9)),
+            paginate(&params(None, None, SLOTS.len() - 1, Order::Asc, None))
+        );
+        assert_eq!(
+            (vec![], None),
+            paginate(&params(Some(10), None, 3, Order::Asc, None))
+        );
+    }
+
+    #[test]
+    fn paginate_bounds() {
+        // The bounds are inclusive, and can be skipped slots.
+        assert_eq!(
+            (vec![3, 4, 7], None),
+            paginate(&params(Some(2), Some(7), 3, Order::Asc, None))
+        );
+        assert_eq!(
+            (vec![7, 4], Some(3)),
+            paginate(&params(Some(2), Some(7), 2, Order::Desc, None))
+        );
+        assert_eq!(
+            (vec![3], None),
+            paginate(&params(Some(2), Some(7), 2, Order::Desc, Some(3)))
+        );
+        assert_eq!(
+            (vec![], None),
+            paginate(&params(Some(5), Some(6), 3, Order::Asc, None))
+        );
+        assert_eq!(
+            (vec![], None),
+            paginate(&params(Some(8), Some(3), 3, Order::Asc, None))
+        );
+        // The cursor out of the bounds does not extend the bounds.
+        assert_eq!(
+            (vec![4, 7], Some(8)),
+            paginate(&params(Some(4), Some(8), 2, Order::Asc, Some(1)))
+        );
+        assert_eq!(
+            (vec![8, 7], Some(4)),
+            paginate(&params(Some(4), Some(8), 2, Order::Desc, Some(20)))
+        );
+    }
+
+    #[test]
+    fn paginate_invalid_limit() {
+        for limit in [0, MAX_PAGE_LIMIT + 1].iter() {
+            let result =
+                params(None, None, *limit, Order::Asc, None).paginate(&SLOTS, |slot| *slot);
+            assert!(matches!(
+                result,
+                Err(PaginationError::InvalidLimit { found }) if found == *limit
+            ));
+        }
+    }
+}
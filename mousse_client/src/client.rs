@@ -0,0 +1,640 @@
+use crate::*;
+use futures::{Stream, StreamExt};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_derive::Serialize;
+use serde_json::Map;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Serialize)]
+struct BidWithData<'a> {
+    bid: &'a Bid,
+    data: String,
+}
+
+#[derive(Serialize)]
+struct BlobParams {
+    slot: Slot,
+    fee: Gwei,
+    point: String,
+    length: u64,
+}
+
+#[derive(Serialize)]
+struct DataSubmission {
+    data: String,
+    fee: Gwei,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shards: Option<Vec<Shard>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slots: Option<Vec<Slot>>,
+}
+
+#[derive(Serialize)]
+struct EquivocationsParams<'a> {
+    proposer_indices: &'a [ValidatorIndex],
+    attester_indices: &'a [ValidatorIndex],
+}
+
+/// Client of the HTTP API.
+/// The error responses of the bids and the slot processing are returned as the errors of the simulator.
+#[derive(Debug, Clone)]
+pub struct Client {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl Client {
+    /// The client of the server at `base_url` (e.g. `http://localhost:3030`).
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Send the request and turn the error response into `ClientError`.
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, ClientError> {
+        let response = request.send().await?;
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let body = response.bytes().await?;
+        // Note: The body is not an error response only if the server is not the emulator.
+        let error =
+            serde_json::from_slice::<ErrorResponse>(&body).unwrap_or_else(|_| ErrorResponse {
+                status: status.as_u16(),
+                code: String::new(),
+                message: String::from_utf8_lossy(&body).into_owned(),
+                fields: Map::new(),
+            });
+        Err(error.into())
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        let response = self.send(self.http.get(self.url(path))).await?;
+        Ok(response.json().await?)
+    }
+
+    /// `None` if the resource is not found.
+    async fn get_optional<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<Option<T>, ClientError> {
+        match self.get(path).await {
+            Ok(value) => Ok(Some(value)),
+            Err(ClientError::Api(e)) if e.status == StatusCode::NOT_FOUND.as_u16() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn get_page<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &PaginationParams,
+    ) -> Result<Page<T>, ClientError> {
+        let request = self.http.get(self.url(path)).query(params);
+        Ok(self.send(request).await?.json().await?)
+    }
+
+    async fn post(&self, path: &str) -> Result<(), ClientError> {
+        self.send(self.http.post(self.url(path))).await?;
+        Ok(())
+    }
+
+    async fn post_json<B: Serialize>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<reqwest::Response, ClientError> {
+        self.send(self.http.post(self.url(path)).json(body)).await
+    }
+
+    /// GET /beacon/blocks
+    pub async fn blocks(
+        &self,
+        params: &PaginationParams,
+    ) -> Result<Page<BeaconBlock>, ClientError> {
+        self.get_page("/beacon/blocks", params).await
+    }
+
+    /// GET /beacon/blocks/head
+    pub async fn head(&self) -> Result<Option<BeaconBlock>, ClientError> {
+        self.get("/beacon/blocks/head").await
+    }
+
+    /// GET /beacon/blocks/{slot|root}
+    pub async fn block(&self, block_id: &str) -> Result<Option<BeaconBlock>, ClientError> {
+        self.get_optional(&format!("/beacon/blocks/{}", block_id))
+            .await
+    }
+
+    /// GET /beacon/headers/{slot|root}
+    pub async fn header(
+        &self,
+        block_id: &str,
+    ) -> Result<Option<BeaconBlockHeaderWithRoot>, ClientError> {
+        self.get_optional(&format!("/beacon/headers/{}", block_id))
+            .await
+    }
+
+    /// GET /beacon/finalized_blocks
+    pub async fn finalized_blocks(
+        &self,
+        params: &PaginationParams,
+    ) -> Result<Page<BeaconBlock>, ClientError> {
+        self.get_page("/beacon/finalized_blocks", params).await
+    }
+
+    /// GET /beacon/states
+    pub async fn states(
+        &self,
+        params: &PaginationParams,
+    ) -> Result<Page<BeaconState>, ClientError> {
+        self.get_page("/beacon/states", params).await
+    }
+
+    /// GET /beacon/states/{slot|root}
+    pub async fn state(&self, state_id: &str) -> Result<Option<BeaconState>, ClientError> {
+        self.get_optional(&format!("/beacon/states/{}", state_id))
+            .await
+    }
+
+    /// GET /beacon/finalized_checkpoint
+    pub async fn finalized_checkpoint(&self) -> Result<Checkpoint, ClientError> {
+        self.get("/beacon/finalized_checkpoint").await
+    }
+
+    /// GET /beacon/justified_checkpoint
+    pub async fn justified_checkpoint(&self) -> Result<Checkpoint, ClientError> {
+        self.get("/beacon/justified_checkpoint").await
+    }
+
+    /// GET /beacon/finality_status
+    pub async fn finality_status(&self) -> Result<FinalityStatus, ClientError> {
+        self.get("/beacon/finality_status").await
+    }
+
+    /// GET /beacon/header_pool
+    pub async fn header_pool(&self) -> Result<HeaderPool, ClientError> {
+        self.get("/beacon/header_pool").await
+    }
+
+    /// GET /beacon/validators
+    pub async fn validators(&self) -> Result<Vec<ValidatorResponse>, ClientError> {
+        self.get("/beacon/validators").await
+    }
+
+    /// POST /beacon/deposits
+    pub async fn deposit(&self, deposit: &Deposit) -> Result<(), ClientError> {
+        self.post_json("/beacon/deposits", deposit).await?;
+        Ok(())
+    }
+
+    /// POST /beacon/voluntary_exits
+    pub async fn voluntary_exit(&self, exit: &VoluntaryExit) -> Result<(), ClientError> {
+        self.post_json("/beacon/voluntary_exits", exit).await?;
+        Ok(())
+    }
+
+    /// POST /beacon/proposer_slashings
+    pub async fn proposer_slashing(&self, slashing: &ProposerSlashing) -> Result<(), ClientError> {
+        self.post_json("/beacon/proposer_slashings", slashing)
+            .await?;
+        Ok(())
+    }
+
+    /// POST /beacon/attester_slashings
+    pub async fn attester_slashing(&self, slashing: &AttesterSlashing) -> Result<(), ClientError> {
+        self.post_json("/beacon/attester_slashings", slashing)
+            .await?;
+        Ok(())
+    }
+
+    /// POST /shards/{shard}/bid
+    pub async fn publish_bid(&self, bid: &Bid) -> Result<(), ClientError> {
+        self.post_json(&format!("/shards/{}/bid", bid.shard), bid)
+            .await?;
+        Ok(())
+    }
+
+    /// POST /shards/{shard}/bid_with_data
+    /// The data is sent in base64.
+    pub async fn publish_bid_with_data(&self, bid: &Bid, data: &[u8]) -> Result<(), ClientError> {
+        let body = BidWithData {
+            bid,
+            data: base64::encode(data),
+        };
+        self.post_json(&format!("/shards/{}/bid_with_data", bid.shard), &body)
+            .await?;
+        Ok(())
+    }
+
+    /// PUT /shards/{shard}/blobs
+    /// The data is streamed in raw bytes, which suits the large blobs.
+    pub async fn upload_blob(&self, bid: &Bid, data: Vec<u8>) -> Result<(), ClientError> {
+        let params = BlobParams {
+            slot: bid.slot,
+            fee: bid.fee,
+            point: hex::encode(&bid.commitment.point[..]),
+            length: bid.commitment.length,
+        };
+        let request = self
+            .http
+            .put(self.url(&format!("/shards/{}/blobs", bid.shard)))
+            .query(&params)
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .body(data);
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// GET /shards/{shard}/headers
+    pub async fn shard_headers(
+        &self,
+        shard: Shard,
+        params: &PaginationParams,
+    ) -> Result<Page<ShardHeaderStatus>, ClientError> {
+        self.get_page(&format!("/shards/{}/headers", shard), params)
+            .await
+    }
+
+    /// GET /shards/{shard}/headers/{slot}
+    pub async fn shard_header(
+        &self,
+        shard: Shard,
+        slot: Slot,
+    ) -> Result<Option<ShardHeaderStatus>, ClientError> {
+        self.get_optional(&format!("/shards/{}/headers/{}", shard, slot))
+            .await
+    }
+
+    /// POST /data/submit
+    /// `None` of `shards` means all the shards, and `None` of `slots` the consecutive slots from the next slot.
+    pub async fn submit_data(
+        &self,
+        data: &[u8],
+        fee: Gwei,
+        shards: Option<Vec<Shard>>,
+        slots: Option<Vec<Slot>>,
+    ) -> Result<Manifest, ClientError> {
+        let body = DataSubmission {
+            data: base64::encode(data),
+            fee,
+            shards,
+            slots,
+        };
+        Ok(self.post_json("/data/submit", &body).await?.json().await?)
+    }
+
+    /// POST /data/reassemble
+    pub async fn reassemble_data(&self, manifest: &Manifest) -> Result<Vec<u8>, ClientError> {
+        let response = self.post_json("/data/reassemble", manifest).await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// GET /proofs/commitment/{shard}/{slot}
+    pub async fn commitment_proof(
+        &self,
+        shard: Shard,
+        slot: Slot,
+    ) -> Result<Option<CommitmentProof>, ClientError> {
+        self.get_optional(&format!("/proofs/commitment/{}/{}", shard, slot))
+            .await
+    }
+
+    /// GET /config
+    pub async fn config(&self) -> Result<ConfigResponse, ClientError> {
+        self.get("/config").await
+    }
+
+    /// POST /config
+    pub async fn set_config(&self, options: &ConfigOptions) -> Result<(), ClientError> {
+        self.post_json("/config", options).await?;
+        Ok(())
+    }
+
+    /// POST /simulator/init
+    pub async fn init(&self) -> Result<(), ClientError> {
+        self.post("/simulator/init").await
+    }
+
+    /// POST /simulator/slot/process/{slot}
+    pub async fn process_slots(&self, slot: Slot) -> Result<(), ClientError> {
+        self.post(&format!("/simulator/slot/process/{}", slot))
+            .await
+    }
+
+    /// POST /simulator/slot/process_without_shard_data_inclusion/{slot}
+    pub async fn process_slots_without_shard_data_inclusion(
+        &self,
+        slot: Slot,
+    ) -> Result<(), ClientError> {
+        self.post(&format!(
+            "/simulator/slot/process_without_shard_data_inclusion/{}",
+            slot
+        ))
+        .await
+    }
+
+    /// POST /simulator/slot/process_without_shard_blob_proposal/{slot}
+    pub async fn process_slots_without_shard_blob_proposal(
+        &self,
+        slot: Slot,
+    ) -> Result<(), ClientError> {
+        self.post(&format!(
+            "/simulator/slot/process_without_shard_blob_proposal/{}",
+            slot
+        ))
+        .await
+    }
+
+    /// POST /simulator/slot/process_without_shard_header_inclusion/{slot}
+    pub async fn process_slots_without_shard_header_inclusion(
+        &self,
+        slot: Slot,
+    ) -> Result<(), ClientError> {
+        self.post(&format!(
+            "/simulator/slot/process_without_shard_header_inclusion/{}",
+            slot
+        ))
+        .await
+    }
+
+    /// POST /simulator/slot/process_without_shard_header_confirmation/{slot}
+    pub async fn process_slots_without_shard_header_confirmation(
+        &self,
+        slot: Slot,
+    ) -> Result<(), ClientError> {
+        self.post(&format!(
+            "/simulator/slot/process_without_shard_header_confirmation/{}",
+            slot
+        ))
+        .await
+    }
+
+    /// POST /simulator/slot/process_without_beacon_chain_finality/{slot}
+    pub async fn process_slots_without_beacon_chain_finality(
+        &self,
+        slot: Slot,
+    ) -> Result<(), ClientError> {
+        self.post(&format!(
+            "/simulator/slot/process_without_beacon_chain_finality/{}",
+            slot
+        ))
+        .await
+    }
+
+    /// POST /simulator/slot/process_without_beacon_block_proposal/{slot}
+    pub async fn process_slots_without_beacon_block_proposal(
+        &self,
+        slot: Slot,
+    ) -> Result<(), ClientError> {
+        self.post(&format!(
+            "/simulator/slot/process_without_beacon_block_proposal/{}",
+            slot
+        ))
+        .await
+    }
+
+    /// POST /simulator/slot/process_with_offline_validators/{slot}?rate={rate}
+    pub async fn process_slots_with_offline_validators(
+        &self,
+        slot: Slot,
+        rate: f32,
+    ) -> Result<(), ClientError> {
+        let request = self
+            .http
+            .post(self.url(&format!(
+                "/simulator/slot/process_with_offline_validators/{}",
+                slot
+            )))
+            .query(&[("rate", rate)]);
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// POST /simulator/slot/process_random/{slot}
+    pub async fn process_slots_random(&self, slot: Slot) -> Result<(), ClientError> {
+        self.post(&format!("/simulator/slot/process_random/{}", slot))
+            .await
+    }
+
+    /// POST /simulator/equivocations
+    pub async fn simulate_equivocations(
+        &self,
+        proposer_indices: &[ValidatorIndex],
+        attester_indices: &[ValidatorIndex],
+    ) -> Result<(), ClientError> {
+        let body = EquivocationsParams {
+            proposer_indices,
+            attester_indices,
+        };
+        self.post_json("/simulator/equivocations", &body).await?;
+        Ok(())
+    }
+
+    /// GET /simulator/clock
+    pub async fn clock(&self) -> Result<Clock, ClientError> {
+        self.get("/simulator/clock").await
+    }
+
+    /// POST /simulator/time/advance?seconds={seconds}
+    pub async fn advance_time(&self, seconds: f64) -> Result<(), ClientError> {
+        let request = self
+            .http
+            .post(self.url("/simulator/time/advance"))
+            .query(&[("seconds", seconds)]);
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// POST /simulator/time/set?time={unix_time}
+    pub async fn set_time(&self, time: f64) -> Result<(), ClientError> {
+        let request = self
+            .http
+            .post(self.url("/simulator/time/set"))
+            .query(&[("time", time)]);
+        self.send(request).await?;
+        Ok(())
+    }
+
+    /// GET /utils/current_status_for_polling
+    pub async fn current_status(&self) -> Result<CurrentStatus, ClientError> {
+        self.get("/utils/current_status_for_polling").await
+    }
+
+    /// POST /utils/data_commitment
+    /// The data is sent in raw bytes.
+    pub async fn data_commitment(&self, data: Vec<u8>) -> Result<DataCommitment, ClientError> {
+        let request = self
+            .http
+            .post(self.url("/utils/data_commitment"))
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .body(data);
+        Ok(self.send(request).await?.json().await?)
+    }
+
+    /// GET /utils/request_logs
+    pub async fn request_logs(&self) -> Result<Vec<RequestLog>, ClientError> {
+        self.get("/utils/request_logs").await
+    }
+
+    /// GET /events?topics={topics}
+    /// The stream of the events of the topics (e.g. `head`, `finalized_checkpoint`).
+    pub async fn events(
+        &self,
+        topics: &[&str],
+    ) -> Result<impl Stream<Item = Result<ServerEvent, ClientError>>, ClientError> {
+        let request = self
+            .http
+            .get(self.url("/events"))
+            .query(&[("topics", topics.join(","))]);
+        let bytes = Box::pin(self.send(request).await?.bytes_stream());
+        Ok(futures::stream::unfold(
+            (bytes, Vec::new()),
+            |(mut bytes, mut buffer)| async move {
+                loop {
+                    // The events are separated by a blank line.
+                    if let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                        let block: Vec<u8> = buffer.drain(..end + 2).collect();
+                        match parse_event(&block[..end]) {
+                            Some(event) => return Some((event, (bytes, buffer))),
+                            // Keep-alive comment.
+                            None => continue,
+                        }
+                    }
+                    match bytes.next().await {
+                        Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                        Some(Err(e)) => return Some((Err(e.into()), (bytes, buffer))),
+                        None => return None,
+                    }
+                }
+            },
+        ))
+    }
+
+    /// GET /eth/v1/beacon/genesis
+    pub async fn eth_genesis(&self) -> Result<Data<Genesis>, ClientError> {
+        self.get("/eth/v1/beacon/genesis").await
+    }
+
+    /// GET /eth/v1/beacon/headers/{block_id}
+    pub async fn eth_header(
+        &self,
+        block_id: &str,
+    ) -> Result<Data<BlockHeaderResponse>, ClientError> {
+        self.get(&format!("/eth/v1/beacon/headers/{}", block_id))
+            .await
+    }
+
+    /// GET /eth/v2/beacon/blocks/{block_id}
+    pub async fn eth_block(&self, block_id: &str) -> Result<BlockResponse, ClientError> {
+        self.get(&format!("/eth/v2/beacon/blocks/{}", block_id))
+            .await
+    }
+
+    /// GET /eth/v1/beacon/states/{state_id}/finality_checkpoints
+    pub async fn eth_finality_checkpoints(
+        &self,
+        state_id: &str,
+    ) -> Result<Data<FinalityCheckpoints>, ClientError> {
+        self.get(&format!(
+            "/eth/v1/beacon/states/{}/finality_checkpoints",
+            state_id
+        ))
+        .await
+    }
+
+    /// GET /eth/v1/node/version
+    pub async fn eth_node_version(&self) -> Result<Data<Version>, ClientError> {
+        self.get("/eth/v1/node/version").await
+    }
+
+    /// GET /eth/v1/node/syncing
+    pub async fn eth_node_syncing(&self) -> Result<Data<Syncing>, ClientError> {
+        self.get("/eth/v1/node/syncing").await
+    }
+
+    /// GET /eth/v1/config/spec
+    pub async fn eth_config_spec(&self) -> Result<Data<BTreeMap<String, String>>, ClientError> {
+        self.get("/eth/v1/config/spec").await
+    }
+
+    /// Wait until the slot is finalized, polling the finalized checkpoint every `poll_interval`.
+    /// Return the finalized checkpoint, or `FinalityTimeout` if the slot is not finalized in `timeout`.
+    pub async fn wait_for_finality(
+        &self,
+        slot: Slot,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<Checkpoint, ClientError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let checkpoint = self.finalized_checkpoint().await?;
+            if compute_start_slot_at_epoch(checkpoint.epoch) >= slot {
+                return Ok(checkpoint);
+            }
+            if Instant::now() >= deadline {
+                return Err(ClientError::FinalityTimeout {
+                    slot,
+                    epoch: checkpoint.epoch,
+                });
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
+
+/// Parse the lines of an event of the event stream. `None` if it has only comments.
+fn parse_event(block: &[u8]) -> Option<Result<ServerEvent, ClientError>> {
+    let block = match std::str::from_utf8(block) {
+        Ok(block) => block,
+        Err(e) => return Some(Err(ClientError::InvalidEvent(e.to_string()))),
+    };
+    let mut topic = None;
+    let mut data: Option<String> = None;
+    for line in block.lines() {
+        if line.is_empty() || line.starts_with(':') {
+            continue;
+        }
+        let (field, value) = match line.find(':') {
+            Some(i) => (&line[..i], line[i + 1..].trim_start_matches(' ')),
+            None => (line, ""),
+        };
+        match field {
+            "event" => topic = Some(value.to_string()),
+            "data" => match data.as_mut() {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => data = Some(value.to_string()),
+            },
+            _ => (),
+        }
+    }
+    if topic.is_none() && data.is_none() {
+        return None;
+    }
+    let topic = topic.unwrap_or_else(|| String::from("message"));
+    let data = match serde_json::from_str(&data.unwrap_or_default()) {
+        Ok(data) => data,
+        Err(e) => {
+            return Some(Err(ClientError::InvalidEvent(format!(
+                "data of {}: {}",
+                topic, e
+            ))))
+        }
+    };
+    Some(Ok(ServerEvent { topic, data }))
+}
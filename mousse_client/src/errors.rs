@@ -0,0 +1,105 @@
+use crate::*;
+use serde::de::DeserializeOwned;
+use serde_derive::Deserialize;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+pub use eth2_simulator::errors::{BidPublicationError, SlotProcessingError};
+
+/// The body of the error responses of the server.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ErrorResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Stable code of the error (e.g. `BID_PAST_SLOT`).
+    pub code: String,
+    pub message: String,
+    /// Structured fields of the error (e.g. `next` and `found` of `BID_PAST_SLOT`).
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+impl ErrorResponse {
+    fn field<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        serde_json::from_value(self.fields.get(name)?.clone()).ok()
+    }
+
+    /// The bid publication error of the `BID_` codes.
+    pub fn bid_publication_error(&self) -> Option<BidPublicationError> {
+        let e = match self.code.as_str() {
+            "BID_UNKNOWN_SHARD" => BidPublicationError::UnknownShard {
+                found: self.field("found")?,
+            },
+            "BID_PAST_SLOT" => BidPublicationError::PastSlot {
+                next: self.field("next")?,
+                found: self.field("found")?,
+            },
+            "BID_TOO_LATE" => BidPublicationError::TooLate {
+                slot: self.field("slot")?,
+                deadline_ms: self.field("deadline_ms")?,
+                now_ms: self.field("now_ms")?,
+            },
+            "BID_TOO_LARGE_DATA" => BidPublicationError::TooLargeData {
+                found: self.field("found")?,
+            },
+            "BID_EMPTY_DATA" => BidPublicationError::EmptyData,
+            "BID_TOO_LOW_FEE" => BidPublicationError::TooLowFee {
+                gasprice: self.field("gasprice")?,
+                found: self.field("found")?,
+            },
+            "BID_DATA_LENGTH_MISMATCH" => BidPublicationError::DataLengthMismatch {
+                expect: self.field("expect")?,
+                found: self.field("found")?,
+            },
+            "BID_INVALID_COMMITMENT" => BidPublicationError::InvalidCommitment {
+                expect: self.field("expect")?,
+                found: self.field("found")?,
+            },
+            "BID_INVALID_SHARD" => BidPublicationError::InvalidShard {
+                expect: self.field("expect")?,
+                found: self.field("found")?,
+            },
+            _ => return None,
+        };
+        Some(e)
+    }
+
+    /// The slot processing error of the `SLOT_` codes.
+    pub fn slot_processing_error(&self) -> Option<SlotProcessingError> {
+        match self.code.as_str() {
+            "SLOT_PAST_SLOT" => Some(SlotProcessingError::PastSlot {
+                next: self.field("next")?,
+                found: self.field("found")?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("bid rejected: {0}")]
+    BidPublication(BidPublicationError),
+    #[error("slot processing failed: {0}")]
+    SlotProcessing(SlotProcessingError),
+    #[error("error response {} {}: {}", .0.status, .0.code, .0.message)]
+    Api(ErrorResponse),
+    #[error("invalid server-sent event: {0}")]
+    InvalidEvent(String),
+    #[error("slot {slot:?} not finalized in time (finalized checkpoint at epoch {epoch:?})")]
+    FinalityTimeout { slot: Slot, epoch: Epoch },
+}
+
+impl From<ErrorResponse> for ClientError {
+    fn from(e: ErrorResponse) -> Self {
+        if let Some(bid_error) = e.bid_publication_error() {
+            ClientError::BidPublication(bid_error)
+        } else if let Some(slot_error) = e.slot_processing_error() {
+            ClientError::SlotProcessing(slot_error)
+        } else {
+            ClientError::Api(e)
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! Client of the HTTP API of the Ethereum 2.0 emulator, which reuses the types of the simulator.
+pub use common::bid::*;
+pub use common::eth2_config::*;
+pub use common::eth2_types::*;
+pub use common::eth2_utils::*;
+pub use common::eth_api::*;
+pub use common::merkle::CommitmentProof;
+pub use common::pagination::*;
+pub use eth2_simulator::data_submission::Manifest;
+pub use eth2_simulator::failure_model::FailureModel;
+pub use eth2_simulator::propagation_model::PropagationModel;
+pub use eth2_simulator::slot_clock::SlotPhase;
+
+pub mod client;
+pub mod errors;
+pub mod types;
+
+pub use client::Client;
+pub use errors::*;
+pub use types::*;
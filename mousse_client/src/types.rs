@@ -0,0 +1,120 @@
+//! Types of the requests and the responses of the endpoints, which are neither in the simulator nor in `common`.
+use crate::*;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BeaconBlockHeaderWithRoot {
+    pub root: Root,
+    pub header: BeaconBlockHeader,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FinalityStatus {
+    pub current_epoch: Epoch,
+    pub justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    pub epochs_since_finality: Epoch,
+    pub inactivity_leak: bool,
+}
+
+/// Shard headers published but not included in the beacon chain yet.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HeaderPool {
+    pub previous_epoch: Vec<SignedShardHeader>,
+    pub current_epoch: Vec<SignedShardHeader>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ValidatorResponse {
+    pub index: ValidatorIndex,
+    pub balance: Gwei,
+    pub status: ValidatorStatus,
+    pub validator: Validator,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ShardHeaderStatus {
+    pub slot: Slot,
+    pub shard: Shard,
+    pub header: Option<SignedShardHeader>,
+    pub proposed: bool,
+    pub included_block_slot: Option<Slot>,
+    pub included_block_root: Option<Root>,
+    /// `None` if it is unknown since the state is already pruned.
+    pub confirmed: Option<bool>,
+    pub finalized: bool,
+}
+
+/// Config of the auto mode.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Config {
+    pub auto: bool,
+    /// Slot time in seconds.
+    pub slot_time: f64,
+    pub failure_rate: f32,
+    pub failure_model: FailureModel,
+}
+
+/// The response of `GET /config`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ConfigResponse {
+    #[serde(flatten)]
+    pub config: Config,
+    /// The offset in seconds from the start of a slot when the bid window for the next slot closes.
+    pub bid_deadline: Option<f64>,
+    pub propagation_model: PropagationModel,
+}
+
+/// Options of `POST /config`. The options of `None` are not changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConfigOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_rate: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bid_deadline: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_model: Option<FailureModel>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub propagation_model: Option<PropagationModel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Clock {
+    /// The current Unix time in milliseconds in the simulation.
+    pub time_ms: u64,
+    pub virtual_time: bool,
+    /// Unix time of the genesis in seconds.
+    pub genesis_time: u64,
+    pub slot_time_ms: u64,
+    pub current_slot: Option<Slot>,
+    /// The next slot to be processed.
+    pub next_slot: Slot,
+    pub phase: Option<SlotPhase>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CurrentStatus {
+    /// The last processed slot.
+    pub slot: Option<Slot>,
+    pub phase: Option<SlotPhase>,
+    pub config: Config,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RequestLog {
+    pub log_id: usize,
+    pub date: String,
+    pub endpoint: String,
+}
+
+/// An event of the event stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerEvent {
+    pub topic: String,
+    pub data: Value,
+}
@@ -0,0 +1,227 @@
+use eth2_simulator::data_submission::MAX_BYTES_PER_PIECE;
+use eth2_simulator::simulator::{Event, Simulator};
+use futures::StreamExt;
+use mousse_client::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, Mutex};
+
+/// Start the server in manual mode on a free port, where slot 0 is processed.
+fn start_server() -> Client {
+    let mut simulator = Simulator::new();
+    simulator.process_slots_happy(0).unwrap();
+    let (event_sender, _) = broadcast::channel(http_api::EVENT_CHANNEL_CAPACITY);
+    let sender = event_sender.clone();
+    simulator.subscribe(Box::new(move |event: &Event| {
+        let _ = sender.send(event.clone());
+    }));
+    let routes = http_api::routes(
+        Arc::new(Mutex::new(simulator)),
+        Arc::new(Mutex::new(Vec::new())),
+        Arc::new(Mutex::new(http_api::Config::default())),
+        event_sender,
+    );
+    let (address, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    Client::new(&format!("http://{}", address))
+}
+
+#[tokio::test]
+async fn process_slots_and_wait_for_finality() {
+    let client = start_server();
+    let end_slot = compute_start_slot_at_epoch(4);
+    client.process_slots(end_slot).await.unwrap();
+
+    let head = client.head().await.unwrap().unwrap();
+    assert_eq!(end_slot, head.slot);
+    let block = client.block(&end_slot.to_string()).await.unwrap().unwrap();
+    assert_eq!(end_slot, block.slot);
+    assert!(client.block("1000").await.unwrap().is_none());
+
+    let page = client
+        .blocks(&PaginationParams {
+            limit: Some(10),
+            order: Some(Order::Asc),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let slots: Vec<Slot> = page.data.iter().map(|block| block.slot).collect();
+    assert_eq!((0..10).collect::<Vec<Slot>>(), slots);
+    assert_eq!(Some(10), page.next);
+
+    let checkpoint = client
+        .wait_for_finality(
+            compute_start_slot_at_epoch(2),
+            Duration::from_millis(10),
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap();
+    assert_eq!(client.finalized_checkpoint().await.unwrap(), checkpoint);
+    let result = client
+        .wait_for_finality(
+            end_slot + 1,
+            Duration::from_millis(10),
+            Duration::from_millis(50),
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(ClientError::FinalityTimeout { slot, .. }) if slot == end_slot + 1
+    ));
+
+    let status = client.current_status().await.unwrap();
+    assert_eq!(Some(end_slot), status.slot);
+    assert!(!client.request_logs().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn eth_responses() {
+    let client = start_server();
+    let end_slot = compute_start_slot_at_epoch(4);
+    client.process_slots(end_slot).await.unwrap();
+
+    let head = client.head().await.unwrap().unwrap();
+    let header = client.eth_header("head").await.unwrap().data;
+    assert_eq!(head.header().root(), header.root);
+    assert_eq!(end_slot.to_string(), header.header.message.slot);
+    let block = client.eth_block(&end_slot.to_string()).await.unwrap();
+    assert_eq!("sharding", block.version);
+    assert_eq!(
+        head.shard_headers.to_vec(),
+        block.data.message.body.shard_headers
+    );
+
+    let state = client.state("head").await.unwrap().unwrap();
+    let checkpoints = client.eth_finality_checkpoints("head").await.unwrap().data;
+    assert_eq!(
+        CheckpointMessage::from(&state.finalized_checkpoint),
+        checkpoints.finalized
+    );
+    let syncing = client.eth_node_syncing().await.unwrap().data;
+    assert_eq!(end_slot.to_string(), syncing.head_slot);
+    let spec = client.eth_config_spec().await.unwrap().data;
+    assert_eq!(
+        Some(&SLOTS_PER_EPOCH.to_string()),
+        spec.get("SLOTS_PER_EPOCH")
+    );
+}
+
+#[tokio::test]
+async fn error_responses() {
+    let client = start_server();
+    client.process_slots(3).await.unwrap();
+
+    let result = client.process_slots(2).await;
+    assert!(matches!(
+        result,
+        Err(ClientError::SlotProcessing(SlotProcessingError::PastSlot {
+            next: 4,
+            found: 2
+        }))
+    ));
+
+    let bid = Bid {
+        shard: 0,
+        slot: 1,
+        commitment: DataCommitment::dummy_from_bytes(b"mousse"),
+        fee: MIN_GASPRICE,
+    };
+    let result = client.publish_bid(&bid).await;
+    assert!(matches!(
+        result,
+        Err(ClientError::BidPublication(BidPublicationError::PastSlot {
+            next: 4,
+            found: 1
+        }))
+    ));
+
+    let result = client.set_time(0.0).await;
+    match &result {
+        Err(ClientError::Api(e)) => assert_eq!("TIME_PAST_TIME", e.code),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+
+    let result = client.process_slots_with_offline_validators(8, 1.5).await;
+    match &result {
+        Err(ClientError::Api(e)) => assert_eq!("SLOT_INVALID_OFFLINE_RATE", e.code),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn publish_bids_with_data() {
+    let client = start_server();
+    let data = b"mousse".to_vec();
+    let commitment = client.data_commitment(data.clone()).await.unwrap();
+    assert_eq!(DataCommitment::dummy_from_bytes(&data), commitment);
+
+    let bid = Bid {
+        shard: 0,
+        slot: 1,
+        commitment: commitment.clone(),
+        fee: MIN_GASPRICE,
+    };
+    client.publish_bid_with_data(&bid, &data).await.unwrap();
+    let blob_bid = Bid { shard: 1, ..bid };
+    client.upload_blob(&blob_bid, data.clone()).await.unwrap();
+    let result = client.upload_blob(&blob_bid, vec![0; 100]).await;
+    assert!(matches!(
+        result,
+        Err(ClientError::BidPublication(
+            BidPublicationError::DataLengthMismatch { .. }
+        ))
+    ));
+
+    let mut events = client.events(&["shard_header"]).await.unwrap();
+    client.process_slots(1).await.unwrap();
+    let event = events.next().await.unwrap().unwrap();
+    assert_eq!("shard_header", event.topic);
+    let header = client.shard_header(0, 1).await.unwrap().unwrap();
+    assert!(header.proposed);
+    assert_eq!(commitment, header.header.unwrap().message.commitment);
+}
+
+#[tokio::test]
+async fn submit_and_reassemble_data() {
+    let client = start_server();
+    let data: Vec<u8> = (0..MAX_BYTES_PER_PIECE * 5 / 2)
+        .map(|i| (i % 251) as u8)
+        .collect();
+    let manifest = client
+        .submit_data(&data, MIN_GASPRICE, Some(vec![1, 2]), None)
+        .await
+        .unwrap();
+    assert_eq!(data.len() as u64, manifest.length);
+    assert_eq!(3, manifest.pieces.len());
+
+    let result = client.reassemble_data(&manifest).await;
+    match &result {
+        Err(ClientError::Api(e)) => assert_eq!("REASSEMBLY_NOT_CONFIRMED", e.code),
+        _ => panic!("unexpected result: {:?}", result),
+    }
+    client
+        .process_slots(compute_start_slot_at_epoch(2))
+        .await
+        .unwrap();
+    assert_eq!(data, client.reassemble_data(&manifest).await.unwrap());
+}
+
+#[tokio::test]
+async fn set_config() {
+    let client = start_server();
+    client
+        .set_config(&ConfigOptions {
+            slot_time: Some(0.25),
+            bid_deadline: Some(0.2),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let config = client.config().await.unwrap();
+    assert!(!config.config.auto);
+    assert_eq!(0.25, config.config.slot_time);
+    assert_eq!(Some(0.2), config.bid_deadline);
+    assert_eq!(250, client.clock().await.unwrap().slot_time_ms);
+}
@@ -1,5 +1,17 @@
 use crate::*;
+use events::Event;
 use simulation_params::BeaconSimulationParams;
+use slot_clock::SlotClock;
+use store::*;
+
+/// The operations included in a beacon block.
+#[derive(Default)]
+struct Operations {
+    proposer_slashings: Vec<ProposerSlashing>,
+    attester_slashings: Vec<AttesterSlashing>,
+    deposits: Vec<Deposit>,
+    voluntary_exits: Vec<VoluntaryExit>,
+}
 
 /// Beacon chain consensus
 pub struct BeaconChain {
@@ -8,15 +20,27 @@ pub struct BeaconChain {
     pub slot: Slot,
     // The latest beacon state (defined even for a slot without beacon block).
     pub state: BeaconState,
-    // The latest finalized checkpoint.
-    // Note: This is off-chain finality, not the finality verified in the beacon state.
+    // The latest justified and finalized checkpoints.
+    // Note: This is off-chain justification and finality with the attestations seen so far,
+    // which is realized in the beacon state at the end of the epoch.
+    pub justified_checkpoint: Checkpoint,
     pub finalized_checkpoint: Checkpoint,
-    // Beacon blocks in the main chain.
+    // The validators attesting to the checkpoints of the previous and current epochs.
+    previous_epoch_target_attesters: Vec<ValidatorIndex>,
+    current_epoch_target_attesters: Vec<ValidatorIndex>,
+    // The quotient of the inactivity penalty, which can be decreased to speed up the inactivity leak.
+    pub inactivity_penalty_quotient: u64,
+    // Storage of beacon blocks in the main chain and beacon state at each block.
     // Note: Slots can be "skipped" i.e., there can be slots without beacon block proposal.
-    // Assumption: No reorg or equivocation. (At most one block exists for each slot.)
-    pub blocks: Vec<BeaconBlock>,
-    // Beacon state at block of the main chain.
-    pub states: Vec<BeaconState>,
+    // Assumption: No reorg. (At most one block exists for each slot.)
+    // Equivocations are simulated only as the slashings of the equivocating validators.
+    pub store: Box<dyn Store>,
+    // Indices of the blocks in the store by slot, block root and state root.
+    block_indices_by_slot: HashMap<Slot, usize>,
+    block_indices_by_root: HashMap<Root, usize>,
+    block_indices_by_state_root: HashMap<Root, usize>,
+    // Whether or not the states before the finalized checkpoint are pruned from the store.
+    pub state_pruning: bool,
     // Checkpoints of each epoch in the main chain.
     // Note: A block can be the checkpoint for multiple consecutive epochs.
     pub checkpoints: Vec<Checkpoint>,
@@ -24,38 +48,371 @@ pub struct BeaconChain {
     // The latter one in the list is the fresher.
     pub previous_epoch_shard_header_pool: Vec<SignedShardHeader>,
     pub current_epoch_shard_header_pool: Vec<SignedShardHeader>,
+    // Slashings, deposits and voluntary exits not included in the main chain.
+    pub proposer_slashing_pool: Vec<ProposerSlashing>,
+    pub attester_slashing_pool: Vec<AttesterSlashing>,
+    pub deposit_pool: Vec<Deposit>,
+    pub voluntary_exit_pool: Vec<VoluntaryExit>,
+    // Events emitted in the slot being processed, which are drained by the simulator.
+    pub events: Vec<Event>,
+    // The slot clock of the simulator, which is stored with the head.
+    pub slot_clock: Option<SlotClock>,
 }
 
 impl BeaconChain {
     pub fn new() -> Self {
+        Self::genesis(Box::new(MemoryStore::new()))
+    }
+
+    /// Create a beacon chain with the storage backend.
+    /// If the store has the head, resume from the end of the last processed slot.
+    /// Note: The shard header pools and the operation pools are not stored,
+    /// so they are empty after resumption.
+    pub fn with_store(store: Box<dyn Store>) -> Result<Self, StoreError> {
+        let mut beacon_chain = Self::genesis(store);
+        for index in 0..beacon_chain.block_summaries().len() {
+            beacon_chain.index_block(index);
+        }
+        let head = match beacon_chain.store.head() {
+            Some(head) => head.clone(),
+            None => return Ok(beacon_chain),
+        };
+        beacon_chain.slot = head.slot;
+        beacon_chain.state = head.state;
+        beacon_chain.justified_checkpoint = head.justified_checkpoint;
+        beacon_chain.finalized_checkpoint = head.finalized_checkpoint;
+        beacon_chain.previous_epoch_target_attesters = head.previous_epoch_target_attesters;
+        beacon_chain.current_epoch_target_attesters = head.current_epoch_target_attesters;
+        beacon_chain.inactivity_penalty_quotient = head.inactivity_penalty_quotient;
+        beacon_chain.slot_clock = head.slot_clock;
+        // Define checkpoints in the same way as `append_new_block_to_chain()`.
+        for summary in beacon_chain.store.block_summaries() {
+            while beacon_chain.checkpoints.len() < compute_epoch_at_slot(summary.slot) as usize + 1
+            {
+                beacon_chain.checkpoints.push(Checkpoint {
+                    epoch: beacon_chain.checkpoints.len() as Epoch,
+                    root: summary.root,
+                })
+            }
+        }
+        Ok(beacon_chain)
+    }
+
+    /// The beacon chain at the end of the last processed slot.
+    fn head(&self) -> ChainHead {
+        ChainHead {
+            slot: self.slot,
+            state: self.state.clone(),
+            justified_checkpoint: self.justified_checkpoint.clone(),
+            finalized_checkpoint: self.finalized_checkpoint.clone(),
+            previous_epoch_target_attesters: self.previous_epoch_target_attesters.clone(),
+            current_epoch_target_attesters: self.current_epoch_target_attesters.clone(),
+            inactivity_penalty_quotient: self.inactivity_penalty_quotient,
+            slot_clock: self.slot_clock,
+        }
+    }
+
+    /// Set the slot clock, and store it with the head if any slot is processed.
+    pub fn put_slot_clock(&mut self, slot_clock: SlotClock) -> Result<(), StoreError> {
+        self.slot_clock = Some(slot_clock);
+        if self.store.head().is_some() {
+            self.store.put_head(self.head())?;
+        }
+        Ok(())
+    }
+
+    fn genesis(store: Box<dyn Store>) -> Self {
         Self {
             slot: GENESIS_SLOT,
             state: BeaconState::genesis_state(),
+            justified_checkpoint: Checkpoint::genesis_finalized_checkpoint(),
             finalized_checkpoint: Checkpoint::genesis_finalized_checkpoint(),
-            blocks: Vec::new(),
-            states: Vec::new(),
+            previous_epoch_target_attesters: Vec::new(),
+            current_epoch_target_attesters: Vec::new(),
+            inactivity_penalty_quotient: INACTIVITY_PENALTY_QUOTIENT,
+            store,
+            block_indices_by_slot: HashMap::new(),
+            block_indices_by_root: HashMap::new(),
+            block_indices_by_state_root: HashMap::new(),
+            state_pruning: false,
             checkpoints: Vec::new(),
             previous_epoch_shard_header_pool: Vec::new(),
             current_epoch_shard_header_pool: Vec::new(),
+            proposer_slashing_pool: Vec::new(),
+            attester_slashing_pool: Vec::new(),
+            deposit_pool: Vec::new(),
+            voluntary_exit_pool: Vec::new(),
+            events: Vec::new(),
+            slot_clock: None,
+        }
+    }
+
+    /// Get the summaries of the beacon blocks in the main chain.
+    pub fn block_summaries(&self) -> &[BlockSummary] {
+        self.store.block_summaries()
+    }
+
+    /// Get beacon blocks in the main chain.
+    /// Note: All the blocks are loaded from the store, so prefer `block_summaries()`
+    /// and the getters of a block unless the whole chain is needed.
+    pub fn blocks(&self) -> Result<Vec<BeaconBlock>, StoreError> {
+        let mut blocks = Vec::new();
+        for summary in self.block_summaries() {
+            blocks.extend(self.store.get_block(summary.slot)?);
+        }
+        Ok(blocks)
+    }
+
+    /// Get the summary of the beacon block at the slot.
+    /// Return `None` if the slot is skipped.
+    pub fn get_block_summary_by_slot(&self, slot: Slot) -> Option<&BlockSummary> {
+        self.block_indices_by_slot
+            .get(&slot)
+            .map(|index| &self.block_summaries()[*index])
+    }
+
+    /// Get the summary of the beacon block of the root.
+    pub fn get_block_summary_by_root(&self, root: &Root) -> Option<&BlockSummary> {
+        self.block_indices_by_root
+            .get(root)
+            .map(|index| &self.block_summaries()[*index])
+    }
+
+    /// Get the summary of the beacon block whose state root is the root.
+    pub fn get_block_summary_by_state_root(&self, state_root: &Root) -> Option<&BlockSummary> {
+        self.block_indices_by_state_root
+            .get(state_root)
+            .map(|index| &self.block_summaries()[*index])
+    }
+
+    /// Get the beacon block at the slot.
+    /// Return `None` if the slot is skipped.
+    pub fn get_block_by_slot(&self, slot: Slot) -> Result<Option<BeaconBlock>, StoreError> {
+        self.load_block(self.get_block_summary_by_slot(slot))
+    }
+
+    /// Get the beacon block of the root.
+    pub fn get_block_by_root(&self, root: &Root) -> Result<Option<BeaconBlock>, StoreError> {
+        self.load_block(self.get_block_summary_by_root(root))
+    }
+
+    /// Get the beacon block whose state root is the root.
+    pub fn get_block_by_state_root(
+        &self,
+        state_root: &Root,
+    ) -> Result<Option<BeaconBlock>, StoreError> {
+        self.load_block(self.get_block_summary_by_state_root(state_root))
+    }
+
+    /// Get the latest beacon block in the main chain.
+    pub fn get_head_block(&self) -> Result<Option<BeaconBlock>, StoreError> {
+        self.load_block(self.block_summaries().last())
+    }
+
+    /// Load the block of the summary from the store.
+    fn load_block(
+        &self,
+        summary: Option<&BlockSummary>,
+    ) -> Result<Option<BeaconBlock>, StoreError> {
+        match summary {
+            Some(summary) => self.store.get_block(summary.slot),
+            None => Ok(None),
         }
     }
 
-    /// Get finalized beacon blocks.
-    pub fn get_finalized_blocks(&self) -> Vec<BeaconBlock> {
-        // Return empty vector if no checkpoint is finalized.
+    /// Add the stored block at the index to the indices.
+    fn index_block(&mut self, index: usize) {
+        let summary = &self.store.block_summaries()[index];
+        let (slot, root, state_root) = (summary.slot, summary.root, summary.state_root);
+        self.block_indices_by_slot.insert(slot, index);
+        self.block_indices_by_root.insert(root, index);
+        self.block_indices_by_state_root.insert(state_root, index);
+    }
+
+    /// Get the beacon state at the block of the slot.
+    pub fn get_state(&self, slot: Slot) -> Result<Option<BeaconState>, StoreError> {
+        self.store.get_state(slot)
+    }
+
+    /// Get the beacon state at the head block.
+    pub fn get_head_state(&self) -> Result<Option<BeaconState>, StoreError> {
+        match self.block_summaries().last() {
+            Some(summary) => self.get_state(summary.slot),
+            None => Ok(None),
+        }
+    }
+
+    /// Get beacon states at the blocks of the main chain.
+    /// The pruned states are skipped.
+    pub fn states(&self) -> Result<Vec<BeaconState>, StoreError> {
+        let mut states = Vec::new();
+        for summary in self.block_summaries() {
+            if let Some(state) = self.get_state(summary.slot)? {
+                states.push(state);
+            }
+        }
+        Ok(states)
+    }
+
+    /// Get the summaries of finalized beacon blocks.
+    pub fn finalized_block_summaries(&self) -> &[BlockSummary] {
+        // Return empty slice if no checkpoint is finalized.
         if self.finalized_checkpoint == Checkpoint::genesis_finalized_checkpoint() {
-            Vec::new()
+            &[]
         } else {
             let latest_finalized_slot =
                 compute_start_slot_at_epoch(self.finalized_checkpoint.epoch);
-            let mut finalized_blocks: Vec<BeaconBlock> = Vec::new();
-            for block in self.blocks.iter() {
-                if block.slot > latest_finalized_slot {
-                    break;
+            &self.block_summaries()[..self.count_blocks_before(latest_finalized_slot + 1)]
+        }
+    }
+
+    /// Get the block including the shard header.
+    pub fn get_block_including_shard_header(
+        &self,
+        header: &SignedShardHeader,
+    ) -> Result<Option<BeaconBlock>, StoreError> {
+        // A shard header can be included until the end of the next epoch.
+        let last_slot =
+            compute_start_slot_at_epoch(compute_epoch_at_slot(header.message.slot) + 2) - 1;
+        for slot in header.message.slot..=last_slot {
+            if let Some(block) = self.get_block_by_slot(slot)? {
+                if block
+                    .shard_headers
+                    .iter()
+                    .any(|included| included.message == header.message)
+                {
+                    return Ok(Some(block));
                 }
-                finalized_blocks.push(block.clone());
             }
-            finalized_blocks
+        }
+        Ok(None)
+    }
+
+    /// Whether or not the shard header of the shard at the slot is confirmed.
+    /// Return `None` if it is unknown since the state is already pruned.
+    pub fn is_shard_header_confirmed(
+        &self,
+        slot: Slot,
+        shard: ShardId,
+    ) -> Result<Option<bool>, StoreError> {
+        let is_confirmed = |state: &BeaconState| {
+            state
+                .previous_epoch_pending_shard_headers
+                .iter()
+                .chain(state.current_epoch_pending_shard_headers.iter())
+                .any(|header| header.slot == slot && header.shard == shard && header.confirmed)
+        };
+        let epoch = compute_epoch_at_slot(slot);
+        if epoch + 1 >= compute_epoch_at_slot(self.slot) {
+            return Ok(Some(is_confirmed(&self.state)));
+        }
+        // The confirmation of the shard headers of an epoch is fixed at the end of the next epoch,
+        // so look up the state at the last block by then.
+        let block_num = self.count_blocks_before(compute_start_slot_at_epoch(epoch + 2));
+        if block_num == 0 {
+            return Ok(Some(false));
+        }
+        Ok(self
+            .get_state(self.block_summaries()[block_num - 1].slot)?
+            .map(|state| is_confirmed(&state)))
+    }
+
+    /// Get the proof that the commitment of the shard at the slot is confirmed.
+    /// Return `None` if no commitment is confirmed or the state is already pruned.
+    pub fn get_commitment_proof(
+        &self,
+        shard: ShardId,
+        slot: Slot,
+    ) -> Result<Option<CommitmentProof>, StoreError> {
+        if shard >= SHARD_NUM as ShardId
+            || self.is_shard_header_confirmed(slot, shard)? != Some(true)
+        {
+            return Ok(None);
+        }
+        let epoch = compute_epoch_at_slot(slot);
+        let grandchild_epoch_start =
+            self.count_blocks_before(compute_start_slot_at_epoch(epoch + 2));
+        let grandchild_epoch_end = self.count_blocks_before(compute_start_slot_at_epoch(epoch + 3));
+        // If there are blocks in the grandchild epoch, the commitment is in
+        // `grandparent_epoch_confirmed_commitments` of the state at the last one.
+        // Otherwise, the pending shard header is in the state at the last block.
+        let block_slot = self.block_summaries()[grandchild_epoch_end - 1].slot;
+        let block = match self.store.get_block(block_slot)? {
+            Some(block) => block,
+            None => {
+                return Err(StoreError::BrokenData(format!(
+                    "block at slot {} is missing",
+                    block_slot
+                )))
+            }
+        };
+        let state = match self.get_state(block.slot)? {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+        let (commitment, pending_header, state_proof) =
+            if grandchild_epoch_start < grandchild_epoch_end {
+                (
+                    state.grandparent_epoch_confirmed_commitments[shard as usize]
+                        [(slot % SLOTS_PER_EPOCH) as usize]
+                        .clone(),
+                    None,
+                    state.confirmed_commitment_proof(shard, slot),
+                )
+            } else {
+                let is_target = |header: &PendingShardHeader| {
+                    (header.slot, header.shard, header.confirmed) == (slot, shard, true)
+                };
+                let (previous_epoch, index) = match state
+                    .previous_epoch_pending_shard_headers
+                    .iter()
+                    .position(is_target)
+                {
+                    Some(index) => (true, index),
+                    None => match state
+                        .current_epoch_pending_shard_headers
+                        .iter()
+                        .position(is_target)
+                    {
+                        Some(index) => (false, index),
+                        None => return Ok(None),
+                    },
+                };
+                let header = if previous_epoch {
+                    state.previous_epoch_pending_shard_headers[index].clone()
+                } else {
+                    state.current_epoch_pending_shard_headers[index].clone()
+                };
+                (
+                    header.commitment.clone(),
+                    Some(header),
+                    state.pending_shard_header_proof(previous_epoch, index),
+                )
+            };
+        let header = block.header();
+        Ok(Some(CommitmentProof {
+            shard,
+            slot,
+            commitment,
+            pending_header,
+            state_proof,
+            state_root: header.state_root,
+            block_proof: header.state_root_proof(),
+            block_root: header.root(),
+            block_slot: block.slot,
+        }))
+    }
+
+    /// The number of the blocks before the slot.
+    fn count_blocks_before(&self, slot: Slot) -> usize {
+        match self.block_summaries().binary_search_by(|summary| {
+            if summary.slot < slot {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            }
+        }) {
+            Ok(index) | Err(index) => index,
         }
     }
 
@@ -69,15 +426,214 @@ impl BeaconChain {
         }
     }
 
+    /// Submit a deposit, which is processed in the next beacon block.
+    pub fn submit_deposit(&mut self, deposit: Deposit) -> Result<(), DepositError> {
+        if deposit.amount < MIN_DEPOSIT_AMOUNT {
+            return Err(DepositError::TooSmallAmount {
+                found: deposit.amount,
+            });
+        }
+        self.deposit_pool.push(deposit);
+        Ok(())
+    }
+
+    /// Submit a voluntary exit, which is processed in a beacon block from its epoch.
+    pub fn submit_voluntary_exit(&mut self, exit: VoluntaryExit) -> Result<(), VoluntaryExitError> {
+        self.validate_voluntary_exit(&exit, compute_epoch_at_slot(self.slot))?;
+        self.voluntary_exit_pool.push(exit);
+        Ok(())
+    }
+
+    /// Validate a voluntary exit at the epoch, except for the epoch of the exit.
+    /// Note: `SHARD_COMMITTEE_PERIOD` is not checked to exit validators soon in the simulation.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#voluntary-exits
+    fn validate_voluntary_exit(
+        &self,
+        exit: &VoluntaryExit,
+        epoch: Epoch,
+    ) -> Result<(), VoluntaryExitError> {
+        let index = exit.validator_index;
+        let validator = match self.state.validators.get(index as usize) {
+            Some(validator) => validator,
+            None => return Err(VoluntaryExitError::UnknownValidator { found: index }),
+        };
+        if validator.exit_epoch != FAR_FUTURE_EPOCH {
+            return Err(VoluntaryExitError::AlreadyExiting {
+                index,
+                exit_epoch: validator.exit_epoch,
+            });
+        }
+        if !validator.is_active(epoch) {
+            return Err(VoluntaryExitError::InactiveValidator { index });
+        }
+        Ok(())
+    }
+
+    /// Submit a proposer slashing, which is processed in the next beacon block.
+    pub fn submit_proposer_slashing(
+        &mut self,
+        slashing: ProposerSlashing,
+    ) -> Result<(), SlashingError> {
+        self.validate_proposer_slashing(&slashing, compute_epoch_at_slot(self.slot))?;
+        self.proposer_slashing_pool.push(slashing);
+        Ok(())
+    }
+
+    /// Submit an attester slashing, which is processed in the next beacon block.
+    pub fn submit_attester_slashing(
+        &mut self,
+        slashing: AttesterSlashing,
+    ) -> Result<(), SlashingError> {
+        self.validate_attester_slashing(&slashing, compute_epoch_at_slot(self.slot))?;
+        self.attester_slashing_pool.push(slashing);
+        Ok(())
+    }
+
+    /// Simulate that the validator proposes two conflicting blocks at the current slot,
+    /// and submit the proposer slashing of them.
+    pub fn simulate_proposer_equivocation(
+        &mut self,
+        index: ValidatorIndex,
+    ) -> Result<(), SlashingError> {
+        let header = BeaconBlockHeader {
+            slot: self.slot,
+            parent_root: self.get_head_root(),
+            state_root: self.state.root(),
+            body_root: root(&(index, 1)),
+        };
+        let slashing = ProposerSlashing {
+            proposer_index: index,
+            header_1: header.clone(),
+            header_2: BeaconBlockHeader {
+                body_root: root(&(index, 2)),
+                ..header
+            },
+        };
+        self.submit_proposer_slashing(slashing)
+    }
+
+    /// Simulate that the validators vote for two conflicting targets of the current epoch (a double vote),
+    /// and submit the attester slashing of them.
+    pub fn simulate_attester_equivocation(
+        &mut self,
+        indices: &[ValidatorIndex],
+    ) -> Result<(), SlashingError> {
+        let mut attesting_indices = indices.to_vec();
+        attesting_indices.sort_unstable();
+        attesting_indices.dedup();
+        let head_root = self.get_head_root();
+        let data = AttestationData {
+            slot: self.slot,
+            beacon_block_root: head_root,
+            source: self.state.current_justified_checkpoint.clone(),
+            target: Checkpoint {
+                epoch: compute_epoch_at_slot(self.slot),
+                root: head_root,
+            },
+        };
+        let conflicting_root = root(&data);
+        let slashing = AttesterSlashing {
+            attestation_1: IndexedAttestation {
+                attesting_indices: attesting_indices.clone(),
+                data: data.clone(),
+            },
+            attestation_2: IndexedAttestation {
+                attesting_indices,
+                data: AttestationData {
+                    beacon_block_root: conflicting_root,
+                    target: Checkpoint {
+                        epoch: data.target.epoch,
+                        root: conflicting_root,
+                    },
+                    ..data
+                },
+            },
+        };
+        self.submit_attester_slashing(slashing)
+    }
+
+    /// The root of the latest block in the main chain.
+    fn get_head_root(&self) -> Root {
+        self.block_summaries()
+            .last()
+            .map_or(GENESIS_PARENT_ROOT, |summary| summary.root)
+    }
+
+    /// Validate a proposer slashing at the epoch.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#proposer-slashings
+    fn validate_proposer_slashing(
+        &self,
+        slashing: &ProposerSlashing,
+        epoch: Epoch,
+    ) -> Result<(), SlashingError> {
+        let (header_1, header_2) = (&slashing.header_1, &slashing.header_2);
+        if header_1.slot != header_2.slot {
+            return Err(SlashingError::DifferentSlots {
+                slot_1: header_1.slot,
+                slot_2: header_2.slot,
+            });
+        }
+        if header_1 == header_2 {
+            return Err(SlashingError::IdenticalHeaders);
+        }
+        self.validate_slashable_validator(slashing.proposer_index, epoch)
+    }
+
+    /// Validate an attester slashing at the epoch, and return the indices of the validators to be slashed.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#attester-slashings
+    fn validate_attester_slashing(
+        &self,
+        slashing: &AttesterSlashing,
+        epoch: Epoch,
+    ) -> Result<Vec<ValidatorIndex>, SlashingError> {
+        for attestation in [&slashing.attestation_1, &slashing.attestation_2].iter() {
+            let indices = &attestation.attesting_indices;
+            if indices.is_empty()
+                || indices.len() > MAX_VALIDATORS_PER_COMMITTEE as usize
+                || indices.windows(2).any(|pair| pair[0] >= pair[1])
+            {
+                return Err(SlashingError::InvalidAttestingIndices);
+            }
+        }
+        if !slashing
+            .attestation_1
+            .data
+            .is_slashable_with(&slashing.attestation_2.data)
+        {
+            return Err(SlashingError::UnslashableAttestationData);
+        }
+        let indices: Vec<ValidatorIndex> = slashing
+            .get_intersecting_indices()
+            .into_iter()
+            .filter(|index| self.validate_slashable_validator(*index, epoch).is_ok())
+            .collect();
+        if indices.is_empty() {
+            return Err(SlashingError::NoSlashableValidators);
+        }
+        Ok(indices)
+    }
+
+    fn validate_slashable_validator(
+        &self,
+        index: ValidatorIndex,
+        epoch: Epoch,
+    ) -> Result<(), SlashingError> {
+        match self.state.validators.get(index as usize) {
+            Some(validator) if validator.is_slashable(epoch) => Ok(()),
+            Some(_) => Err(SlashingError::UnslashableValidator { index }),
+            None => Err(SlashingError::UnknownValidator { found: index }),
+        }
+    }
+
     /// Process of a slot.
-    pub fn process_slot(&mut self, params: &BeaconSimulationParams) {
+    pub fn process_slot(&mut self, params: &BeaconSimulationParams) -> Result<(), StoreError> {
         if params.beacon_block_proposed {
             // Propose a new beacon block.
             // Shard shard headers to be included in the new beacon block.
             let (included_previous_epoch_shard_headers, mut included_current_epoch_shard_headers) =
                 self.select_included_shard_headers(params.shard_headers_included);
             // Update the state for the new block.
-            self.update_state_for_new_block(
+            let operations = self.update_state_for_new_block(
                 &included_previous_epoch_shard_headers,
                 &included_current_epoch_shard_headers,
                 params.shard_headers_confirmed,
@@ -86,12 +642,22 @@ impl BeaconChain {
             let mut included_shard_headers = included_previous_epoch_shard_headers;
             included_shard_headers.append(&mut included_current_epoch_shard_headers);
             // Append the new block to the chain.
-            self.append_new_block_to_chain(included_shard_headers);
+            self.append_new_block_to_chain(included_shard_headers, operations)?;
         }
         if params.beacon_chain_finalized {
-            // Finalize a new checkpoint.
-            self.progress_consensus();
+            // The online validators in the committee of the slot attest to the checkpoint.
+            let mut attesters = self.get_attesting_indices(params.offline_validator_rate);
+            self.current_epoch_target_attesters.append(&mut attesters);
         }
+        self.progress_consensus()?;
+        self.finish_slot();
+        // Commit the slot.
+        self.store.put_head(self.head())?;
+        Ok(())
+    }
+
+    /// Process at the end of a slot.
+    fn finish_slot(&mut self) {
         if (self.slot + 1) % SLOTS_PER_EPOCH == 0 {
             self.process_epoch();
         }
@@ -102,7 +668,15 @@ impl BeaconChain {
 
     // Process at the end of an epoch.
     fn process_epoch(&mut self) {
+        self.events.push(Event::EpochProcessed {
+            epoch: compute_epoch_at_slot(self.slot),
+        });
+        self.process_justification_and_finalization();
+        self.process_inactivity_penalties();
         self.update_shard_gasprice();
+        self.process_registry_updates();
+        self.process_effective_balance_updates();
+        self.process_participation_record_updates();
         // Store the previous epoch confirmed shard headers in the state.
         for header in self.state.previous_epoch_pending_shard_headers.iter() {
             if !header.confirmed {
@@ -126,6 +700,51 @@ impl BeaconChain {
         self.current_epoch_shard_header_pool.clear();
     }
 
+    /// Realize the justification and finality in the state with the attestations of the epoch.
+    fn process_justification_and_finalization(&mut self) {
+        let previous_epoch_target_balance = self
+            .state
+            .get_total_balance(&self.previous_epoch_target_attesters);
+        let current_epoch_target_balance = self
+            .state
+            .get_total_balance(&self.current_epoch_target_attesters);
+        self.state.weigh_justification_and_finalization(
+            compute_epoch_at_slot(self.slot),
+            &self.checkpoints,
+            previous_epoch_target_balance,
+            current_epoch_target_balance,
+        );
+    }
+
+    /// Penalize the validators not attesting to the previous epoch's checkpoint during the inactivity leak.
+    /// The penalty grows with the finality delay, so the balances of the offline validators decay
+    /// until the online validators regain 2/3 of the active stake.
+    /// Note: The other rewards and penalties are not simulated.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#inactivity-penalty-deltas
+    fn process_inactivity_penalties(&mut self) {
+        if compute_epoch_at_slot(self.slot) == GENESIS_EPOCH || !self.state.is_in_inactivity_leak()
+        {
+            return;
+        }
+        let finality_delay = self.state.get_finality_delay();
+        let previous_epoch = self.state.get_previous_epoch();
+        for index in self.state.get_active_validator_indices(previous_epoch) {
+            if self.previous_epoch_target_attesters.contains(&index) {
+                continue;
+            }
+            let effective_balance = self.state.validators[index as usize].effective_balance;
+            let penalty = effective_balance * finality_delay / self.inactivity_penalty_quotient;
+            let balance = &mut self.state.balances[index as usize];
+            *balance = balance.saturating_sub(penalty);
+        }
+    }
+
+    /// Rotate the attestations of the current epoch to the previous epoch.
+    fn process_participation_record_updates(&mut self) {
+        self.previous_epoch_target_attesters =
+            std::mem::take(&mut self.current_epoch_target_attesters);
+    }
+
     /// Update shard gasprice.
     fn update_shard_gasprice(&mut self) {
         if compute_epoch_at_slot(self.slot) == GENESIS_EPOCH {
@@ -153,9 +772,266 @@ impl BeaconChain {
                 new_gasprice = compute_updated_gasprice(new_gasprice, candidate.commitment.length)
             }
         }
+        if new_gasprice != self.state.shard_gasprice {
+            self.events.push(Event::GaspriceUpdated {
+                previous: self.state.shard_gasprice,
+                current: new_gasprice,
+            });
+        }
         self.state.shard_gasprice = new_gasprice
     }
 
+    /// Update the validator registry: The eligibility for activation, the ejection and the activation.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#registry-updates
+    fn process_registry_updates(&mut self) {
+        let current_epoch = compute_epoch_at_slot(self.slot);
+        for index in 0..self.state.validators.len() {
+            let validator = &mut self.state.validators[index];
+            if validator.is_eligible_for_activation_queue() {
+                validator.activation_eligibility_epoch = current_epoch + 1;
+            }
+            if validator.is_active(current_epoch) && validator.effective_balance <= EJECTION_BALANCE
+            {
+                self.initiate_validator_exit(index as ValidatorIndex);
+            }
+        }
+
+        // Queue the validators eligible for activation and not yet dequeued for activation.
+        let finalized_epoch = self.state.finalized_checkpoint.epoch;
+        let mut activation_queue: Vec<usize> = (0..self.state.validators.len())
+            .filter(|index| {
+                let validator = &self.state.validators[*index];
+                validator.activation_eligibility_epoch <= finalized_epoch
+                    && validator.activation_epoch == FAR_FUTURE_EPOCH
+            })
+            .collect();
+        // Order by the sequence of activation eligibility epoch and then index.
+        activation_queue.sort_by_key(|index| {
+            (
+                self.state.validators[*index].activation_eligibility_epoch,
+                *index,
+            )
+        });
+        // Dequeue validators for activation up to churn limit.
+        let activation_epoch = compute_activation_exit_epoch(current_epoch);
+        let churn_limit = self.get_validator_churn_limit() as usize;
+        for index in activation_queue.into_iter().take(churn_limit) {
+            self.state.validators[index].activation_epoch = activation_epoch;
+            self.events.push(Event::ValidatorActivationScheduled {
+                index: index as ValidatorIndex,
+                activation_epoch,
+            });
+        }
+    }
+
+    /// Update the effective balances with hysteresis.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#effective-balances-updates
+    fn process_effective_balance_updates(&mut self) {
+        let hysteresis_increment = EFFECTIVE_BALANCE_INCREMENT / HYSTERESIS_QUOTIENT;
+        let downward_threshold = hysteresis_increment * HYSTERESIS_DOWNWARD_MULTIPLIER;
+        let upward_threshold = hysteresis_increment * HYSTERESIS_UPWARD_MULTIPLIER;
+        for index in 0..self.state.validators.len() {
+            let balance = self.state.balances[index];
+            let validator = &mut self.state.validators[index];
+            if balance + downward_threshold < validator.effective_balance
+                || validator.effective_balance + upward_threshold < balance
+            {
+                validator.effective_balance = std::cmp::min(
+                    balance - balance % EFFECTIVE_BALANCE_INCREMENT,
+                    MAX_EFFECTIVE_BALANCE,
+                );
+            }
+        }
+    }
+
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#get_validator_churn_limit
+    fn get_validator_churn_limit(&self) -> u64 {
+        let active_validator_num = self
+            .state
+            .get_active_validator_indices(compute_epoch_at_slot(self.slot))
+            .len() as u64;
+        std::cmp::max(
+            MIN_PER_EPOCH_CHURN_LIMIT,
+            active_validator_num / CHURN_LIMIT_QUOTIENT,
+        )
+    }
+
+    /// Initiate the exit of the validator.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#initiate_validator_exit
+    fn initiate_validator_exit(&mut self, index: ValidatorIndex) {
+        if self.state.validators[index as usize].exit_epoch != FAR_FUTURE_EPOCH {
+            return;
+        }
+        let exit_epochs: Vec<Epoch> = self
+            .state
+            .validators
+            .iter()
+            .map(|validator| validator.exit_epoch)
+            .filter(|exit_epoch| *exit_epoch != FAR_FUTURE_EPOCH)
+            .collect();
+        let mut exit_queue_epoch = exit_epochs.iter().copied().fold(
+            compute_activation_exit_epoch(compute_epoch_at_slot(self.slot)),
+            std::cmp::max,
+        );
+        let exit_queue_churn = exit_epochs
+            .iter()
+            .filter(|exit_epoch| **exit_epoch == exit_queue_epoch)
+            .count() as u64;
+        if exit_queue_churn >= self.get_validator_churn_limit() {
+            exit_queue_epoch += 1;
+        }
+        let validator = &mut self.state.validators[index as usize];
+        validator.exit_epoch = exit_queue_epoch;
+        validator.withdrawable_epoch = exit_queue_epoch + MIN_VALIDATOR_WITHDRAWABILITY_DELAY;
+        self.events.push(Event::ValidatorExitInitiated {
+            index,
+            exit_epoch: exit_queue_epoch,
+        });
+    }
+
+    /// Slash the validator: Initiate its exit, delay its withdrawability and apply the minimum penalty.
+    /// Note: The proportional penalty by the total slashed balance and the whistleblower reward are not simulated.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#slash_validator
+    fn slash_validator(&mut self, index: ValidatorIndex) {
+        let epoch = compute_epoch_at_slot(self.slot);
+        self.initiate_validator_exit(index);
+        let validator = &mut self.state.validators[index as usize];
+        validator.slashed = true;
+        validator.withdrawable_epoch = std::cmp::max(
+            validator.withdrawable_epoch,
+            epoch + EPOCHS_PER_SLASHINGS_VECTOR,
+        );
+        let penalty = validator.effective_balance / MIN_SLASHING_PENALTY_QUOTIENT;
+        let balance = &mut self.state.balances[index as usize];
+        *balance = balance.saturating_sub(penalty);
+        self.events.push(Event::ValidatorSlashed { index, penalty });
+    }
+
+    /// Process a deposit included in a beacon block.
+    /// A new validator is added to the registry, or the balance of the existing one is increased.
+    /// Ref: https://github.com/ethereum/eth2.0-specs/blob/dev/specs/phase0/beacon-chain.md#deposits
+    fn process_deposit(&mut self, deposit: &Deposit) {
+        let index = match self.state.get_validator_index(&deposit.pubkey) {
+            Some(index) => {
+                self.state.balances[index as usize] += deposit.amount;
+                index
+            }
+            None => {
+                self.state
+                    .validators
+                    .push(Validator::from_deposit(deposit))
+                    .unwrap();
+                self.state.balances.push(deposit.amount).unwrap();
+                self.state.validators.len() as ValidatorIndex - 1
+            }
+        };
+        self.events.push(Event::DepositProcessed {
+            index,
+            amount: deposit.amount,
+        });
+    }
+
+    /// Select the operations to be included in the new beacon block and process them.
+    /// The slashings and the voluntary exits that became invalid are dropped from the pools.
+    fn process_operations(&mut self) -> Operations {
+        let current_epoch = compute_epoch_at_slot(self.slot);
+        let mut proposer_slashings = Vec::new();
+        let mut pending_proposer_slashings = Vec::new();
+        for slashing in std::mem::take(&mut self.proposer_slashing_pool) {
+            if proposer_slashings.len() == MAX_PROPOSER_SLASHINGS as usize {
+                pending_proposer_slashings.push(slashing);
+            } else if self
+                .validate_proposer_slashing(&slashing, current_epoch)
+                .is_ok()
+            {
+                self.events.push(Event::ProposerSlashingProcessed {
+                    slashing: slashing.clone(),
+                });
+                self.slash_validator(slashing.proposer_index);
+                proposer_slashings.push(slashing);
+            }
+        }
+        self.proposer_slashing_pool = pending_proposer_slashings;
+
+        let mut attester_slashings = Vec::new();
+        let mut pending_attester_slashings = Vec::new();
+        for slashing in std::mem::take(&mut self.attester_slashing_pool) {
+            if attester_slashings.len() == MAX_ATTESTER_SLASHINGS as usize {
+                pending_attester_slashings.push(slashing);
+            } else if let Ok(indices) = self.validate_attester_slashing(&slashing, current_epoch) {
+                self.events.push(Event::AttesterSlashingProcessed {
+                    slashing: slashing.clone(),
+                });
+                for index in indices {
+                    self.slash_validator(index);
+                }
+                attester_slashings.push(slashing);
+            }
+        }
+        self.attester_slashing_pool = pending_attester_slashings;
+
+        let deposit_num = std::cmp::min(self.deposit_pool.len(), MAX_DEPOSITS as usize);
+        let deposits: Vec<Deposit> = self.deposit_pool.drain(..deposit_num).collect();
+        for deposit in deposits.iter() {
+            self.process_deposit(deposit);
+        }
+
+        let mut voluntary_exits = Vec::new();
+        let mut pending_exits = Vec::new();
+        for exit in std::mem::take(&mut self.voluntary_exit_pool) {
+            if voluntary_exits.len() == MAX_VOLUNTARY_EXITS as usize || current_epoch < exit.epoch {
+                pending_exits.push(exit);
+            } else if self.validate_voluntary_exit(&exit, current_epoch).is_ok() {
+                self.initiate_validator_exit(exit.validator_index);
+                voluntary_exits.push(exit);
+            }
+        }
+        self.voluntary_exit_pool = pending_exits;
+        Operations {
+            proposer_slashings,
+            attester_slashings,
+            deposits,
+            voluntary_exits,
+        }
+    }
+
+    /// The balances of the online and all the active validators at the current epoch.
+    pub fn get_participation(&self, offline_validator_rate: f32) -> (Gwei, Gwei) {
+        let active_indices = self
+            .state
+            .get_active_validator_indices(compute_epoch_at_slot(self.slot));
+        (
+            self.state
+                .get_total_balance(&self.get_online_validator_indices(offline_validator_rate)),
+            self.state.get_total_balance(&active_indices),
+        )
+    }
+
+    /// The indices of the online validators at the current epoch.
+    /// The offline validators are the `offline_validator_rate` of the active validators with the smallest indices.
+    fn get_online_validator_indices(&self, offline_validator_rate: f32) -> Vec<ValidatorIndex> {
+        let mut active_indices = self
+            .state
+            .get_active_validator_indices(compute_epoch_at_slot(self.slot));
+        let offline_num = std::cmp::min(
+            (active_indices.len() as f32 * offline_validator_rate).round() as usize,
+            active_indices.len(),
+        );
+        active_indices.split_off(offline_num)
+    }
+
+    /// The indices of the online validators in the committee of the current slot.
+    /// Assumption: The committee of a slot is the active validators whose index is congruent to the slot
+    /// modulo `SLOTS_PER_EPOCH`, so that every active validator attests once per epoch.
+    /// The attestations of the slashed validators are not counted.
+    fn get_attesting_indices(&self, offline_validator_rate: f32) -> Vec<ValidatorIndex> {
+        self.get_online_validator_indices(offline_validator_rate)
+            .into_iter()
+            .filter(|index| index % SLOTS_PER_EPOCH == self.slot % SLOTS_PER_EPOCH)
+            .filter(|index| !self.state.validators[*index as usize].slashed)
+            .collect()
+    }
+
     /// Update the pending shard headers in the beacon state.
     /// Store the shard headers included in the new beacon block in the state.
     fn update_pending_shard_headers(
@@ -178,43 +1054,58 @@ impl BeaconChain {
         }
 
         if shard_headers_confirmed {
-            for header in self.state.previous_epoch_pending_shard_headers.iter_mut() {
-                header.confirmed = true;
-            }
-            for header in self.state.current_epoch_pending_shard_headers.iter_mut() {
-                header.confirmed = true;
+            for header in self
+                .state
+                .previous_epoch_pending_shard_headers
+                .iter_mut()
+                .chain(self.state.current_epoch_pending_shard_headers.iter_mut())
+            {
+                if !header.confirmed {
+                    header.confirmed = true;
+                    self.events.push(Event::ShardHeaderConfirmed {
+                        header: header.clone(),
+                    });
+                }
             }
         }
     }
 
     /// Update the beacon state for the new beacon block.
+    /// Return the operations included in the block.
     fn update_state_for_new_block(
         &mut self,
         included_previous_epoch_shard_headers: &[SignedShardHeader],
         included_current_epoch_shard_headers: &[SignedShardHeader],
         shard_headers_confirmed: bool,
-    ) {
+    ) -> Operations {
         self.update_pending_shard_headers(
             included_previous_epoch_shard_headers,
             included_current_epoch_shard_headers,
             shard_headers_confirmed,
         );
 
-        // Assumption: A new beacon block always include the attestations of the latest finalized checkpoint.
-        self.state.finalized_checkpoint = self.finalized_checkpoint.clone();
+        self.process_operations()
     }
 
     /// Create a new block and append to the main chain.
-    fn append_new_block_to_chain(&mut self, included_shard_headers: Vec<SignedShardHeader>) {
+    fn append_new_block_to_chain(
+        &mut self,
+        included_shard_headers: Vec<SignedShardHeader>,
+        operations: Operations,
+    ) -> Result<(), StoreError> {
         let new_block = BeaconBlock {
             slot: self.slot,
             parent_root: if self.is_first_block_proposal() {
                 GENESIS_PARENT_ROOT
             } else {
-                self.blocks.last().unwrap().header().root()
+                self.get_head_root()
             },
             state_root: self.state.root(),
             shard_headers: VariableList::from(included_shard_headers),
+            proposer_slashings: VariableList::from(operations.proposer_slashings),
+            attester_slashings: VariableList::from(operations.attester_slashings),
+            deposits: VariableList::from(operations.deposits),
+            voluntary_exits: VariableList::from(operations.voluntary_exits),
         };
 
         // Define checkpoints if necessary.
@@ -228,10 +1119,14 @@ impl BeaconChain {
         }
         assert!(self.checkpoints.len() == compute_epoch_at_slot(self.slot) as usize + 1);
 
-        // Store the new block.
-        self.blocks.push(new_block);
-        // Store the state of the new block.
-        self.states.push(self.state.clone());
+        self.events.push(Event::BlockAppended {
+            root: new_block.header().root(),
+            block: new_block.clone(),
+        });
+        // Store the new block and the state of the new block.
+        self.store.put_block(new_block, &self.state)?;
+        self.index_block(self.block_summaries().len() - 1);
+        Ok(())
     }
 
     /// Shard shard headers to be included in the current slot's beacon block.
@@ -275,26 +1170,47 @@ impl BeaconChain {
         )
     }
 
-    /// Progress consensus (off-chain finality).
-    /// The `finalized_checkpoint` in the beacon state is not updated in a slot without block proposal,
-    /// since attestations to finalized the checkpoint are not included in the chain yet.
-    /// Assumption: Checkpoints can be finalized only in the grandchild epochs.
-    fn progress_consensus(&mut self) {
-        if compute_epoch_at_slot(self.slot) < 2 {
-            return;
+    /// Progress consensus (off-chain justification and finality).
+    /// The checkpoints are justified and finalized with the attestations seen so far without waiting for the end of the epoch.
+    /// In the best case, the parent epoch's checkpoint gets finalized at 2/3 of the current epoch.
+    /// Assumption: The attestations are seen even in a slot without block proposal.
+    fn progress_consensus(&mut self) -> Result<(), StoreError> {
+        let mut state = self.state.clone();
+        state.weigh_justification_and_finalization(
+            compute_epoch_at_slot(self.slot),
+            &self.checkpoints,
+            self.state
+                .get_total_balance(&self.previous_epoch_target_attesters),
+            self.state
+                .get_total_balance(&self.current_epoch_target_attesters),
+        );
+        if state.current_justified_checkpoint != self.justified_checkpoint
+            && state.current_justified_checkpoint.epoch >= self.justified_checkpoint.epoch
+        {
+            self.justified_checkpoint = state.current_justified_checkpoint;
+            self.events.push(Event::CheckpointJustified {
+                checkpoint: self.justified_checkpoint.clone(),
+            });
         }
-        let finalized_epoch = compute_epoch_at_slot(self.slot) - 2;
-        if ((self.finalized_checkpoint == Checkpoint::genesis_finalized_checkpoint())
-            | (self.finalized_checkpoint.epoch < finalized_epoch))
-            && (self.checkpoints.len() > finalized_epoch as usize)
+        if state.finalized_checkpoint != self.finalized_checkpoint
+            && state.finalized_checkpoint.epoch >= self.finalized_checkpoint.epoch
         {
-            self.finalized_checkpoint = self.checkpoints[finalized_epoch as usize].clone();
+            self.finalized_checkpoint = state.finalized_checkpoint;
+            self.events.push(Event::CheckpointFinalized {
+                checkpoint: self.finalized_checkpoint.clone(),
+            });
+            if self.state_pruning {
+                // The checkpoint block is at or after the start slot of the finalized epoch.
+                self.store
+                    .prune_states(compute_start_slot_at_epoch(self.finalized_checkpoint.epoch))?;
+            }
         }
+        Ok(())
     }
 
     /// Whether or not it is the first time to propose a beacon block.
     fn is_first_block_proposal(&self) -> bool {
-        self.blocks.is_empty()
+        self.block_summaries().is_empty()
     }
 
     /// Whether or not checkpoints are not enough.
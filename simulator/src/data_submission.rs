@@ -0,0 +1,102 @@
+//! Submission of the data larger than a shard blob, which is split into pieces
+//! published as the bids across shards and slots.
+use crate::*;
+use errors::DataSubmissionError;
+use serde_derive::{Deserialize, Serialize};
+
+/// The maximum number of bytes of a piece, which fills a shard blob.
+pub const MAX_BYTES_PER_PIECE: usize = MAX_POINTS_PER_BLOCK as usize * BYTES_PER_POINT;
+
+/// The maximum number of bytes of the pieces kept by the simulator to reassemble the data.
+pub const MAX_KEPT_PIECE_BYTES: usize = 64 * 1024 * 1024;
+
+/// A piece of the submitted data, which is published as a bid of the shard at the slot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Piece {
+    pub shard: Shard,
+    pub slot: Slot,
+    pub commitment: DataCommitment,
+}
+
+/// The data of a piece kept by the simulator, which is published as a bid of the shard.
+#[derive(Debug, Clone)]
+pub struct KeptPiece {
+    pub shard: Shard,
+    pub commitment: DataCommitment,
+    pub data: Vec<u8>,
+}
+
+/// The pieces of the submitted data in order, which is needed to reassemble the data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// The number of bytes of the data.
+    pub length: u64,
+    pub pieces: Vec<Piece>,
+}
+
+/// The shards and the slots where the pieces are placed, and the fee of each bid.
+/// The pieces fill the shards of a slot before the next slot, in the ascending order of the slots.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Placement {
+    pub shards: Vec<Shard>,
+    pub slots: Vec<Slot>,
+    pub fee: Gwei,
+}
+
+impl Placement {
+    /// The placement in the consecutive slots from `start_slot`, with as many slots as the data needs.
+    pub fn consecutive(shards: Vec<Shard>, start_slot: Slot, data_len: usize, fee: Gwei) -> Self {
+        let pieces = piece_count(data_len);
+        let slot_num = match shards.len() {
+            0 => 0,
+            shard_num => (pieces + shard_num - 1) / shard_num,
+        };
+        Self {
+            slots: (start_slot..start_slot + slot_num as Slot).collect(),
+            shards,
+            fee,
+        }
+    }
+
+    /// The shard and the slot of each of `pieces` pieces.
+    pub fn place(&self, pieces: usize) -> Result<Vec<(Shard, Slot)>, DataSubmissionError> {
+        if self.shards.is_empty() {
+            return Err(DataSubmissionError::NoShards);
+        }
+        for (i, shard) in self.shards.iter().enumerate() {
+            if *shard >= SHARD_NUM as Shard {
+                return Err(DataSubmissionError::UnknownShard { found: *shard });
+            }
+            if self.shards[..i].contains(shard) {
+                return Err(DataSubmissionError::DuplicateShard { found: *shard });
+            }
+        }
+        let mut slots = self.slots.clone();
+        slots.sort_unstable();
+        if let Some(slot) = slots.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(DataSubmissionError::DuplicateSlot { found: slot[0] });
+        }
+        let capacity = self.shards.len() * slots.len();
+        if pieces > capacity {
+            return Err(DataSubmissionError::TooManyPieces {
+                pieces: pieces as u64,
+                capacity: capacity as u64,
+            });
+        }
+        Ok(slots
+            .iter()
+            .flat_map(|slot| self.shards.iter().map(move |shard| (*shard, *slot)))
+            .take(pieces)
+            .collect())
+    }
+}
+
+/// The number of the pieces of the data of `data_len` bytes.
+pub fn piece_count(data_len: usize) -> usize {
+    (data_len + MAX_BYTES_PER_PIECE - 1) / MAX_BYTES_PER_PIECE
+}
+
+/// Split the data into the pieces of at most `MAX_BYTES_PER_PIECE` bytes.
+pub fn split_data(data: &[u8]) -> Vec<&[u8]> {
+    data.chunks(MAX_BYTES_PER_PIECE).collect()
+}
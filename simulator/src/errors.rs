@@ -5,17 +5,33 @@ use thiserror::Error;
 pub enum SlotProcessingError {
     #[error("already processed slot (next slot is {next:?}, found {found:?})")]
     PastSlot { next: Slot, found: Slot },
+    #[error("failed to store the beacon chain: {0}")]
+    Store(#[from] StoreError),
 }
 
 #[derive(Error, Debug, PartialEq)]
 pub enum BidPublicationError {
+    #[error("bid for unknown shard (found {found:?})")]
+    UnknownShard { found: Shard },
     #[error("bid for already processed slot (next slot is {next:?}, found {found:?})")]
     PastSlot { next: Slot, found: Slot },
+    #[error("bid after the bid window of the slot {slot:?} closed (deadline {deadline_ms:?} ms, now {now_ms:?} ms)")]
+    TooLate {
+        slot: Slot,
+        deadline_ms: u64,
+        now_ms: u64,
+    },
     #[error(
         "bid with too large data (max length is {}, found {found:?})",
         MAX_POINTS_PER_BLOCK
     )]
     TooLargeData { found: u64 },
+    #[error("bid with empty data")]
+    EmptyData,
+    #[error("bid with fee below the shard gasprice (gasprice is {gasprice:?}, found {found:?})")]
+    TooLowFee { gasprice: Gwei, found: Gwei },
+    #[error("bid with data not matching the commitment length (expect {expect:?} points, found {found:?} points)")]
+    DataLengthMismatch { expect: u64, found: u64 },
     #[error("bid with invalid commitment (expect {expect:?}, found {found:?})")]
     InvalidCommitment {
         expect: DataCommitment,
@@ -25,3 +41,137 @@ pub enum BidPublicationError {
     #[error("bid with invalid shard (expect {expect:?}, found {found:?})")]
     InvalidShard { expect: Shard, found: Shard },
 }
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DepositError {
+    #[error(
+        "deposit with too small amount (min amount is {}, found {found:?})",
+        MIN_DEPOSIT_AMOUNT
+    )]
+    TooSmallAmount { found: Gwei },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum VoluntaryExitError {
+    #[error("exit of unknown validator (found {found:?})")]
+    UnknownValidator { found: ValidatorIndex },
+    #[error("exit of inactive validator (index {index:?})")]
+    InactiveValidator { index: ValidatorIndex },
+    #[error("exit of already exiting validator (index {index:?}, exit epoch {exit_epoch:?})")]
+    AlreadyExiting {
+        index: ValidatorIndex,
+        exit_epoch: Epoch,
+    },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SlashingError {
+    #[error("slashing of unknown validator (found {found:?})")]
+    UnknownValidator { found: ValidatorIndex },
+    #[error("slashing of unslashable validator (index {index:?})")]
+    UnslashableValidator { index: ValidatorIndex },
+    #[error("proposer slashing with headers of different slots ({slot_1:?} and {slot_2:?})")]
+    DifferentSlots { slot_1: Slot, slot_2: Slot },
+    #[error("proposer slashing with identical headers")]
+    IdenticalHeaders,
+    #[error(
+        "attestation with invalid attesting indices (must be sorted, unique and at most {} indices)",
+        MAX_VALIDATORS_PER_COMMITTEE
+    )]
+    InvalidAttestingIndices,
+    #[error("attester slashing with neither double vote nor surround vote")]
+    UnslashableAttestationData,
+    #[error("attester slashing without slashable validators")]
+    NoSlashableValidators,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FailureModelError {
+    #[error("probability must be between 0 and 1 (found {found:?})")]
+    InvalidRate { found: f64 },
+    #[error("unknown shard (found {found:?})")]
+    UnknownShard { found: ShardId },
+    #[error("invalid duration of outages (min {min_epochs:?} epochs, max {max_epochs:?} epochs)")]
+    InvalidOutageDuration {
+        min_epochs: Epoch,
+        max_epochs: Epoch,
+    },
+    #[error("invalid slots of an incident (start {start_slot:?}, end {end_slot:?})")]
+    InvalidIncidentSlots { start_slot: Slot, end_slot: Slot },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum PropagationModelError {
+    #[error("drop rate must be between 0 and 1 (found {found:?})")]
+    InvalidDropRate { found: f64 },
+    #[error("invalid latency (min {min_ms:?} ms, max {max_ms:?} ms)")]
+    InvalidLatency { min_ms: u64, max_ms: u64 },
+    #[error("unknown shard (found {found:?})")]
+    UnknownShard { found: ShardId },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TimeError {
+    #[error("time before the current virtual time (now {now_ms:?} ms, found {found:?} ms)")]
+    PastTime { now_ms: u64, found: u64 },
+    // Note: This is only used in http_api.
+    #[error("invalid time or duration in seconds (found {found:?})")]
+    InvalidSeconds { found: f64 },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DataSubmissionError {
+    #[error("submission of empty data")]
+    EmptyData,
+    #[error("submission without shards")]
+    NoShards,
+    #[error("submission to unknown shard (found {found:?})")]
+    UnknownShard { found: Shard },
+    #[error("submission to duplicate shard (found {found:?})")]
+    DuplicateShard { found: Shard },
+    #[error("submission to duplicate slot (found {found:?})")]
+    DuplicateSlot { found: Slot },
+    #[error("submission of more pieces than the shards and the slots can hold (pieces {pieces:?}, capacity {capacity:?})")]
+    TooManyPieces { pieces: u64, capacity: u64 },
+    #[error("piece {index:?} rejected: {error}")]
+    Bid {
+        index: usize,
+        error: BidPublicationError,
+    },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DataReassemblyError {
+    #[error("piece {index:?} not confirmed (shard {shard:?}, slot {slot:?})")]
+    NotConfirmed {
+        index: usize,
+        shard: Shard,
+        slot: Slot,
+    },
+    #[error("data of piece {index:?} not kept by the simulator")]
+    UnknownPiece { index: usize },
+    #[error("reassembled data of wrong length (expect {expect:?}, found {found:?})")]
+    InvalidLength { expect: u64, found: u64 },
+    #[error("failed to read the beacon chain: {0}")]
+    Store(#[from] StoreError),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum StoreError {
+    #[error("I/O error: {0}")]
+    Io(String),
+    #[error("broken data: {0}")]
+    BrokenData(String),
+}
+
+impl From<std::io::Error> for StoreError {
+    fn from(e: std::io::Error) -> Self {
+        StoreError::Io(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self {
+        StoreError::BrokenData(e.to_string())
+    }
+}
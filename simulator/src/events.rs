@@ -0,0 +1,69 @@
+use crate::slot_clock::SlotClock;
+use crate::*;
+
+/// Events in the simulation.
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The simulator is reset to the genesis.
+    SimulatorReset,
+    /// The genesis time or the slot duration of the slot clock is changed.
+    SlotClockChanged {
+        previous: SlotClock,
+        current: SlotClock,
+    },
+    /// A slot is processed.
+    SlotProcessed { slot: Slot },
+    /// A shard blob is proposed.
+    ShardBlobProposed { header: SignedShardHeader },
+    /// A bid is selected by a shard blob proposer.
+    BidSelected { bid: Bid },
+    /// A bid is dropped in the network or arrives after its slot is processed.
+    BidMissed { bid: Bid },
+    /// A shard header is published on the global subnet.
+    ShardHeaderPublished { header: SignedShardHeader },
+    /// A beacon block is appended to the main chain.
+    BlockAppended { root: Root, block: BeaconBlock },
+    /// A shard header included in the beacon chain is confirmed.
+    ShardHeaderConfirmed { header: PendingShardHeader },
+    /// A checkpoint is justified.
+    CheckpointJustified { checkpoint: Checkpoint },
+    /// A checkpoint is finalized.
+    CheckpointFinalized { checkpoint: Checkpoint },
+    /// An epoch is processed.
+    EpochProcessed { epoch: Epoch },
+    /// The shard gasprice is updated.
+    GaspriceUpdated { previous: Gwei, current: Gwei },
+    /// A deposit is processed, which adds a validator or increases its balance.
+    DepositProcessed { index: ValidatorIndex, amount: Gwei },
+    /// A validator is dequeued from the activation queue.
+    ValidatorActivationScheduled {
+        index: ValidatorIndex,
+        activation_epoch: Epoch,
+    },
+    /// The exit of a validator is initiated.
+    ValidatorExitInitiated {
+        index: ValidatorIndex,
+        exit_epoch: Epoch,
+    },
+    /// A proposer slashing is included in a beacon block.
+    ProposerSlashingProcessed { slashing: ProposerSlashing },
+    /// An attester slashing is included in a beacon block.
+    AttesterSlashingProcessed { slashing: AttesterSlashing },
+    /// A validator is slashed and its balance is decreased by the penalty.
+    ValidatorSlashed {
+        index: ValidatorIndex,
+        penalty: Gwei,
+    },
+}
+
+/// Observer of the events in the simulation.
+/// Observers are registered by `Simulator::subscribe()`.
+pub trait Observer: Send {
+    fn on_event(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event) + Send> Observer for F {
+    fn on_event(&mut self, event: &Event) {
+        self(event)
+    }
+}
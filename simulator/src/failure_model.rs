@@ -0,0 +1,257 @@
+//! Failure model of the auto mode, which samples the simulation params of each slot.
+use crate::*;
+use errors::FailureModelError;
+use serde_derive::{Deserialize, Serialize};
+use simulation_params::*;
+use std::collections::BTreeMap;
+
+/// Probabilities of the failures of the beacon chain in a slot, which are independent of each other.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BeaconFailureRates {
+    /// Probability that no beacon block is proposed.
+    pub block_proposal: f64,
+    /// Probability that the validators in the committee do not attest to the checkpoint.
+    pub attestation: f64,
+    /// Probability that the non-included shard headers are not included.
+    pub shard_header_inclusion: f64,
+    /// Probability that the non-confirmed shard headers are not confirmed.
+    pub shard_header_confirmation: f64,
+}
+
+/// Probabilities of the failures of a shard in a slot, which are independent of each other.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShardFailureRates {
+    /// Probability that no shard blob is proposed.
+    pub blob_proposal: f64,
+    /// Probability that no data is included in the proposed shard blob.
+    pub data_inclusion: f64,
+}
+
+/// A failure lasting during an outage or an incident.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Failure {
+    NoBeaconBlockProposal,
+    /// No validator attests to the checkpoints, so the finality stalls.
+    NoChainFinality,
+    NoShardHeaderInclusion,
+    NoShardHeaderConfirmation,
+    /// The validators of the rate of the active stake are offline.
+    OfflineValidators {
+        rate: f32,
+    },
+    /// No shard blob is proposed in the shard, or in any shard if `shard` is `None`.
+    NoShardBlobProposal {
+        #[serde(default)]
+        shard: Option<ShardId>,
+    },
+    /// No data is included in the shard, or in any shard if `shard` is `None`.
+    NoShardDataInclusion {
+        #[serde(default)]
+        shard: Option<ShardId>,
+    },
+}
+
+impl Failure {
+    fn apply(&self, params: &mut SimulationParams) {
+        let beacon_params = &mut params.beacon_params;
+        match *self {
+            Failure::NoBeaconBlockProposal => {
+                // Note: If there is no beacon block proposed, shard headers cannot be included.
+                beacon_params.beacon_block_proposed = false;
+                beacon_params.shard_headers_included = false;
+                beacon_params.shard_headers_confirmed = false;
+            }
+            Failure::NoChainFinality => beacon_params.beacon_chain_finalized = false,
+            Failure::NoShardHeaderInclusion => beacon_params.shard_headers_included = false,
+            Failure::NoShardHeaderConfirmation => beacon_params.shard_headers_confirmed = false,
+            Failure::OfflineValidators { rate } => {
+                beacon_params.offline_validator_rate =
+                    beacon_params.offline_validator_rate.max(rate)
+            }
+            Failure::NoShardBlobProposal { shard } => {
+                for shard_params in Self::shard_params(params, shard) {
+                    *shard_params = ShardSimulationParams::no_blob_proposal();
+                }
+            }
+            Failure::NoShardDataInclusion { shard } => {
+                for shard_params in Self::shard_params(params, shard) {
+                    shard_params.data_included = false;
+                }
+            }
+        }
+    }
+
+    /// The params of the shard, or of all the shards if `shard` is `None`.
+    fn shard_params(
+        params: &mut SimulationParams,
+        shard: Option<ShardId>,
+    ) -> impl Iterator<Item = &mut ShardSimulationParams> {
+        params
+            .shard_params
+            .iter_mut()
+            .enumerate()
+            .filter(move |(id, _)| shard.map_or(true, |shard| shard == *id as ShardId))
+            .map(|(_, shard_params)| shard_params)
+    }
+
+    fn validate(&self) -> Result<(), FailureModelError> {
+        match *self {
+            Failure::OfflineValidators { rate } => validate_rate(rate as f64),
+            Failure::NoShardBlobProposal { shard: Some(shard) }
+            | Failure::NoShardDataInclusion { shard: Some(shard) } => validate_shard(shard),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Outages starting at random epochs and lasting for a random number of epochs.
+/// It is a Markov chain of two states (up and down) per epoch,
+/// where the number of epochs of an outage is uniformly distributed in `[min_epochs, max_epochs]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutageModel {
+    pub failure: Failure,
+    /// Probability that an outage starts at the start of an epoch without an ongoing outage.
+    pub start_rate: f64,
+    pub min_epochs: Epoch,
+    pub max_epochs: Epoch,
+}
+
+/// An incident scheduled in the slots from `start_slot` to `end_slot` (inclusive).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Incident {
+    pub failure: Failure,
+    pub start_slot: Slot,
+    pub end_slot: Slot,
+}
+
+/// Failure model of the slots. The default model has no failure.
+/// The failures of the independent rates, the ongoing outages and the scheduled incidents are combined.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailureModel {
+    /// Probability that a slot fails by one of the preset failure cases picked at random.
+    pub preset: f64,
+    pub beacon: BeaconFailureRates,
+    /// Failure rates of each shard.
+    pub shard: ShardFailureRates,
+    /// Failure rates of the specific shards, which override `shard`.
+    pub shards: BTreeMap<ShardId, ShardFailureRates>,
+    pub outages: Vec<OutageModel>,
+    pub incidents: Vec<Incident>,
+}
+
+impl FailureModel {
+    pub fn validate(&self) -> Result<(), FailureModelError> {
+        validate_rate(self.preset)?;
+        validate_rate(self.beacon.block_proposal)?;
+        validate_rate(self.beacon.attestation)?;
+        validate_rate(self.beacon.shard_header_inclusion)?;
+        validate_rate(self.beacon.shard_header_confirmation)?;
+        validate_rate(self.shard.blob_proposal)?;
+        validate_rate(self.shard.data_inclusion)?;
+        for (shard, rates) in self.shards.iter() {
+            validate_shard(*shard)?;
+            validate_rate(rates.blob_proposal)?;
+            validate_rate(rates.data_inclusion)?;
+        }
+        for outage in self.outages.iter() {
+            outage.failure.validate()?;
+            validate_rate(outage.start_rate)?;
+            if outage.min_epochs == 0 || outage.min_epochs > outage.max_epochs {
+                return Err(FailureModelError::InvalidOutageDuration {
+                    min_epochs: outage.min_epochs,
+                    max_epochs: outage.max_epochs,
+                });
+            }
+        }
+        for incident in self.incidents.iter() {
+            incident.failure.validate()?;
+            if incident.start_slot > incident.end_slot {
+                return Err(FailureModelError::InvalidIncidentSlots {
+                    start_slot: incident.start_slot,
+                    end_slot: incident.end_slot,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Sample the params of the slot.
+    /// `ongoing_outages` has the last epoch of the ongoing outage of each of `outages`, and is updated.
+    pub fn sample<R: Rng>(
+        &self,
+        slot: Slot,
+        ongoing_outages: &mut Vec<Option<Epoch>>,
+        rng: &mut R,
+    ) -> SimulationParams {
+        let mut params = if rng.gen_bool(self.preset) {
+            SimulationParams::random_failure(rng)
+        } else {
+            SimulationParams::happy()
+        };
+
+        let beacon = &self.beacon;
+        if rng.gen_bool(beacon.block_proposal) {
+            Failure::NoBeaconBlockProposal.apply(&mut params);
+        }
+        if rng.gen_bool(beacon.attestation) {
+            Failure::NoChainFinality.apply(&mut params);
+        }
+        if rng.gen_bool(beacon.shard_header_inclusion) {
+            Failure::NoShardHeaderInclusion.apply(&mut params);
+        }
+        if rng.gen_bool(beacon.shard_header_confirmation) {
+            Failure::NoShardHeaderConfirmation.apply(&mut params);
+        }
+        for (shard, shard_params) in params.shard_params.iter_mut().enumerate() {
+            let rates = self.shards.get(&(shard as ShardId)).unwrap_or(&self.shard);
+            if rng.gen_bool(rates.blob_proposal) {
+                *shard_params = ShardSimulationParams::no_blob_proposal();
+            } else if rng.gen_bool(rates.data_inclusion) {
+                shard_params.data_included = false;
+            }
+        }
+
+        let epoch = compute_epoch_at_slot(slot);
+        ongoing_outages.resize(self.outages.len(), None);
+        for (outage, last_epoch) in self.outages.iter().zip(ongoing_outages.iter_mut()) {
+            if last_epoch.map_or(false, |last_epoch| last_epoch < epoch) {
+                *last_epoch = None;
+            }
+            if last_epoch.is_none()
+                && slot == compute_start_slot_at_epoch(epoch)
+                && rng.gen_bool(outage.start_rate)
+            {
+                let epochs = rng.gen_range(outage.min_epochs..=outage.max_epochs);
+                *last_epoch = Some(epoch + epochs - 1);
+            }
+            if last_epoch.is_some() {
+                outage.failure.apply(&mut params);
+            }
+        }
+
+        for incident in self.incidents.iter() {
+            if (incident.start_slot..=incident.end_slot).contains(&slot) {
+                incident.failure.apply(&mut params);
+            }
+        }
+        params
+    }
+}
+
+fn validate_rate(rate: f64) -> Result<(), FailureModelError> {
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(FailureModelError::InvalidRate { found: rate });
+    }
+    Ok(())
+}
+
+fn validate_shard(shard: ShardId) -> Result<(), FailureModelError> {
+    if shard >= SHARD_NUM as ShardId {
+        return Err(FailureModelError::UnknownShard { found: shard });
+    }
+    Ok(())
+}
@@ -6,9 +6,16 @@ pub use common::eth2_types::*;
 pub use common::bid::*;
 pub use common::eth2_config::*;
 pub use common::eth2_utils::*;
+pub use common::merkle::*;
 
 mod beacon_chain;
 mod shard;
 mod simulation_params;
 pub mod simulator;
+pub mod slot_clock;
+pub mod store;
 pub mod errors;
+pub mod events;
+pub mod failure_model;
+pub mod propagation_model;
+pub mod data_submission;
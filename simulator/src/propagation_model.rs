@@ -0,0 +1,116 @@
+//! Propagation model of the shard headers and the bids in the network.
+use crate::*;
+use errors::PropagationModelError;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Distribution of the latency in milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Latency {
+    Constant {
+        ms: u64,
+    },
+    /// Uniformly distributed in `[min_ms, max_ms]`.
+    Uniform {
+        min_ms: u64,
+        max_ms: u64,
+    },
+    /// `min_ms` plus an exponentially distributed delay of the mean `mean_extra_ms`,
+    /// which has a long tail like gossip propagation.
+    Exponential {
+        min_ms: u64,
+        mean_extra_ms: u64,
+    },
+}
+
+impl Default for Latency {
+    /// No latency.
+    fn default() -> Self {
+        Latency::Constant { ms: 0 }
+    }
+}
+
+impl Latency {
+    fn sample<R: Rng>(&self, rng: &mut R) -> u64 {
+        match *self {
+            Latency::Constant { ms } => ms,
+            Latency::Uniform { min_ms, max_ms } => rng.gen_range(min_ms..=max_ms),
+            Latency::Exponential {
+                min_ms,
+                mean_extra_ms,
+            } => {
+                // Inverse transform sampling, where `1 - u` is in (0, 1].
+                let u: f64 = rng.gen();
+                min_ms + (-(1.0 - u).ln() * mean_extra_ms as f64).round() as u64
+            }
+        }
+    }
+}
+
+/// Propagation of the messages from the senders to the receivers in a subnet.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkModel {
+    pub latency: Latency,
+    /// Probability that a message never reaches the receiver.
+    pub drop_rate: f64,
+}
+
+impl LinkModel {
+    /// Sample the latency in milliseconds of a message. `None` if the message is dropped.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Option<u64> {
+        if rng.gen_bool(self.drop_rate) {
+            return None;
+        }
+        Some(self.latency.sample(rng))
+    }
+
+    fn validate(&self) -> Result<(), PropagationModelError> {
+        if !(0.0..=1.0).contains(&self.drop_rate) {
+            return Err(PropagationModelError::InvalidDropRate {
+                found: self.drop_rate,
+            });
+        }
+        if let Latency::Uniform { min_ms, max_ms } = self.latency {
+            if min_ms > max_ms {
+                return Err(PropagationModelError::InvalidLatency { min_ms, max_ms });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Propagation model of the network. The default model delivers every message instantly.
+/// Assumption: A shard blob is proposed at the start of its slot, and the beacon block of the slot
+/// includes the shard headers arriving by the end of the slot.
+/// A shard blob proposer selects the bids arriving by the time its slot is processed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PropagationModel {
+    /// Propagation of the shard headers from the shard blob proposers to the beacon block proposers.
+    pub global_subnet: LinkModel,
+    /// Propagation of the bids from the submitters to the shard blob proposers.
+    pub shard_subnet: LinkModel,
+    /// Propagation of the bids in the specific shards, which overrides `shard_subnet`.
+    pub shard_subnets: BTreeMap<ShardId, LinkModel>,
+}
+
+impl PropagationModel {
+    pub fn validate(&self) -> Result<(), PropagationModelError> {
+        self.global_subnet.validate()?;
+        self.shard_subnet.validate()?;
+        for (shard, link) in self.shard_subnets.iter() {
+            if *shard >= SHARD_NUM as ShardId {
+                return Err(PropagationModelError::UnknownShard { found: *shard });
+            }
+            link.validate()?;
+        }
+        Ok(())
+    }
+
+    /// The propagation of the bids in the shard.
+    pub fn shard_subnet(&self, shard: ShardId) -> &LinkModel {
+        self.shard_subnets.get(&shard).unwrap_or(&self.shard_subnet)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::*;
+use events::Event;
 use simulation_params::ShardSimulationParams;
 
 /// Shard.
@@ -12,6 +13,8 @@ pub struct Shard {
     // Proposed shard headers of each slot.
     // Assumption: No equivocation.
     pub proposed_headers: Vec<Option<SignedShardHeader>>,
+    // Events emitted in the slot being processed, which are drained by the simulator.
+    pub events: Vec<Event>,
 }
 
 impl Shard {
@@ -21,9 +24,22 @@ impl Shard {
             shard_id,
             bid_pool: Vec::new(),
             proposed_headers: Vec::new(),
+            events: Vec::new(),
         }
     }
 
+    /// Move to the slot without proposing shard blobs.
+    /// Note: This is used to resume the simulation from a store.
+    pub fn resume_at(&mut self, slot: Slot) {
+        while self.bid_pool.len() < slot as usize {
+            self.bid_pool.push(Vec::new());
+        }
+        while self.proposed_headers.len() < slot as usize {
+            self.proposed_headers.push(None);
+        }
+        self.slot = slot;
+    }
+
     /// Publish a bid in the shard's subnet.
     pub fn publish_bid(&mut self, bid: Bid) {
         while self.bid_pool.len() <= bid.slot as usize {
@@ -57,14 +73,19 @@ impl Shard {
         } else {
             // Sort bids in ascending order by fee
             self.bid_pool[self.slot as usize].sort_by(|a, b| a.fee.cmp(&b.fee));
-            commitment = self.bid_pool[self.slot as usize].pop().unwrap().commitment;
+            let bid = self.bid_pool[self.slot as usize].pop().unwrap();
+            commitment = bid.commitment.clone();
+            self.events.push(Event::BidSelected { bid });
         }
         // Note: For now, use dummy data to replace BLS signature.
-        self.proposed_headers
-            .push(Some(SignedShardHeader::dummy_from_header(ShardHeader {
-                slot: self.slot,
-                shard: self.shard_id,
-                commitment,
-            })));
+        let header = SignedShardHeader::dummy_from_header(ShardHeader {
+            slot: self.slot,
+            shard: self.shard_id,
+            commitment,
+        });
+        self.events.push(Event::ShardBlobProposed {
+            header: header.clone(),
+        });
+        self.proposed_headers.push(Some(header));
     }
 }
@@ -47,9 +47,10 @@ impl ShardSimulationParams {
 pub struct BeaconSimulationParams {
     /// Whether or not a beacon block is proposed.
     pub beacon_block_proposed: bool,
-    /// Whether or not the grandparent epoch's checkpoint gets finalized (if it is not finalized yet).
-    /// Assumption: Checkpoints can be finalized only in the grandchild epochs.
-    /// TODO: Simulate the best case where the parent epoch's checkpoint gets finalized at the 2/3 of the current epoch.
+    /// Whether or not the online validators in the committee of the slot attest to the checkpoint of the current epoch.
+    /// A checkpoint is justified with the attestations of 2/3 of the active stake,
+    /// and finalized by the rules of Casper FFG with the justified checkpoints.
+    /// In the best case, the parent epoch's checkpoint gets finalized at 2/3 of the current epoch.
     pub beacon_chain_finalized: bool,
     /// Whether or not non-included headers of each shard are included.
     /// Assumption: All the skipped headers are included in a beacon block later at the same time.
@@ -61,6 +62,10 @@ pub struct BeaconSimulationParams {
     /// Assumption: All the non-confirmed headers are confirmed later at the same time.
     /// TODO: Allow shard-by-shard configuration.
     pub shard_headers_confirmed: bool,
+    /// The rate of the offline validators in the active validators, which do not attest.
+    /// Assumption: The offline validators are the active validators with the smallest indices.
+    /// Their balances decay during the inactivity leak, i.e. when the chain has not finalized for a while.
+    pub offline_validator_rate: f32,
 }
 
 impl BeaconSimulationParams {
@@ -71,6 +76,7 @@ impl BeaconSimulationParams {
             beacon_chain_finalized: true,
             shard_headers_included: true,
             shard_headers_confirmed: true,
+            offline_validator_rate: 0.0,
         }
     }
 
@@ -81,6 +87,7 @@ impl BeaconSimulationParams {
             beacon_chain_finalized: false,
             shard_headers_included: false,
             shard_headers_confirmed: false,
+            offline_validator_rate: 0.0,
         }
     }
 
@@ -92,16 +99,26 @@ impl BeaconSimulationParams {
             beacon_chain_finalized: true,
             shard_headers_included: false,
             shard_headers_confirmed: false,
+            offline_validator_rate: 0.0,
         }
     }
 
-    /// No checkpoint gets finalized.
+    /// The validators of the rate are offline.
+    pub fn offline_validators(offline_validator_rate: f32) -> Self {
+        Self {
+            offline_validator_rate,
+            ..Self::happy()
+        }
+    }
+
+    /// No validator attests to the checkpoints.
     pub fn no_chain_finality() -> Self {
         Self {
             beacon_block_proposed: true,
             beacon_chain_finalized: false,
             shard_headers_included: true,
             shard_headers_confirmed: true,
+            offline_validator_rate: 0.0,
         }
     }
 
@@ -112,6 +129,7 @@ impl BeaconSimulationParams {
             beacon_chain_finalized: false,
             shard_headers_included: false,
             shard_headers_confirmed: true,
+            offline_validator_rate: 0.0,
         }
     }
 
@@ -122,6 +140,7 @@ impl BeaconSimulationParams {
             beacon_chain_finalized: false,
             shard_headers_included: true,
             shard_headers_confirmed: false,
+            offline_validator_rate: 0.0,
         }
     }
 }
@@ -207,6 +226,16 @@ impl SimulationParams {
         }
     }
 
+    /// The validators of the rate are offline.
+    pub fn offline_validators(offline_validator_rate: f32) -> Self {
+        Self {
+            beacon_params: BeaconSimulationParams::offline_validators(offline_validator_rate),
+            shard_params: (0..SHARD_NUM)
+                .map(|_| ShardSimulationParams::happy())
+                .collect(),
+        }
+    }
+
     /// No beacon block is proposed.
     pub fn no_beacon_block_proposal() -> Self {
         Self {
@@ -217,6 +246,19 @@ impl SimulationParams {
         }
     }
 
+    /// One of the failure cases picked at random.
+    pub fn random_failure<R: Rng>(rng: &mut R) -> Self {
+        match rng.gen_range(0..7) {
+            0 => Self::all_failure(),
+            1 => Self::no_shard_data_inclusion(),
+            2 => Self::no_shard_blob_proposal(),
+            3 => Self::no_shard_header_inclusion(),
+            4 => Self::no_shard_header_confirmation(),
+            5 => Self::no_beacon_chain_finality(),
+            _ => Self::no_beacon_block_proposal(),
+        }
+    }
+
     /// Fails randomly.
     pub fn random() -> Self {
         let rn: usize = rand::thread_rng().gen();
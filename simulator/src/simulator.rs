@@ -1,7 +1,30 @@
 use crate::*;
 use beacon_chain::*;
+use data_submission::{KeptPiece, Manifest, Piece, Placement, MAX_KEPT_PIECE_BYTES};
 pub use errors::*;
+pub use events::*;
+use failure_model::FailureModel;
+use propagation_model::PropagationModel;
 use simulation_params::*;
+use slot_clock::{SlotClock, SlotPhase};
+use std::collections::BTreeMap;
+use store::*;
+
+/// Status of the shard header of a shard at a slot.
+#[derive(Debug, Clone)]
+pub struct ShardHeaderStatus {
+    pub slot: Slot,
+    pub shard: ShardId,
+    // The proposed shard header. `None` if no shard blob is proposed.
+    pub header: Option<SignedShardHeader>,
+    // The beacon block including the shard header.
+    pub included_block: Option<BeaconBlock>,
+    // Whether or not the shard header is confirmed.
+    // `None` if it is unknown since the state is already pruned.
+    pub confirmed: Option<bool>,
+    // Whether or not the beacon block including the shard header is finalized.
+    pub finalized: bool,
+}
 
 /// Eth2 simulator.
 pub struct Simulator {
@@ -12,6 +35,33 @@ pub struct Simulator {
     pub shards: Vec<shard::Shard>,
     // Settings of the simulation of each slot.
     pub params: Vec<SimulationParams>,
+    // The clock of the slots from the genesis time.
+    // Note: The simulator does not process the slots as the clock ticks; the caller does.
+    pub slot_clock: SlotClock,
+    // The virtual Unix time in milliseconds, which is controlled explicitly.
+    // `None` if the wall-clock time is used.
+    pub virtual_time_ms: Option<u64>,
+    // The offset in milliseconds from the start of a slot when the bid window for the next slot closes.
+    // `None` if the bids are accepted until the slot is processed.
+    // Note: The deadline follows the slot clock, so it is meant for the auto mode or the virtual time.
+    pub bid_deadline_ms: Option<u64>,
+    // The last epoch of the ongoing outage of each outage model of the failure model.
+    ongoing_outages: Vec<Option<Epoch>>,
+    // Model of the propagation of the shard headers and the bids in the network.
+    propagation_model: PropagationModel,
+    // Shard headers in flight on the global subnet with the slot they arrive in.
+    in_flight_headers: Vec<(Slot, SignedShardHeader)>,
+    // Bids in flight on the shard subnets with the Unix time in milliseconds they arrive at.
+    in_flight_bids: Vec<(u64, Bid)>,
+    // Data of the pieces submitted by `submit_data` by slot, to reassemble the data.
+    // The pieces are kept until their slot is finalized or they can no longer be confirmed,
+    // and the pieces of the oldest slots are evicted beyond `MAX_KEPT_PIECE_BYTES`.
+    // Note: The pieces are not stored, so they are lost when the simulator is restarted.
+    piece_data: BTreeMap<Slot, Vec<KeptPiece>>,
+    // The total number of bytes of the kept pieces.
+    piece_data_bytes: usize,
+    // Observers of the events in the simulation.
+    observers: Vec<Box<dyn Observer>>,
 }
 
 impl Simulator {
@@ -23,7 +73,176 @@ impl Simulator {
                 .map(|shard_id| shard::Shard::new(shard_id as ShardId))
                 .collect(),
             params: Vec::new(),
+            slot_clock: SlotClock::default(),
+            virtual_time_ms: None,
+            bid_deadline_ms: None,
+            ongoing_outages: Vec::new(),
+            propagation_model: PropagationModel::default(),
+            in_flight_headers: Vec::new(),
+            in_flight_bids: Vec::new(),
+            piece_data: BTreeMap::new(),
+            piece_data_bytes: 0,
+            observers: Vec::new(),
+        }
+    }
+
+    /// Create a simulator with the storage backend of the beacon chain.
+    /// If the store has blocks, resume from the latest stored block with the stored slot clock.
+    pub fn with_store(store: Box<dyn Store>) -> Result<Self, StoreError> {
+        let beacon_chain = BeaconChain::with_store(store)?;
+        let slot_clock = beacon_chain.slot_clock.unwrap_or_default();
+        let slot = beacon_chain.slot;
+        let mut shards: Vec<shard::Shard> = (0..SHARD_NUM)
+            .map(|shard_id| shard::Shard::new(shard_id as ShardId))
+            .collect();
+        for shard in shards.iter_mut() {
+            shard.resume_at(slot);
+        }
+        // Restore the proposed shard headers that are included in the stored blocks.
+        // Note: The blocks are loaded one by one so as not to keep all of them in memory.
+        for summary in beacon_chain.block_summaries() {
+            let block = match beacon_chain.get_block_by_slot(summary.slot)? {
+                Some(block) => block,
+                None => continue,
+            };
+            for header in block.shard_headers.iter() {
+                shards[header.message.shard as usize].proposed_headers
+                    [header.message.slot as usize] = Some(header.clone());
+            }
+        }
+        Ok(Self {
+            slot,
+            beacon_chain,
+            shards,
+            // Note: The settings of the slots processed before the resumption are not stored.
+            // They are not used after the slots are processed, so fill them with the happy case.
+            params: (0..slot).map(|_| SimulationParams::happy()).collect(),
+            slot_clock,
+            virtual_time_ms: None,
+            bid_deadline_ms: None,
+            ongoing_outages: Vec::new(),
+            propagation_model: PropagationModel::default(),
+            in_flight_headers: Vec::new(),
+            in_flight_bids: Vec::new(),
+            piece_data: BTreeMap::new(),
+            piece_data_bytes: 0,
+            observers: Vec::new(),
+        })
+    }
+
+    /// Reset the simulator to the genesis, deleting everything in the store.
+    pub fn reset(&mut self) -> Result<(), StoreError> {
+        let mut store: Box<dyn Store> = Box::new(MemoryStore::new());
+        std::mem::swap(&mut store, &mut self.beacon_chain.store);
+        if let Err(e) = store.clear() {
+            self.beacon_chain.store = store;
+            return Err(e);
         }
+        let state_pruning = self.beacon_chain.state_pruning;
+        let slot_clock = self.slot_clock;
+        let virtual_time_ms = self.virtual_time_ms;
+        let bid_deadline_ms = self.bid_deadline_ms;
+        let propagation_model = std::mem::take(&mut self.propagation_model);
+        let observers = std::mem::take(&mut self.observers);
+        *self = Self::with_store(store)?;
+        self.beacon_chain.state_pruning = state_pruning;
+        self.slot_clock = slot_clock;
+        self.virtual_time_ms = virtual_time_ms;
+        self.bid_deadline_ms = bid_deadline_ms;
+        self.propagation_model = propagation_model;
+        self.observers = observers;
+        self.notify(&[Event::SimulatorReset]);
+        Ok(())
+    }
+
+    /// The current Unix time in milliseconds in the simulation: the virtual time if it is used,
+    /// or the wall-clock time.
+    pub fn now_ms(&self) -> u64 {
+        self.virtual_time_ms
+            .unwrap_or_else(slot_clock::unix_time_ms)
+    }
+
+    /// Set the virtual time, switching from the wall-clock time if it is used.
+    /// The virtual time can not go backwards.
+    pub fn set_time(&mut self, time_ms: u64) -> Result<(), TimeError> {
+        if let Some(now_ms) = self.virtual_time_ms {
+            if time_ms < now_ms {
+                return Err(TimeError::PastTime {
+                    now_ms,
+                    found: time_ms,
+                });
+            }
+        }
+        self.virtual_time_ms = Some(time_ms);
+        Ok(())
+    }
+
+    /// Advance the virtual time, switching from the wall-clock time if it is used.
+    pub fn advance_time(&mut self, duration_ms: u64) {
+        self.virtual_time_ms = Some(self.now_ms() + duration_ms);
+    }
+
+    /// Replace the slot clock, which is stored with the head.
+    /// The observers are notified if the clock is changed.
+    pub fn set_slot_clock(&mut self, slot_clock: SlotClock) -> Result<(), StoreError> {
+        self.beacon_chain.put_slot_clock(slot_clock)?;
+        let previous = self.slot_clock;
+        self.slot_clock = slot_clock;
+        if previous != slot_clock {
+            self.notify(&[Event::SlotClockChanged {
+                previous,
+                current: slot_clock,
+            }]);
+        }
+        Ok(())
+    }
+
+    /// The slot clock stored with the head. `None` if no slot is stored or it is stored without the clock.
+    pub fn stored_slot_clock(&self) -> Option<SlotClock> {
+        self.beacon_chain
+            .store
+            .head()
+            .and_then(|head| head.slot_clock)
+    }
+
+    /// The Unix time in milliseconds when the bid window for the slot closes,
+    /// which is in the slot before it. `None` if the window does not close.
+    pub fn bid_deadline_time_ms(&self, slot: Slot) -> Option<u64> {
+        let bid_deadline_ms = self.bid_deadline_ms?;
+        let window_start_ms = self
+            .slot_clock
+            .slot_start_time_ms(slot)
+            .saturating_sub(self.slot_clock.slot_duration_ms);
+        Some(window_start_ms + bid_deadline_ms)
+    }
+
+    /// The phase within the current slot of the clock. `None` before the genesis.
+    pub fn slot_phase(&self) -> Option<SlotPhase> {
+        let now_ms = self.now_ms();
+        let slot = self.slot_clock.slot_at(now_ms)?;
+        match self.bid_deadline_time_ms(slot + 1) {
+            Some(deadline_ms) if deadline_ms <= now_ms => Some(SlotPhase::BidsClosed),
+            _ => Some(SlotPhase::BidWindow),
+        }
+    }
+
+    pub fn propagation_model(&self) -> &PropagationModel {
+        &self.propagation_model
+    }
+
+    /// Set the propagation model. The messages in flight keep their arrival times.
+    pub fn set_propagation_model(
+        &mut self,
+        propagation_model: PropagationModel,
+    ) -> Result<(), PropagationModelError> {
+        propagation_model.validate()?;
+        self.propagation_model = propagation_model;
+        Ok(())
+    }
+
+    /// Register an observer of the events in the simulation.
+    pub fn subscribe(&mut self, observer: Box<dyn Observer>) {
+        self.observers.push(observer);
     }
 
     /// Process to the given slot in a happy case.
@@ -81,7 +300,7 @@ impl Simulator {
         self.process_slots(slot)
     }
 
-    /// Process to the given slot. No checkpoint gets finalized.
+    /// Process to the given slot. No validator attests to the checkpoints, so no checkpoint gets justified.
     pub fn process_slots_without_beacon_chain_finality(
         &mut self,
         slot: Slot,
@@ -105,6 +324,19 @@ impl Simulator {
         self.process_slots(slot)
     }
 
+    /// Process to the given slot. The validators of the rate are offline.
+    pub fn process_slots_with_offline_validators(
+        &mut self,
+        slot: Slot,
+        offline_validator_rate: f32,
+    ) -> Result<(), SlotProcessingError> {
+        while self.params.len() <= slot as usize {
+            self.params
+                .push(SimulationParams::offline_validators(offline_validator_rate));
+        }
+        self.process_slots(slot)
+    }
+
     /// Process to the given slot. Fails randomly.
     pub fn process_slots_random(&mut self, slot: Slot) -> Result<(), SlotProcessingError> {
         while self.params.len() <= slot as usize {
@@ -113,6 +345,30 @@ impl Simulator {
         self.process_slots(slot)
     }
 
+    /// Process to the given slot. Fails by the failure model.
+    /// Note: Call `end_outages` if the outage models of the failure model are changed.
+    pub fn process_slots_with_failure_model(
+        &mut self,
+        slot: Slot,
+        failure_model: &FailureModel,
+    ) -> Result<(), SlotProcessingError> {
+        let mut rng = rand::thread_rng();
+        while self.params.len() <= slot as usize {
+            let params = failure_model.sample(
+                self.params.len() as Slot,
+                &mut self.ongoing_outages,
+                &mut rng,
+            );
+            self.params.push(params);
+        }
+        self.process_slots(slot)
+    }
+
+    /// End the ongoing outages of the failure model.
+    pub fn end_outages(&mut self) {
+        self.ongoing_outages.clear();
+    }
+
     /// Process to the given slot.
     fn process_slots(&mut self, slot: Slot) -> Result<(), SlotProcessingError> {
         if self.slot > slot {
@@ -122,7 +378,8 @@ impl Simulator {
             });
         }
         while self.slot <= slot {
-            self.process_slot();
+            self.process_slot()?;
+            self.prune_piece_data()?;
             // Move to the next slot.
             self.slot += 1;
         }
@@ -131,23 +388,179 @@ impl Simulator {
     }
 
     /// Process of a slot.
-    fn process_slot(&mut self) {
+    fn process_slot(&mut self) -> Result<(), SlotProcessingError> {
+        let mut events: Vec<Event> = Vec::new();
+        self.deliver_bids(&mut events);
+        self.deliver_headers();
+        let mut rng = rand::thread_rng();
         let params = &self.params[self.slot as usize];
         for shard in self.shards.iter_mut() {
             shard.process_slot(&params.shard_params[shard.shard_id as usize]);
+            events.append(&mut shard.events);
             // The new shard header is published on the global subnet.
             // Assumption: If a shard blob is proposed, its header is published on the global subnet.
-            if shard.proposed_headers[self.slot as usize].is_some() {
-                self.beacon_chain.publish_shard_header(
-                    shard.proposed_headers[self.slot as usize].clone().unwrap(),
-                );
+            if let Some(header) = shard.proposed_headers[self.slot as usize].clone() {
+                events.push(Event::ShardHeaderPublished {
+                    header: header.clone(),
+                });
+                match self.propagation_model.global_subnet.sample(&mut rng) {
+                    // The header arriving by the end of the slot is included in the slot's block.
+                    Some(latency_ms) if latency_ms < self.slot_clock.slot_duration_ms => {
+                        self.beacon_chain.publish_shard_header(header)
+                    }
+                    Some(latency_ms) => {
+                        let arrival_slot =
+                            self.slot + latency_ms / self.slot_clock.slot_duration_ms;
+                        self.in_flight_headers.push((arrival_slot, header));
+                    }
+                    None => (),
+                }
+            }
+        }
+        // Store the clock with the head of the slot.
+        self.beacon_chain.slot_clock = Some(self.slot_clock);
+        let result = self.beacon_chain.process_slot(&params.beacon_params);
+        events.append(&mut self.beacon_chain.events);
+        result?;
+        events.push(Event::SlotProcessed { slot: self.slot });
+        self.notify(&events);
+        Ok(())
+    }
+
+    /// Deliver the shard headers arriving in the slot to the beacon block proposers.
+    fn deliver_headers(&mut self) {
+        let slot = self.slot;
+        let (arrived, in_flight) = std::mem::take(&mut self.in_flight_headers)
+            .into_iter()
+            .partition(|(arrival_slot, _)| *arrival_slot <= slot);
+        self.in_flight_headers = in_flight;
+        for (_, header) in arrived {
+            self.beacon_chain.publish_shard_header(header);
+        }
+    }
+
+    /// Deliver the bids arriving by now to the shard blob proposers.
+    /// The bids for the processed slots miss the proposers.
+    fn deliver_bids(&mut self, events: &mut Vec<Event>) {
+        let now_ms = self.now_ms();
+        let (arrived, in_flight) = std::mem::take(&mut self.in_flight_bids)
+            .into_iter()
+            .partition(|(arrival_ms, _)| *arrival_ms <= now_ms);
+        self.in_flight_bids = in_flight;
+        for (_, bid) in arrived {
+            if bid.slot < self.slot {
+                events.push(Event::BidMissed { bid });
+            } else {
+                self.shards[bid.shard as usize].publish_bid(bid);
+            }
+        }
+    }
+
+    /// Notify the observers of the events.
+    fn notify(&mut self, events: &[Event]) {
+        for observer in self.observers.iter_mut() {
+            for event in events.iter() {
+                observer.on_event(event);
             }
         }
-        self.beacon_chain.process_slot(&params.beacon_params);
     }
 
-    /// Submit a bid.
+    /// Get the status of the shard header of the shard at the slot.
+    /// Return `None` if the shard does not exist or the slot is not processed yet.
+    pub fn get_shard_header_status(
+        &self,
+        shard: ShardId,
+        slot: Slot,
+    ) -> Result<Option<ShardHeaderStatus>, StoreError> {
+        if shard >= SHARD_NUM as ShardId || slot >= self.slot {
+            return Ok(None);
+        }
+        let header = self.shards[shard as usize].proposed_headers[slot as usize].clone();
+        let included_block = match &header {
+            Some(header) => self.beacon_chain.get_block_including_shard_header(header)?,
+            None => None,
+        };
+        let confirmed = match included_block {
+            Some(_) => self.beacon_chain.is_shard_header_confirmed(slot, shard)?,
+            None => Some(false),
+        };
+        let finalized = match (
+            &included_block,
+            self.beacon_chain.finalized_block_summaries().last(),
+        ) {
+            (Some(block), Some(finalized_block)) => block.slot <= finalized_block.slot,
+            _ => false,
+        };
+        Ok(Some(ShardHeaderStatus {
+            slot,
+            shard,
+            header,
+            included_block,
+            confirmed,
+            finalized,
+        }))
+    }
+
+    /// Submit a deposit, which is processed in the next beacon block.
+    pub fn process_deposit(&mut self, deposit: Deposit) -> Result<(), DepositError> {
+        self.beacon_chain.submit_deposit(deposit)
+    }
+
+    /// Submit a voluntary exit, which is processed in a beacon block from its epoch.
+    pub fn submit_voluntary_exit(&mut self, exit: VoluntaryExit) -> Result<(), VoluntaryExitError> {
+        self.beacon_chain.submit_voluntary_exit(exit)
+    }
+
+    /// Submit a proposer slashing, which is processed in the next beacon block.
+    pub fn submit_proposer_slashing(
+        &mut self,
+        slashing: ProposerSlashing,
+    ) -> Result<(), SlashingError> {
+        self.beacon_chain.submit_proposer_slashing(slashing)
+    }
+
+    /// Submit an attester slashing, which is processed in the next beacon block.
+    pub fn submit_attester_slashing(
+        &mut self,
+        slashing: AttesterSlashing,
+    ) -> Result<(), SlashingError> {
+        self.beacon_chain.submit_attester_slashing(slashing)
+    }
+
+    /// Let the validators equivocate at the next slot to be processed.
+    /// The proposers propose two conflicting blocks and the attesters make a double vote,
+    /// and the slashings of them are processed in the next beacon block.
+    pub fn simulate_equivocations(
+        &mut self,
+        proposer_indices: &[ValidatorIndex],
+        attester_indices: &[ValidatorIndex],
+    ) -> Result<(), SlashingError> {
+        for index in proposer_indices.iter() {
+            self.beacon_chain.simulate_proposer_equivocation(*index)?;
+        }
+        if !attester_indices.is_empty() {
+            self.beacon_chain
+                .simulate_attester_equivocation(attester_indices)?;
+        }
+        Ok(())
+    }
+
+    /// Submit a bid. The fee must be at least the current shard gasprice.
     pub fn publish_bid(&mut self, bid: Bid) -> Result<(), BidPublicationError> {
+        let now_ms = self.now_ms();
+        self.check_bid(&bid, now_ms)?;
+        self.propagate_bid(bid, now_ms);
+        Ok(())
+    }
+
+    /// Check that a bid can be published at `now_ms`.
+    fn check_bid(&self, bid: &Bid, now_ms: u64) -> Result<(), BidPublicationError> {
+        if bid.shard >= SHARD_NUM as Shard {
+            return Err(BidPublicationError::UnknownShard { found: bid.shard });
+        }
+        if bid.commitment.length == 0 {
+            return Err(BidPublicationError::EmptyData);
+        }
         if bid.commitment.length > MAX_POINTS_PER_BLOCK {
             return Err(BidPublicationError::TooLargeData {
                 found: bid.commitment.length,
@@ -159,24 +572,232 @@ impl Simulator {
                 found: bid.slot,
             });
         }
-        self.shards[bid.shard as usize].publish_bid(bid);
+        if let Some(deadline_ms) = self.bid_deadline_time_ms(bid.slot) {
+            if deadline_ms <= now_ms {
+                return Err(BidPublicationError::TooLate {
+                    slot: bid.slot,
+                    deadline_ms,
+                    now_ms,
+                });
+            }
+        }
+        let gasprice = self.beacon_chain.state.shard_gasprice;
+        if bid.fee < gasprice {
+            return Err(BidPublicationError::TooLowFee {
+                gasprice,
+                found: bid.fee,
+            });
+        }
         Ok(())
     }
 
+    /// Send a checked bid to the shard subnet.
+    fn propagate_bid(&mut self, bid: Bid, now_ms: u64) {
+        let mut rng = rand::thread_rng();
+        match self
+            .propagation_model
+            .shard_subnet(bid.shard)
+            .sample(&mut rng)
+        {
+            Some(0) => self.shards[bid.shard as usize].publish_bid(bid),
+            Some(latency_ms) => self.in_flight_bids.push((now_ms + latency_ms, bid)),
+            None => self.notify(&[Event::BidMissed { bid }]),
+        }
+    }
+
     /// Submit a bid with data.
     pub fn publish_bid_with_data(
         &mut self,
         bid: Bid,
         data: &[u8],
     ) -> Result<(), BidPublicationError> {
+        Self::check_bid_data(&bid, data)?;
+        self.publish_bid(bid)
+    }
+
+    /// Check that the commitment of a bid is the commitment of the data.
+    fn check_bid_data(bid: &Bid, data: &[u8]) -> Result<(), BidPublicationError> {
+        let length = DataCommitment::length_of_bytes(data.len());
+        if bid.commitment.length != length {
+            return Err(BidPublicationError::DataLengthMismatch {
+                expect: bid.commitment.length,
+                found: length,
+            });
+        }
         let commitment = DataCommitment::dummy_from_bytes(data);
         if bid.commitment != commitment {
             return Err(BidPublicationError::InvalidCommitment {
                 expect: commitment,
-                found: bid.commitment,
+                found: bid.commitment.clone(),
             });
         }
-        self.publish_bid(bid)
+        Ok(())
+    }
+
+    /// Split the data into pieces, and publish the bids of the pieces with the placement.
+    /// All the bids are checked before publishing any, so if a bid is rejected, no bid is published.
+    pub fn submit_data(
+        &mut self,
+        data: &[u8],
+        placement: &Placement,
+    ) -> Result<Manifest, DataSubmissionError> {
+        if data.is_empty() {
+            return Err(DataSubmissionError::EmptyData);
+        }
+        let pieces = data_submission::split_data(data);
+        let positions = placement.place(pieces.len())?;
+        let now_ms = self.now_ms();
+        let mut bids = Vec::with_capacity(pieces.len());
+        for (index, (piece, (shard, slot))) in pieces.iter().zip(positions).enumerate() {
+            let bid = Bid {
+                shard,
+                slot,
+                commitment: DataCommitment::dummy_from_bytes(piece),
+                fee: placement.fee,
+            };
+            self.check_bid(&bid, now_ms)
+                .map_err(|error| DataSubmissionError::Bid { index, error })?;
+            bids.push(bid);
+        }
+        let mut manifest = Manifest {
+            length: data.len() as u64,
+            pieces: Vec::with_capacity(bids.len()),
+        };
+        for (piece, bid) in pieces.into_iter().zip(bids) {
+            self.keep_piece(&bid, piece);
+            manifest.pieces.push(Piece {
+                shard: bid.shard,
+                slot: bid.slot,
+                commitment: bid.commitment.clone(),
+            });
+            self.propagate_bid(bid, now_ms);
+        }
+        Ok(manifest)
+    }
+
+    /// Keep the data of the piece published as the bid, evicting the pieces of the oldest slots
+    /// if the kept pieces exceed `MAX_KEPT_PIECE_BYTES`.
+    fn keep_piece(&mut self, bid: &Bid, data: &[u8]) {
+        self.piece_data_bytes += data.len();
+        self.piece_data
+            .entry(bid.slot)
+            .or_insert_with(Vec::new)
+            .push(KeptPiece {
+                shard: bid.shard,
+                commitment: bid.commitment.clone(),
+                data: data.to_vec(),
+            });
+        while self.piece_data_bytes > MAX_KEPT_PIECE_BYTES {
+            let oldest_slot = match self.piece_data.keys().next() {
+                Some(slot) => *slot,
+                None => break,
+            };
+            self.remove_pieces(oldest_slot, |_| true);
+        }
+    }
+
+    /// Remove the kept pieces at the slot that match the predicate.
+    fn remove_pieces<F: Fn(&KeptPiece) -> bool>(&mut self, slot: Slot, predicate: F) {
+        let kept = match self.piece_data.get_mut(&slot) {
+            Some(kept) => kept,
+            None => return,
+        };
+        let bytes: usize = kept.iter().map(|piece| piece.data.len()).sum();
+        kept.retain(|piece| !predicate(piece));
+        let retained_bytes: usize = kept.iter().map(|piece| piece.data.len()).sum();
+        self.piece_data_bytes -= bytes - retained_bytes;
+        if kept.is_empty() {
+            self.piece_data.remove(&slot);
+        }
+    }
+
+    /// Remove the kept pieces after processing a slot.
+    /// The pieces are removed once their slot is finalized, once a different header is proposed
+    /// at their shard and slot, and once their header is not confirmed by the end of the next epoch.
+    fn prune_piece_data(&mut self) -> Result<(), StoreError> {
+        let current_epoch = compute_epoch_at_slot(self.slot);
+        let finalized_epoch = self.beacon_chain.finalized_checkpoint.epoch;
+        let slots: Vec<Slot> = self
+            .piece_data
+            .range(..=self.slot)
+            .map(|(slot, _)| *slot)
+            .collect();
+        for slot in slots {
+            let epoch = compute_epoch_at_slot(slot);
+            // The header of the slot is included by the end of the next epoch,
+            // so the block including it is finalized once the epoch after that is finalized.
+            if epoch + 2 <= finalized_epoch {
+                self.remove_pieces(slot, |_| true);
+                continue;
+            }
+            let proposed_commitments: Vec<Option<DataCommitment>> = self
+                .shards
+                .iter()
+                .map(|shard| {
+                    shard.proposed_headers[slot as usize]
+                        .as_ref()
+                        .map(|header| header.message.commitment.clone())
+                })
+                .collect();
+            self.remove_pieces(slot, |piece| {
+                proposed_commitments[piece.shard as usize].as_ref() != Some(&piece.commitment)
+            });
+            // The confirmation is fixed at the end of the next epoch.
+            if epoch + 2 <= current_epoch {
+                let mut unconfirmed_shards = Vec::new();
+                for piece in self.piece_data.get(&slot).into_iter().flatten() {
+                    if self
+                        .beacon_chain
+                        .is_shard_header_confirmed(slot, piece.shard)?
+                        == Some(false)
+                    {
+                        unconfirmed_shards.push(piece.shard);
+                    }
+                }
+                self.remove_pieces(slot, |piece| unconfirmed_shards.contains(&piece.shard));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reassemble the data of the manifest, whose pieces must all be confirmed.
+    /// Note: The data of the pieces is kept only until their slot is finalized,
+    /// and is not kept across a restart of the simulator.
+    pub fn reassemble_data(&self, manifest: &Manifest) -> Result<Vec<u8>, DataReassemblyError> {
+        let mut data = Vec::new();
+        for (index, piece) in manifest.pieces.iter().enumerate() {
+            let confirmed = self
+                .get_shard_header_status(piece.shard, piece.slot)?
+                .map_or(false, |status| {
+                    status.confirmed == Some(true)
+                        && status.header.map(|header| header.message.commitment)
+                            == Some(piece.commitment.clone())
+                });
+            if !confirmed {
+                return Err(DataReassemblyError::NotConfirmed {
+                    index,
+                    shard: piece.shard,
+                    slot: piece.slot,
+                });
+            }
+            let piece_data = self
+                .piece_data
+                .get(&piece.slot)
+                .and_then(|kept| {
+                    kept.iter().find(|kept| {
+                        kept.shard == piece.shard && kept.commitment == piece.commitment
+                    })
+                })
+                .ok_or(DataReassemblyError::UnknownPiece { index })?;
+            data.extend_from_slice(&piece_data.data);
+        }
+        if data.len() as u64 != manifest.length {
+            return Err(DataReassemblyError::InvalidLength {
+                expect: manifest.length,
+                found: data.len() as u64,
+            });
+        }
+        Ok(data)
     }
 }
 
@@ -203,8 +824,8 @@ mod tests {
             Checkpoint::genesis_finalized_checkpoint(),
             beacon_chain.finalized_checkpoint
         );
-        assert!(beacon_chain.blocks.is_empty());
-        assert!(beacon_chain.states.is_empty());
+        assert!(beacon_chain.block_summaries().is_empty());
+        assert!(beacon_chain.states().unwrap().is_empty());
         assert!(beacon_chain.previous_epoch_shard_header_pool.is_empty());
         assert!(beacon_chain.current_epoch_shard_header_pool.is_empty());
     }
@@ -0,0 +1,74 @@
+//! Clock of the slots aligned to the wall-clock time or the virtual time.
+use crate::*;
+use serde_derive::{Deserialize, Serialize};
+
+pub const MILLIS_PER_SECOND: u64 = 1000;
+
+/// The wall-clock Unix time in milliseconds.
+pub fn unix_time_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Slot clock, where the slot at a time is `(time - genesis_time) / slot_duration`.
+/// The times are Unix times in milliseconds to support sub-second slot durations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotClock {
+    /// Unix time of the genesis in milliseconds.
+    /// It is a multiple of a second, so the genesis time in seconds is exact.
+    pub genesis_time_ms: u64,
+    pub slot_duration_ms: u64,
+}
+
+impl SlotClock {
+    /// A clock where the slot starts at the time or within the last second before it.
+    /// The genesis time is rounded down to a second.
+    pub fn starting_at(slot: Slot, time_ms: u64, slot_duration_ms: u64) -> Self {
+        assert!(slot_duration_ms > 0, "slot duration must be positive");
+        let genesis_time_ms = time_ms.saturating_sub(slot * slot_duration_ms);
+        Self {
+            genesis_time_ms: genesis_time_ms - genesis_time_ms % MILLIS_PER_SECOND,
+            slot_duration_ms,
+        }
+    }
+
+    /// Unix time of the genesis in seconds.
+    pub fn genesis_time(&self) -> u64 {
+        self.genesis_time_ms / MILLIS_PER_SECOND
+    }
+
+    /// The slot at the time. `None` before the genesis.
+    pub fn slot_at(&self, time_ms: u64) -> Option<Slot> {
+        if time_ms < self.genesis_time_ms {
+            return None;
+        }
+        Some((time_ms - self.genesis_time_ms) / self.slot_duration_ms)
+    }
+
+    /// Unix time in milliseconds when the slot starts.
+    pub fn slot_start_time_ms(&self, slot: Slot) -> u64 {
+        self.genesis_time_ms + slot * self.slot_duration_ms
+    }
+}
+
+/// Phase within a slot of the clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlotPhase {
+    /// The bids for the next slot are accepted.
+    BidWindow,
+    /// The bid window for the next slot is closed until the next slot starts.
+    BidsClosed,
+}
+
+impl Default for SlotClock {
+    /// The clock with the genesis at the Unix epoch and `SECONDS_PER_SLOT`.
+    fn default() -> Self {
+        Self {
+            genesis_time_ms: 0,
+            slot_duration_ms: SECONDS_PER_SLOT * MILLIS_PER_SECOND,
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use crate::*;
+pub use errors::StoreError;
+use serde_derive::{Deserialize, Serialize};
+use slot_clock::SlotClock;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// The beacon chain at the end of the last processed slot, which is needed to resume the processing.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChainHead {
+    /// The next slot to be processed.
+    pub slot: Slot,
+    /// The beacon state after the epoch processing of the last processed slot.
+    pub state: BeaconState,
+    pub justified_checkpoint: Checkpoint,
+    pub finalized_checkpoint: Checkpoint,
+    pub previous_epoch_target_attesters: Vec<ValidatorIndex>,
+    pub current_epoch_target_attesters: Vec<ValidatorIndex>,
+    pub inactivity_penalty_quotient: u64,
+    /// The slot clock of the simulator, which keeps the genesis time across restarts.
+    /// `None` in the heads stored by the older versions.
+    #[serde(default)]
+    pub slot_clock: Option<SlotClock>,
+}
+
+/// The slot and the roots of a stored block, which are kept in memory to look up the blocks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockSummary {
+    pub slot: Slot,
+    pub root: Root,
+    pub state_root: Root,
+}
+
+impl BlockSummary {
+    pub fn new(block: &BeaconBlock) -> Self {
+        Self {
+            slot: block.slot,
+            root: block.header().root(),
+            state_root: block.state_root,
+        }
+    }
+}
+
+/// Storage backend of the beacon chain.
+/// Blocks are stored in ascending order by slot, and a beacon state is stored for each block.
+/// The head is stored at the end of every slot, and it is the commit point of the slot:
+/// the blocks at or after the next slot of the head are discarded when the store is reopened.
+/// Note: The summaries of the blocks are always kept in memory to walk the main chain.
+/// The blocks themselves are loaded by slot, and the states can be pruned.
+pub trait Store: Send {
+    /// Store a new block of the main chain and the beacon state at the block.
+    fn put_block(&mut self, block: BeaconBlock, state: &BeaconState) -> Result<(), StoreError>;
+
+    /// Store the head at the end of a slot.
+    fn put_head(&mut self, head: ChainHead) -> Result<(), StoreError>;
+
+    /// Get the head of the last processed slot. `None` if no slot is processed.
+    fn head(&self) -> Option<&ChainHead>;
+
+    /// Get the summaries of the stored blocks in ascending order by slot.
+    fn block_summaries(&self) -> &[BlockSummary];
+
+    /// Get the stored block at the slot. Return `None` if there is no block at the slot.
+    fn get_block(&self, slot: Slot) -> Result<Option<BeaconBlock>, StoreError>;
+
+    /// Get the beacon state at the block of the slot.
+    /// Return `None` if there is no block at the slot or the state is already pruned.
+    fn get_state(&self, slot: Slot) -> Result<Option<BeaconState>, StoreError>;
+
+    /// Delete the states at the blocks before the slot.
+    fn prune_states(&mut self, slot: Slot) -> Result<(), StoreError>;
+
+    /// Delete all the blocks, states and the head.
+    fn clear(&mut self) -> Result<(), StoreError>;
+}
+
+/// In-memory store.
+/// Everything is lost when the process exits.
+#[derive(Default)]
+pub struct MemoryStore {
+    blocks: Vec<BeaconBlock>,
+    summaries: Vec<BlockSummary>,
+    states: BTreeMap<Slot, BeaconState>,
+    head: Option<ChainHead>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Store for MemoryStore {
+    fn put_block(&mut self, block: BeaconBlock, state: &BeaconState) -> Result<(), StoreError> {
+        self.states.insert(block.slot, state.clone());
+        self.summaries.push(BlockSummary::new(&block));
+        self.blocks.push(block);
+        Ok(())
+    }
+
+    fn put_head(&mut self, head: ChainHead) -> Result<(), StoreError> {
+        self.head = Some(head);
+        Ok(())
+    }
+
+    fn head(&self) -> Option<&ChainHead> {
+        self.head.as_ref()
+    }
+
+    fn block_summaries(&self) -> &[BlockSummary] {
+        &self.summaries
+    }
+
+    fn get_block(&self, slot: Slot) -> Result<Option<BeaconBlock>, StoreError> {
+        Ok(find_block(&self.summaries, slot).map(|index| self.blocks[index].clone()))
+    }
+
+    fn get_state(&self, slot: Slot) -> Result<Option<BeaconState>, StoreError> {
+        Ok(self.states.get(&slot).cloned())
+    }
+
+    fn prune_states(&mut self, slot: Slot) -> Result<(), StoreError> {
+        self.states = self.states.split_off(&slot);
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), StoreError> {
+        self.blocks.clear();
+        self.summaries.clear();
+        self.states.clear();
+        self.head = None;
+        Ok(())
+    }
+}
+
+/// The index of the block at the slot in the summaries.
+fn find_block(summaries: &[BlockSummary], slot: Slot) -> Option<usize> {
+    summaries
+        .binary_search_by_key(&slot, |summary| summary.slot)
+        .ok()
+}
+
+const BLOCKS_FILE: &str = "blocks.jsonl";
+const STATES_DIR: &str = "states";
+const HEAD_FILE: &str = "head.json";
+
+/// The number of the latest blocks cached in memory by `DiskStore`.
+const RECENT_BLOCKS: usize = 64;
+
+/// On-disk store in a data directory.
+/// Blocks are appended to `blocks.jsonl` as JSON lines,
+/// each state is written to `states/{slot}.json`, and the head to `head.json`.
+/// Only the summaries and the offsets of the blocks in the file and the latest blocks are kept
+/// in memory, and the older blocks are read from the file on demand.
+pub struct DiskStore {
+    dir: PathBuf,
+    summaries: Vec<BlockSummary>,
+    // The offset of the line of each block in the file.
+    offsets: Vec<u64>,
+    // The length of the committed lines in the file.
+    blocks_len: u64,
+    // Cache of the latest blocks, up to `RECENT_BLOCKS`.
+    recent_blocks: VecDeque<BeaconBlock>,
+    // Cache of the head in the file.
+    head: Option<ChainHead>,
+    // The states before this slot are already pruned.
+    pruned_slot: Slot,
+}
+
+impl DiskStore {
+    /// Open the store in the directory. The directory is created if it does not exist.
+    /// The stored blocks are scanned to index them, and the blocks after the head are discarded.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, StoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(dir.join(STATES_DIR))?;
+        let head: Option<ChainHead> = match fs::read(dir.join(HEAD_FILE)) {
+            Ok(bytes) => Some(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let next_slot = head.as_ref().map_or(GENESIS_SLOT, |head| head.slot);
+        let mut store = Self {
+            dir,
+            summaries: Vec::new(),
+            offsets: Vec::new(),
+            blocks_len: 0,
+            recent_blocks: VecDeque::new(),
+            head,
+            pruned_slot: GENESIS_SLOT,
+        };
+        let blocks_path = store.dir.join(BLOCKS_FILE);
+        let file = match File::open(&blocks_path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(store),
+            Err(e) => return Err(e.into()),
+        };
+        let file_len = file.metadata()?.len();
+        // Note: `read_line()` keeps the line break, which tells an incomplete line.
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            // Note: The last line without the line break is an incomplete write.
+            if !line.ends_with('\n') {
+                break;
+            }
+            let block: BeaconBlock = serde_json::from_str(&line)?;
+            // The block is written in a slot whose processing was interrupted.
+            if block.slot >= next_slot {
+                if store.head.is_none() {
+                    // Keep the blocks since they can be written by a different version.
+                    return Err(StoreError::BrokenData(String::from(
+                        "blocks are stored without the head",
+                    )));
+                }
+                break;
+            }
+            let offset = store.blocks_len;
+            store.blocks_len += line.len() as u64;
+            store.index_block(block, offset);
+        }
+        // Truncate the uncommitted writes so that the next block is appended to a new line.
+        if store.blocks_len < file_len {
+            OpenOptions::new()
+                .write(true)
+                .open(&blocks_path)?
+                .set_len(store.blocks_len)?;
+        }
+        Ok(store)
+    }
+
+    /// Add a block written at the offset of the file to the index and the cache.
+    fn index_block(&mut self, block: BeaconBlock, offset: u64) {
+        self.summaries.push(BlockSummary::new(&block));
+        self.offsets.push(offset);
+        if self.recent_blocks.len() == RECENT_BLOCKS {
+            self.recent_blocks.pop_front();
+        }
+        self.recent_blocks.push_back(block);
+    }
+
+    /// Read the block at the index from the file.
+    fn read_block(&self, index: usize) -> Result<BeaconBlock, StoreError> {
+        let mut file = File::open(self.dir.join(BLOCKS_FILE))?;
+        file.seek(SeekFrom::Start(self.offsets[index]))?;
+        let mut line = String::new();
+        BufReader::new(file).read_line(&mut line)?;
+        Ok(serde_json::from_str(&line)?)
+    }
+
+    fn state_path(&self, slot: Slot) -> PathBuf {
+        self.dir.join(STATES_DIR).join(format!("{}.json", slot))
+    }
+}
+
+impl Store for DiskStore {
+    fn put_block(&mut self, block: BeaconBlock, state: &BeaconState) -> Result<(), StoreError> {
+        // Write the state first so that a stored block always has its state.
+        let path = self.state_path(block.slot);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec(state)?)?;
+        fs::rename(&tmp_path, &path)?;
+
+        let mut line = serde_json::to_string(&block)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(BLOCKS_FILE))?
+            .write_all(line.as_bytes())?;
+        let offset = self.blocks_len;
+        self.blocks_len += line.len() as u64;
+        self.index_block(block, offset);
+        Ok(())
+    }
+
+    fn put_head(&mut self, head: ChainHead) -> Result<(), StoreError> {
+        let path = self.dir.join(HEAD_FILE);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec(&head)?)?;
+        fs::rename(&tmp_path, &path)?;
+        self.head = Some(head);
+        Ok(())
+    }
+
+    fn head(&self) -> Option<&ChainHead> {
+        self.head.as_ref()
+    }
+
+    fn block_summaries(&self) -> &[BlockSummary] {
+        &self.summaries
+    }
+
+    fn get_block(&self, slot: Slot) -> Result<Option<BeaconBlock>, StoreError> {
+        let index = match find_block(&self.summaries, slot) {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let first_recent = self.summaries.len() - self.recent_blocks.len();
+        if index >= first_recent {
+            return Ok(Some(self.recent_blocks[index - first_recent].clone()));
+        }
+        self.read_block(index).map(Some)
+    }
+
+    fn get_state(&self, slot: Slot) -> Result<Option<BeaconState>, StoreError> {
+        match fs::read(self.state_path(slot)) {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn prune_states(&mut self, slot: Slot) -> Result<(), StoreError> {
+        for summary in self.summaries.iter() {
+            if summary.slot < self.pruned_slot {
+                continue;
+            }
+            if summary.slot >= slot {
+                break;
+            }
+            match fs::remove_file(self.state_path(summary.slot)) {
+                Ok(_) => (),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        self.pruned_slot = std::cmp::max(self.pruned_slot, slot);
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<(), StoreError> {
+        // Delete only the files of the store, since the directory can have other files.
+        for file in [HEAD_FILE, BLOCKS_FILE].iter() {
+            match fs::remove_file(self.dir.join(file)) {
+                Ok(_) => (),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        match fs::remove_dir_all(self.dir.join(STATES_DIR)) {
+            Ok(_) => (),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e.into()),
+        }
+        fs::create_dir_all(self.dir.join(STATES_DIR))?;
+        self.summaries.clear();
+        self.offsets.clear();
+        self.blocks_len = 0;
+        self.recent_blocks.clear();
+        self.head = None;
+        self.pruned_slot = GENESIS_SLOT;
+        Ok(())
+    }
+}
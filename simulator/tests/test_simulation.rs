@@ -1,6 +1,13 @@
+use data_submission::*;
 use errors::*;
 use eth2_simulator::*;
-use simulator::Simulator;
+use failure_model::*;
+use propagation_model::*;
+use simulator::{Event, Simulator};
+use slot_clock::{SlotClock, SlotPhase};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use store::{DiskStore, Store};
 
 #[test]
 fn process_slots_happy() {
@@ -17,27 +24,33 @@ fn process_slots_happy() {
     // Next slot to be processed.
     let beacon_chain = simulator.beacon_chain;
     assert_eq!(end_slot + 1, beacon_chain.slot);
+    let states = beacon_chain.states().unwrap();
 
     // Finality
     let finalized_epoch = compute_epoch_at_slot(end_slot) - 2;
     let finalized_slot = compute_start_slot_at_epoch(finalized_epoch);
     assert_eq!(finalized_epoch, beacon_chain.finalized_checkpoint.epoch);
     assert_eq!(
-        beacon_chain.blocks[finalized_slot as usize].header().root(),
+        beacon_chain.blocks().unwrap()[finalized_slot as usize]
+            .header()
+            .root(),
         beacon_chain.finalized_checkpoint.root
     );
     assert_eq!(
         finalized_slot,
-        beacon_chain.get_finalized_blocks().last().unwrap().slot
+        beacon_chain
+            .finalized_block_summaries()
+            .last()
+            .unwrap()
+            .slot
     );
     assert_eq!(
         beacon_chain.finalized_checkpoint.root,
         beacon_chain
-            .get_finalized_blocks()
+            .finalized_block_summaries()
             .last()
             .unwrap()
-            .header()
-            .root()
+            .root
     );
 
     for processed_slot in 0..end_slot + 1 {
@@ -45,20 +58,22 @@ fn process_slots_happy() {
         // Beacon blocks are proposed at every slot.
         assert_eq!(
             processed_slot,
-            beacon_chain.blocks[processed_slot as usize].slot
+            beacon_chain.blocks().unwrap()[processed_slot as usize].slot
         );
         if processed_slot < end_slot - 1 {
             // Verify the hash chain.
             assert_eq!(
-                beacon_chain.blocks[processed_slot as usize].header().root(),
-                beacon_chain.blocks[processed_slot as usize + 1].parent_root
+                beacon_chain.blocks().unwrap()[processed_slot as usize]
+                    .header()
+                    .root(),
+                beacon_chain.blocks().unwrap()[processed_slot as usize + 1].parent_root
             );
         }
 
         // Shard header existence.
         let shard_set = (0..SHARD_NUM as Shard).collect::<HashSet<Shard>>();
         let mut proposed_shard_set: HashSet<Shard> = HashSet::new();
-        for shard_header in beacon_chain.blocks[processed_slot as usize]
+        for shard_header in beacon_chain.blocks().unwrap()[processed_slot as usize]
             .shard_headers
             .iter()
         {
@@ -68,7 +83,7 @@ fn process_slots_happy() {
         assert_eq!(shard_set, proposed_shard_set);
 
         // Shard header confirmation.
-        for pending_shard_header in beacon_chain.states[processed_slot as usize]
+        for pending_shard_header in states[processed_slot as usize]
             .current_epoch_pending_shard_headers
             .iter()
         {
@@ -76,30 +91,29 @@ fn process_slots_happy() {
         }
 
         // Beacon state are proposed at every slot.
-        assert_eq!(
-            processed_slot,
-            beacon_chain.states[processed_slot as usize].slot
-        );
+        assert_eq!(processed_slot, states[processed_slot as usize].slot);
 
-        // The grandparent epoch's checkpoint is always assumed to be finalized.
-        // The next beacon block learns the finalized checkpoint in the state.
-        if compute_epoch_at_slot(processed_slot) < 2 {
+        // The parent epoch's checkpoint is finalized in the state at the end of the epoch,
+        // so the state of the next slot has the grandparent epoch's checkpoint as finalized.
+        if compute_epoch_at_slot(processed_slot + 1) < 2 {
             assert_eq!(
                 Checkpoint::genesis_finalized_checkpoint(),
-                beacon_chain.states[processed_slot as usize + 1].finalized_checkpoint
+                states[processed_slot as usize + 1].finalized_checkpoint
             );
         } else if processed_slot < end_slot - 1 {
-            let finalized_epoch = compute_epoch_at_slot(processed_slot) - 2;
+            let finalized_epoch = compute_epoch_at_slot(processed_slot + 1) - 2;
             let finalized_slot = compute_start_slot_at_epoch(finalized_epoch);
             assert_eq!(
                 finalized_epoch,
-                beacon_chain.states[processed_slot as usize + 1]
+                states[processed_slot as usize + 1]
                     .finalized_checkpoint
                     .epoch
             );
             assert_eq!(
-                beacon_chain.blocks[finalized_slot as usize].header().root(),
-                beacon_chain.states[processed_slot as usize + 1]
+                beacon_chain.blocks().unwrap()[finalized_slot as usize]
+                    .header()
+                    .root(),
+                states[processed_slot as usize + 1]
                     .finalized_checkpoint
                     .root
             );
@@ -141,7 +155,7 @@ fn process_slots_with_bids() {
                         )
                         .into_bytes(),
                     ),
-                    fee: 1,
+                    fee: MIN_GASPRICE,
                 };
                 let high_fee_bid = Bid {
                     shard,
@@ -197,8 +211,8 @@ fn process_slots_with_bids() {
             // All the bid with the highest fee is included and confirmed in the beacon chain.
             let confirmed_bid_ids: HashSet<(Shard, Slot, DataCommitment)> = simulator
                 .beacon_chain
-                .states
-                .last()
+                .get_head_state()
+                .unwrap()
                 .unwrap()
                 .current_epoch_pending_shard_headers
                 .iter()
@@ -215,8 +229,8 @@ fn process_slots_with_bids() {
             // No bid with lower fee is included in the beacon chain.
             let included_bid_ids: HashSet<(Shard, Slot, DataCommitment)> = simulator
                 .beacon_chain
-                .states
-                .last()
+                .get_head_state()
+                .unwrap()
                 .unwrap()
                 .current_epoch_pending_shard_headers
                 .iter()
@@ -266,7 +280,7 @@ fn process_slots_without_shard_data_inclusion() {
                 commitment: DataCommitment::dummy_from_bytes(
                     &format!("Slot {}, Shard {}", processed_slot, shard).into_bytes(),
                 ),
-                fee: 1,
+                fee: MIN_GASPRICE,
             };
             commitments.insert(bid.commitment.clone());
             let result = simulator.publish_bid(bid);
@@ -278,8 +292,8 @@ fn process_slots_without_shard_data_inclusion() {
             result = simulator.process_slots_without_shard_data_inclusion(processed_slot);
             let included_commitments: HashSet<DataCommitment> = simulator
                 .beacon_chain
-                .blocks
-                .last()
+                .get_head_block()
+                .unwrap()
                 .unwrap()
                 .shard_headers
                 .iter()
@@ -295,8 +309,8 @@ fn process_slots_without_shard_data_inclusion() {
             result = simulator.process_slots_happy(processed_slot);
             let included_commitments: HashSet<DataCommitment> = simulator
                 .beacon_chain
-                .blocks
-                .last()
+                .get_head_block()
+                .unwrap()
                 .unwrap()
                 .shard_headers
                 .iter()
@@ -324,7 +338,7 @@ fn publish_bids_without_shard_blob_proposal() {
                 commitment: DataCommitment::dummy_from_bytes(
                     &format!("Slot {}, Shard {}", processed_slot, shard).into_bytes(),
                 ),
-                fee: 1,
+                fee: MIN_GASPRICE,
             };
             commitments.insert(bid.commitment.clone());
             let result = simulator.publish_bid(bid);
@@ -339,8 +353,8 @@ fn publish_bids_without_shard_blob_proposal() {
             }
             assert!(simulator
                 .beacon_chain
-                .blocks
-                .last()
+                .get_head_block()
+                .unwrap()
                 .unwrap()
                 .shard_headers
                 .is_empty());
@@ -351,8 +365,8 @@ fn publish_bids_without_shard_blob_proposal() {
             }
             let included_commitments: HashSet<DataCommitment> = simulator
                 .beacon_chain
-                .blocks
-                .last()
+                .get_head_block()
+                .unwrap()
                 .unwrap()
                 .shard_headers
                 .iter()
@@ -389,8 +403,8 @@ fn grandparent_epoch_header_not_included() {
                 SHARD_NUM as usize,
                 simulator
                     .beacon_chain
-                    .blocks
-                    .last()
+                    .get_head_block()
+                    .unwrap()
                     .unwrap()
                     .shard_headers
                     .len()
@@ -400,8 +414,8 @@ fn grandparent_epoch_header_not_included() {
             result = simulator.process_slots_without_shard_header_inclusion(processed_slot);
             assert!(simulator
                 .beacon_chain
-                .blocks
-                .last()
+                .get_head_block()
+                .unwrap()
                 .unwrap()
                 .shard_headers
                 .is_empty());
@@ -411,8 +425,8 @@ fn grandparent_epoch_header_not_included() {
             assert!(
                 simulator
                     .beacon_chain
-                    .blocks
-                    .last()
+                    .get_head_block()
+                    .unwrap()
                     .unwrap()
                     .shard_headers
                     .len()
@@ -424,8 +438,8 @@ fn grandparent_epoch_header_not_included() {
         // Only the shard headers from the previous or current epoch can be included.
         for signed_header in simulator
             .beacon_chain
-            .blocks
-            .last()
+            .get_head_block()
+            .unwrap()
             .unwrap()
             .shard_headers
             .iter()
@@ -437,7 +451,7 @@ fn grandparent_epoch_header_not_included() {
         }
     }
     // Invariants about pending shard headers.
-    for state in simulator.beacon_chain.states.iter() {
+    for state in simulator.beacon_chain.states().unwrap().iter() {
         for header in state.previous_epoch_pending_shard_headers.iter() {
             assert_eq!(
                 compute_epoch_at_slot(state.slot),
@@ -465,8 +479,8 @@ fn process_slots_without_shard_header_inclusion() {
             result = simulator.process_slots_without_shard_header_inclusion(processed_slot);
             assert!(simulator
                 .beacon_chain
-                .blocks
-                .last()
+                .get_head_block()
+                .unwrap()
                 .unwrap()
                 .shard_headers
                 .is_empty());
@@ -476,8 +490,8 @@ fn process_slots_without_shard_header_inclusion() {
                 SHARD_NUM as usize * 2,
                 simulator
                     .beacon_chain
-                    .blocks
-                    .last()
+                    .get_head_block()
+                    .unwrap()
                     .unwrap()
                     .shard_headers
                     .len()
@@ -501,8 +515,8 @@ fn process_slots_without_shard_header_confirmation() {
                 SHARD_NUM as usize,
                 simulator
                     .beacon_chain
-                    .states
-                    .last()
+                    .get_head_state()
+                    .unwrap()
                     .unwrap()
                     .current_epoch_pending_shard_headers
                     .iter()
@@ -515,8 +529,8 @@ fn process_slots_without_shard_header_confirmation() {
                 0,
                 simulator
                     .beacon_chain
-                    .states
-                    .last()
+                    .get_head_state()
+                    .unwrap()
                     .unwrap()
                     .current_epoch_pending_shard_headers
                     .iter()
@@ -531,26 +545,421 @@ fn process_slots_without_shard_header_confirmation() {
 #[test]
 fn process_slots_without_beacon_chain_finality() {
     let mut simulator = Simulator::new();
+    let start_slot = compute_start_slot_at_epoch(3);
     let end_slot = compute_start_slot_at_epoch(15);
+    let result = simulator.process_slots_happy(start_slot - 1);
+    assert!(result.is_ok());
 
-    for processed_slot in 0..end_slot + 1 {
+    for processed_slot in start_slot..end_slot + 1 {
         println!("Check the result of Slot {}", processed_slot);
         let result: Result<(), SlotProcessingError>;
         let epoch = compute_epoch_at_slot(processed_slot);
-        if epoch < 2 {
-            continue;
-        }
-        if epoch % 3 == 0 {
+        // Whether or not 2/3 of the validators have attested to the checkpoint of the epoch so far.
+        let attested_slots = processed_slot % SLOTS_PER_EPOCH + 1;
+        let supermajority = attested_slots * 3 >= SLOTS_PER_EPOCH * 2;
+        // The checkpoint of every third epoch is not justified.
+        // The finality catches up when the checkpoints of two consecutive epochs are justified.
+        let (justified_epoch, finalized_epoch) = if epoch % 3 == 0 {
             result = simulator.process_slots_without_beacon_chain_finality(processed_slot);
-            assert_eq!(epoch - 3, simulator.beacon_chain.finalized_checkpoint.epoch);
+            (epoch - 1, epoch - 2)
         } else {
             result = simulator.process_slots_happy(processed_slot);
-            assert_eq!(epoch - 2, simulator.beacon_chain.finalized_checkpoint.epoch);
+            match (epoch % 3, supermajority) {
+                (1, false) => (epoch - 2, epoch - 3),
+                (1, true) => (epoch, epoch - 3),
+                (_, false) => (epoch - 1, epoch - 4),
+                (_, true) => (epoch, epoch - 1),
+            }
         };
         assert!(result.is_ok());
+        assert_eq!(
+            justified_epoch,
+            simulator.beacon_chain.justified_checkpoint.epoch
+        );
+        assert_eq!(
+            finalized_epoch,
+            simulator.beacon_chain.finalized_checkpoint.epoch
+        );
     }
 }
 
+#[test]
+fn process_slots_with_offline_validators() {
+    let mut simulator = Simulator::new();
+    // 2/3 of the active stake is online.
+    simulator
+        .process_slots_with_offline_validators(compute_start_slot_at_epoch(4), 0.33)
+        .unwrap();
+    assert_eq!(2, simulator.beacon_chain.finalized_checkpoint.epoch);
+
+    // Finality stalls while more than 1/3 of the active stake is offline.
+    simulator
+        .process_slots_with_offline_validators(compute_start_slot_at_epoch(8), 0.35)
+        .unwrap();
+    assert_eq!(2, simulator.beacon_chain.finalized_checkpoint.epoch);
+    let (online_balance, total_balance) = simulator.beacon_chain.get_participation(0.35);
+    assert!(online_balance * 3 < total_balance * 2);
+
+    // Finality resumes when the checkpoints of two consecutive epochs are justified.
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(9))
+        .unwrap();
+    assert_eq!(8, simulator.beacon_chain.justified_checkpoint.epoch);
+    assert_eq!(2, simulator.beacon_chain.finalized_checkpoint.epoch);
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(10))
+        .unwrap();
+    assert_eq!(8, simulator.beacon_chain.finalized_checkpoint.epoch);
+}
+
+#[test]
+fn inactivity_leak() {
+    let mut simulator = Simulator::new();
+    // Speed up the inactivity leak.
+    simulator.beacon_chain.inactivity_penalty_quotient = 1024;
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(4) - 1)
+        .unwrap();
+
+    // The leak starts when the finality delay exceeds `MIN_EPOCHS_TO_INACTIVITY_PENALTY`.
+    simulator
+        .process_slots_with_offline_validators(compute_start_slot_at_epoch(7), 0.35)
+        .unwrap();
+    let state = &simulator.beacon_chain.state;
+    assert_eq!(MIN_EPOCHS_TO_INACTIVITY_PENALTY, state.get_finality_delay());
+    assert!(!state.is_in_inactivity_leak());
+    assert!(state
+        .balances
+        .iter()
+        .all(|balance| *balance == MAX_EFFECTIVE_BALANCE));
+
+    // Only the offline validators are penalized.
+    simulator
+        .process_slots_with_offline_validators(compute_start_slot_at_epoch(9), 0.35)
+        .unwrap();
+    let state = &simulator.beacon_chain.state;
+    assert_eq!(6, state.get_finality_delay());
+    assert!(state.is_in_inactivity_leak());
+    assert_eq!(31_843_750_000, state.balances[0]);
+    assert_eq!(MAX_EFFECTIVE_BALANCE, state.balances[127]);
+
+    // Finality resumes when the online validators regain 2/3 of the active stake.
+    simulator
+        .process_slots_with_offline_validators(compute_start_slot_at_epoch(18), 0.35)
+        .unwrap();
+    assert_eq!(2, simulator.beacon_chain.state.finalized_checkpoint.epoch);
+    assert!(simulator.beacon_chain.state.is_in_inactivity_leak());
+    simulator
+        .process_slots_with_offline_validators(compute_start_slot_at_epoch(19), 0.35)
+        .unwrap();
+    assert_eq!(17, simulator.beacon_chain.state.finalized_checkpoint.epoch);
+    assert!(!simulator.beacon_chain.state.is_in_inactivity_leak());
+    simulator
+        .process_slots_with_offline_validators(compute_start_slot_at_epoch(20), 0.35)
+        .unwrap();
+    let state = &simulator.beacon_chain.state;
+    assert_eq!(18, state.finalized_checkpoint.epoch);
+    assert!(!state.is_in_inactivity_leak());
+    assert_eq!(29_000_000_000, state.validators[0].effective_balance);
+    let (online_balance, total_balance) = simulator.beacon_chain.get_participation(0.35);
+    assert!(online_balance * 3 >= total_balance * 2);
+}
+
+#[test]
+fn process_deposits() {
+    let mut simulator = Simulator::new();
+    simulator.process_slots_happy(0).unwrap();
+    let pubkey = Validator::dummy_pubkey(GENESIS_VALIDATOR_COUNT);
+    assert_eq!(
+        Err(DepositError::TooSmallAmount {
+            found: MIN_DEPOSIT_AMOUNT - 1
+        }),
+        simulator.process_deposit(Deposit {
+            pubkey,
+            amount: MIN_DEPOSIT_AMOUNT - 1,
+        })
+    );
+    simulator
+        .process_deposit(Deposit {
+            pubkey,
+            amount: MAX_EFFECTIVE_BALANCE,
+        })
+        .unwrap();
+    // Top up the existing validator.
+    simulator
+        .process_deposit(Deposit {
+            pubkey: Validator::dummy_pubkey(0),
+            amount: MIN_DEPOSIT_AMOUNT,
+        })
+        .unwrap();
+    simulator.process_slots_happy(1).unwrap();
+
+    let block = simulator.beacon_chain.get_head_block().unwrap().unwrap();
+    assert_eq!(2, block.deposits.len());
+    let state = &simulator.beacon_chain.state;
+    let index = GENESIS_VALIDATOR_COUNT as usize;
+    assert_eq!(index + 1, state.validators.len());
+    assert_eq!(
+        Some(index as ValidatorIndex),
+        state.get_validator_index(&pubkey)
+    );
+    assert_eq!(
+        ValidatorStatus::PendingInitialized,
+        state.validators[index].status(GENESIS_EPOCH)
+    );
+    assert_eq!(
+        MAX_EFFECTIVE_BALANCE + MIN_DEPOSIT_AMOUNT,
+        state.balances[0]
+    );
+    assert_eq!(MAX_EFFECTIVE_BALANCE, state.validators[0].effective_balance);
+
+    // The validator is eligible for activation from the next epoch,
+    // and activated after the eligibility epoch gets finalized.
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(2))
+        .unwrap();
+    let validator = &simulator.beacon_chain.state.validators[index];
+    assert_eq!(1, validator.activation_eligibility_epoch);
+    assert_eq!(ValidatorStatus::PendingQueued, validator.status(2));
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(3))
+        .unwrap();
+    let activation_epoch = compute_activation_exit_epoch(2);
+    let validator = &simulator.beacon_chain.state.validators[index];
+    assert_eq!(activation_epoch, validator.activation_epoch);
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(activation_epoch))
+        .unwrap();
+    let state = &simulator.beacon_chain.state;
+    assert_eq!(
+        ValidatorStatus::ActiveOngoing,
+        state.validators[index].status(activation_epoch)
+    );
+    assert_eq!(
+        GENESIS_VALIDATOR_COUNT as usize + 1,
+        state.get_active_validator_indices(activation_epoch).len()
+    );
+}
+
+#[test]
+fn process_voluntary_exits() {
+    let mut simulator = Simulator::new();
+    simulator.process_slots_happy(0).unwrap();
+    for validator_index in 0..MIN_PER_EPOCH_CHURN_LIMIT + 1 {
+        simulator
+            .submit_voluntary_exit(VoluntaryExit {
+                epoch: GENESIS_EPOCH,
+                validator_index,
+            })
+            .unwrap();
+    }
+    // An exit from the next epoch.
+    let late_index = MIN_PER_EPOCH_CHURN_LIMIT + 1;
+    simulator
+        .submit_voluntary_exit(VoluntaryExit {
+            epoch: 1,
+            validator_index: late_index,
+        })
+        .unwrap();
+    assert_eq!(
+        Err(VoluntaryExitError::UnknownValidator {
+            found: GENESIS_VALIDATOR_COUNT
+        }),
+        simulator.submit_voluntary_exit(VoluntaryExit {
+            epoch: GENESIS_EPOCH,
+            validator_index: GENESIS_VALIDATOR_COUNT,
+        })
+    );
+    simulator.process_slots_happy(1).unwrap();
+
+    let block = simulator.beacon_chain.get_head_block().unwrap().unwrap();
+    assert_eq!(
+        MIN_PER_EPOCH_CHURN_LIMIT as usize + 1,
+        block.voluntary_exits.len()
+    );
+    let exit_epoch = compute_activation_exit_epoch(GENESIS_EPOCH);
+    let state = &simulator.beacon_chain.state;
+    for index in 0..MIN_PER_EPOCH_CHURN_LIMIT as usize {
+        assert_eq!(exit_epoch, state.validators[index].exit_epoch);
+    }
+    // The exit queue is limited by the churn limit.
+    let validator = &state.validators[MIN_PER_EPOCH_CHURN_LIMIT as usize];
+    assert_eq!(exit_epoch + 1, validator.exit_epoch);
+    assert_eq!(
+        exit_epoch + 1 + MIN_VALIDATOR_WITHDRAWABILITY_DELAY,
+        validator.withdrawable_epoch
+    );
+    assert_eq!(
+        ValidatorStatus::ActiveExiting,
+        validator.status(GENESIS_EPOCH)
+    );
+    assert_eq!(
+        FAR_FUTURE_EPOCH,
+        state.validators[late_index as usize].exit_epoch
+    );
+    assert_eq!(
+        Err(VoluntaryExitError::AlreadyExiting {
+            index: 0,
+            exit_epoch
+        }),
+        simulator.submit_voluntary_exit(VoluntaryExit {
+            epoch: GENESIS_EPOCH,
+            validator_index: 0,
+        })
+    );
+
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(exit_epoch + 1))
+        .unwrap();
+    let state = &simulator.beacon_chain.state;
+    assert_eq!(
+        exit_epoch + 1,
+        state.validators[late_index as usize].exit_epoch
+    );
+    assert_eq!(
+        ValidatorStatus::ExitedUnslashed,
+        state.validators[0].status(exit_epoch + 1)
+    );
+    assert_eq!(
+        GENESIS_VALIDATOR_COUNT as usize - MIN_PER_EPOCH_CHURN_LIMIT as usize - 2,
+        state.get_active_validator_indices(exit_epoch + 1).len()
+    );
+}
+
+#[test]
+fn process_slashings() {
+    let mut simulator = Simulator::new();
+    let events: Arc<Mutex<Vec<Event>>> = Arc::new(Mutex::new(Vec::new()));
+    let observed_events = events.clone();
+    simulator.subscribe(Box::new(move |event: &Event| {
+        observed_events.lock().unwrap().push(event.clone())
+    }));
+    let epoch = 1;
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(epoch) - 1)
+        .unwrap();
+
+    let header = simulator
+        .beacon_chain
+        .get_head_block()
+        .unwrap()
+        .unwrap()
+        .header();
+    assert_eq!(
+        Err(SlashingError::IdenticalHeaders),
+        simulator.submit_proposer_slashing(ProposerSlashing {
+            proposer_index: 0,
+            header_1: header.clone(),
+            header_2: header.clone(),
+        })
+    );
+    assert_eq!(
+        Err(SlashingError::DifferentSlots {
+            slot_1: header.slot,
+            slot_2: header.slot + 1,
+        }),
+        simulator.submit_proposer_slashing(ProposerSlashing {
+            proposer_index: 0,
+            header_1: header.clone(),
+            header_2: BeaconBlockHeader {
+                slot: header.slot + 1,
+                ..header
+            },
+        })
+    );
+    assert_eq!(
+        Err(SlashingError::UnknownValidator {
+            found: GENESIS_VALIDATOR_COUNT
+        }),
+        simulator.simulate_equivocations(&[GENESIS_VALIDATOR_COUNT], &[])
+    );
+    let attestation = IndexedAttestation {
+        attesting_indices: vec![0, 1],
+        data: AttestationData {
+            slot: 0,
+            beacon_block_root: H256::zero(),
+            source: Checkpoint::genesis_finalized_checkpoint(),
+            target: Checkpoint::genesis_finalized_checkpoint(),
+        },
+    };
+    assert_eq!(
+        Err(SlashingError::UnslashableAttestationData),
+        simulator.submit_attester_slashing(AttesterSlashing {
+            attestation_1: attestation.clone(),
+            attestation_2: attestation.clone(),
+        })
+    );
+    assert_eq!(
+        Err(SlashingError::InvalidAttestingIndices),
+        simulator.submit_attester_slashing(AttesterSlashing {
+            attestation_1: IndexedAttestation {
+                attesting_indices: vec![1, 0],
+                ..attestation.clone()
+            },
+            attestation_2: attestation,
+        })
+    );
+
+    // Validator 0 proposes two blocks, and validators 1 and 2 make a double vote.
+    simulator.simulate_equivocations(&[0], &[2, 1]).unwrap();
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(epoch))
+        .unwrap();
+
+    let block = simulator.beacon_chain.get_head_block().unwrap().unwrap();
+    assert_eq!(1, block.proposer_slashings.len());
+    assert_eq!(1, block.attester_slashings.len());
+    let state = &simulator.beacon_chain.state;
+    for index in 0..3 {
+        let validator = &state.validators[index];
+        assert!(validator.slashed);
+        assert_eq!(ValidatorStatus::ActiveSlashed, validator.status(epoch));
+        assert_eq!(compute_activation_exit_epoch(epoch), validator.exit_epoch);
+        assert_eq!(
+            epoch + EPOCHS_PER_SLASHINGS_VECTOR,
+            validator.withdrawable_epoch
+        );
+        assert_eq!(
+            MAX_EFFECTIVE_BALANCE - MAX_EFFECTIVE_BALANCE / MIN_SLASHING_PENALTY_QUOTIENT,
+            state.balances[index]
+        );
+    }
+    assert!(!state.validators[3].slashed);
+    assert_eq!(
+        Err(SlashingError::UnslashableValidator { index: 0 }),
+        simulator.simulate_equivocations(&[0], &[])
+    );
+    assert_eq!(
+        Err(SlashingError::NoSlashableValidators),
+        simulator.simulate_equivocations(&[], &[1, 2])
+    );
+
+    let events = events.lock().unwrap();
+    let count = |f: fn(&Event) -> bool| events.iter().filter(|event| f(event)).count();
+    assert_eq!(
+        1,
+        count(|event| matches!(event, Event::ProposerSlashingProcessed { .. }))
+    );
+    assert_eq!(
+        1,
+        count(|event| matches!(event, Event::AttesterSlashingProcessed { .. }))
+    );
+    assert_eq!(
+        3,
+        count(|event| matches!(event, Event::ValidatorSlashed { .. }))
+    );
+
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(compute_activation_exit_epoch(
+            epoch,
+        )))
+        .unwrap();
+    assert_eq!(
+        ValidatorStatus::ExitedSlashed,
+        simulator.beacon_chain.state.validators[0].status(compute_activation_exit_epoch(epoch))
+    );
+}
+
 #[test]
 fn process_slot_without_beacon_block_proposal() {
     let mut simulator = Simulator::new();
@@ -562,34 +971,56 @@ fn process_slot_without_beacon_block_proposal() {
         if processed_slot % 2 == 0 {
             result = simulator.process_slots_without_beacon_block_proposal(processed_slot);
             if processed_slot == GENESIS_SLOT {
-                assert!(simulator.beacon_chain.blocks.is_empty());
-                assert!(simulator.beacon_chain.states.is_empty());
+                assert!(simulator.beacon_chain.block_summaries().is_empty());
+                assert!(simulator.beacon_chain.get_head_state().unwrap().is_none());
             } else {
                 assert_eq!(
                     processed_slot,
-                    simulator.beacon_chain.blocks.last().unwrap().slot + 1
+                    simulator
+                        .beacon_chain
+                        .get_head_block()
+                        .unwrap()
+                        .unwrap()
+                        .slot
+                        + 1
                 );
                 assert_eq!(
                     processed_slot,
-                    simulator.beacon_chain.states.last().unwrap().slot + 1
+                    simulator
+                        .beacon_chain
+                        .get_head_state()
+                        .unwrap()
+                        .unwrap()
+                        .slot
+                        + 1
                 );
             }
         } else {
             result = simulator.process_slots_happy(processed_slot);
             assert_eq!(
                 processed_slot,
-                simulator.beacon_chain.blocks.last().unwrap().slot
+                simulator
+                    .beacon_chain
+                    .get_head_block()
+                    .unwrap()
+                    .unwrap()
+                    .slot
             );
             assert_eq!(
                 processed_slot,
-                simulator.beacon_chain.states.last().unwrap().slot
+                simulator
+                    .beacon_chain
+                    .get_head_state()
+                    .unwrap()
+                    .unwrap()
+                    .slot
             );
             assert_eq!(
                 SHARD_NUM as usize * 2,
                 simulator
                     .beacon_chain
-                    .blocks
-                    .last()
+                    .get_head_block()
+                    .unwrap()
                     .unwrap()
                     .shard_headers
                     .len()
@@ -597,12 +1028,19 @@ fn process_slot_without_beacon_block_proposal() {
         };
         assert_eq!(
             (processed_slot as usize + 1) / 2,
-            simulator.beacon_chain.blocks.len()
-        );
-        assert_eq!(
-            (processed_slot as usize + 1) / 2,
-            simulator.beacon_chain.states.len()
+            simulator.beacon_chain.block_summaries().len()
         );
+        if let Some(block) = simulator.beacon_chain.get_head_block().unwrap() {
+            assert_eq!(
+                block.slot,
+                simulator
+                    .beacon_chain
+                    .get_head_state()
+                    .unwrap()
+                    .unwrap()
+                    .slot
+            );
+        }
         assert!(result.is_ok());
     }
 }
@@ -625,7 +1063,12 @@ fn recovery_from_epoch_without_beacon_block_proposal() {
             // The block proposed at the last slot before the catastrophy.
             assert_eq!(
                 catastrophy_start_slot - 1,
-                simulator.beacon_chain.blocks.last().unwrap().slot
+                simulator
+                    .beacon_chain
+                    .get_head_block()
+                    .unwrap()
+                    .unwrap()
+                    .slot
             );
         } else if compute_epoch_at_slot(processed_slot) == catastrophic_epoch + 1 {
             result = simulator.process_slots_happy(processed_slot);
@@ -646,8 +1089,8 @@ fn recovery_from_epoch_without_beacon_block_proposal() {
                     4 * SHARD_NUM as usize,
                     simulator
                         .beacon_chain
-                        .blocks
-                        .last()
+                        .get_head_block()
+                        .unwrap()
                         .unwrap()
                         .shard_headers
                         .len()
@@ -663,8 +1106,8 @@ fn recovery_from_epoch_without_beacon_block_proposal() {
                     4 * SHARD_NUM as usize,
                     simulator
                         .beacon_chain
-                        .blocks
-                        .last()
+                        .get_head_block()
+                        .unwrap()
                         .unwrap()
                         .shard_headers
                         .len()
@@ -678,8 +1121,8 @@ fn recovery_from_epoch_without_beacon_block_proposal() {
                     3 * SHARD_NUM as usize,
                     simulator
                         .beacon_chain
-                        .blocks
-                        .last()
+                        .get_head_block()
+                        .unwrap()
                         .unwrap()
                         .shard_headers
                         .len()
@@ -699,8 +1142,8 @@ fn recovery_from_epoch_without_beacon_block_proposal() {
                 SHARD_NUM as usize,
                 simulator
                     .beacon_chain
-                    .blocks
-                    .last()
+                    .get_head_block()
+                    .unwrap()
                     .unwrap()
                     .shard_headers
                     .len()
@@ -729,7 +1172,7 @@ fn recovery_from_epoch_without_beacon_block_proposal() {
     );
 
     // Invariants about pending shard headers.
-    for state in simulator.beacon_chain.states.iter() {
+    for state in simulator.beacon_chain.states().unwrap().iter() {
         for header in state.previous_epoch_pending_shard_headers.iter() {
             assert_eq!(
                 compute_epoch_at_slot(state.slot),
@@ -793,20 +1236,33 @@ fn process_slots_random() {
         {
             block_proposed_slots += 1;
         }
-        assert_eq!(block_proposed_slots, simulator.beacon_chain.blocks.len());
-        assert_eq!(block_proposed_slots, simulator.beacon_chain.states.len());
+        assert_eq!(
+            block_proposed_slots,
+            simulator.beacon_chain.block_summaries().len()
+        );
+        if let Some(block) = simulator.beacon_chain.get_head_block().unwrap() {
+            assert_eq!(
+                block.slot,
+                simulator
+                    .beacon_chain
+                    .get_head_state()
+                    .unwrap()
+                    .unwrap()
+                    .slot
+            );
+        }
 
         // Verify the hash chain.
         match block_proposed_slots {
             1 => assert_eq!(
                 GENESIS_PARENT_ROOT,
-                simulator.beacon_chain.blocks[0].parent_root
+                simulator.beacon_chain.blocks().unwrap()[0].parent_root
             ),
             s if s > 1 => assert_eq!(
-                simulator.beacon_chain.blocks[block_proposed_slots - 2]
+                simulator.beacon_chain.blocks().unwrap()[block_proposed_slots - 2]
                     .header()
                     .root(),
-                simulator.beacon_chain.blocks[block_proposed_slots - 1].parent_root
+                simulator.beacon_chain.blocks().unwrap()[block_proposed_slots - 1].parent_root
             ),
             _ => (),
         }
@@ -827,7 +1283,7 @@ fn process_slots_random() {
     );
 
     // Invariants about pending shard headers.
-    for state in simulator.beacon_chain.states.iter() {
+    for state in simulator.beacon_chain.states().unwrap().iter() {
         for header in state.previous_epoch_pending_shard_headers.iter() {
             assert_eq!(
                 compute_epoch_at_slot(state.slot),
@@ -880,7 +1336,7 @@ fn publish_bid_validation() {
         shard: 0,
         slot: end_slot + 1,
         commitment: DataCommitment::dummy_from_bytes(&String::from("Test bid").into_bytes()),
-        fee: 1,
+        fee: MIN_GASPRICE,
     };
     let result = simulator.publish_bid(good_bid.clone());
     assert!(result.is_ok());
@@ -907,7 +1363,7 @@ fn publish_bid_validation() {
         })
     );
 
-    let mut large_bid = good_bid;
+    let mut large_bid = good_bid.clone();
     large_bid.commitment.length = MAX_POINTS_PER_BLOCK + 1;
     let result = simulator.publish_bid(large_bid);
     assert_eq!(
@@ -916,4 +1372,913 @@ fn publish_bid_validation() {
             found: MAX_POINTS_PER_BLOCK + 1
         })
     );
+
+    let mut unknown_shard_bid = good_bid.clone();
+    unknown_shard_bid.shard = SHARD_NUM as Shard;
+    let result = simulator.publish_bid(unknown_shard_bid);
+    assert_eq!(
+        result,
+        Err(BidPublicationError::UnknownShard {
+            found: SHARD_NUM as Shard
+        })
+    );
+
+    let mut empty_bid = good_bid.clone();
+    empty_bid.commitment.length = 0;
+    let result = simulator.publish_bid(empty_bid);
+    assert_eq!(result, Err(BidPublicationError::EmptyData));
+
+    let data = vec![1; BYTES_PER_POINT * 2];
+    let mut data_bid = good_bid.clone();
+    data_bid.commitment = DataCommitment::dummy_from_bytes(&data);
+    let result = simulator.publish_bid_with_data(data_bid.clone(), &data[..BYTES_PER_POINT]);
+    assert_eq!(
+        result,
+        Err(BidPublicationError::DataLengthMismatch {
+            expect: 2,
+            found: 1
+        })
+    );
+    let mut other_data = data.clone();
+    other_data[0] = 2;
+    let result = simulator.publish_bid_with_data(data_bid.clone(), &other_data);
+    assert!(matches!(
+        result,
+        Err(BidPublicationError::InvalidCommitment { .. })
+    ));
+    let result = simulator.publish_bid_with_data(data_bid, &data);
+    assert!(result.is_ok());
+
+    // The gasprice is updated from the initial gasprice at the end of epoch 1.
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(2))
+        .unwrap();
+    let gasprice = simulator.beacon_chain.state.shard_gasprice;
+    assert!(gasprice > INIT_SHARD_GASPRICE);
+    let mut low_fee_bid = good_bid;
+    low_fee_bid.slot = simulator.slot;
+    low_fee_bid.fee = gasprice - 1;
+    let result = simulator.publish_bid(low_fee_bid);
+    assert_eq!(
+        result,
+        Err(BidPublicationError::TooLowFee {
+            gasprice,
+            found: gasprice - 1
+        })
+    );
+}
+
+#[test]
+fn resume_from_disk_store() {
+    let dir = std::env::temp_dir().join(format!("mousse_test_store_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let end_slot = compute_start_slot_at_epoch(3) + 5;
+    let slot_clock = SlotClock::starting_at(GENESIS_SLOT, 1_000, 500);
+    {
+        let mut simulator =
+            Simulator::with_store(Box::new(DiskStore::open(&dir).unwrap())).unwrap();
+        simulator.set_slot_clock(slot_clock).unwrap();
+        let result = simulator.process_slots_happy(end_slot);
+        assert!(result.is_ok());
+    }
+    // An unrelated file in the directory, and an incomplete write of a block.
+    std::fs::write(dir.join("notes.txt"), "not a part of the store").unwrap();
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(dir.join("blocks.jsonl"))
+        .unwrap()
+        .write_all(b"{\"slot\":")
+        .unwrap();
+
+    // Restart the simulator with the same directory.
+    let mut resumed = Simulator::with_store(Box::new(DiskStore::open(&dir).unwrap())).unwrap();
+    // The genesis time is kept.
+    assert_eq!(slot_clock, resumed.slot_clock);
+    assert_eq!(Some(slot_clock), resumed.stored_slot_clock());
+    let mut expected = Simulator::new();
+    let result = expected.process_slots_happy(end_slot);
+    assert!(result.is_ok());
+    assert_eq!(expected.slot, resumed.slot);
+    assert_eq!(expected.beacon_chain.slot, resumed.beacon_chain.slot);
+    assert_eq!(
+        expected.beacon_chain.finalized_checkpoint,
+        resumed.beacon_chain.finalized_checkpoint
+    );
+    assert_eq!(
+        expected.beacon_chain.checkpoints,
+        resumed.beacon_chain.checkpoints
+    );
+    assert_eq!(
+        expected.beacon_chain.block_summaries().len(),
+        resumed.beacon_chain.block_summaries().len()
+    );
+    // The block indices are rebuilt.
+    let first_block_root = expected.beacon_chain.blocks().unwrap()[0].header().root();
+    assert_eq!(
+        GENESIS_SLOT,
+        resumed
+            .beacon_chain
+            .get_block_by_root(&first_block_root)
+            .unwrap()
+            .unwrap()
+            .slot
+    );
+    // The blocks older than the cached ones are read from the file.
+    let block_roots = |simulator: &Simulator| -> Vec<Root> {
+        simulator
+            .beacon_chain
+            .blocks()
+            .unwrap()
+            .iter()
+            .map(|block| block.header().root())
+            .collect()
+    };
+    assert_eq!(block_roots(&expected), block_roots(&resumed));
+
+    // The resumed simulator builds the same chain.
+    let next_end_slot = end_slot + SLOTS_PER_EPOCH;
+    let result = resumed.process_slots_happy(next_end_slot);
+    assert!(result.is_ok());
+    let result = expected.process_slots_happy(next_end_slot);
+    assert!(result.is_ok());
+    assert_eq!(
+        expected
+            .beacon_chain
+            .get_head_block()
+            .unwrap()
+            .unwrap()
+            .header()
+            .root(),
+        resumed
+            .beacon_chain
+            .get_head_block()
+            .unwrap()
+            .unwrap()
+            .header()
+            .root()
+    );
+    // The blocks after the incomplete write are loaded.
+    let reopened_store = DiskStore::open(&dir).unwrap();
+    assert_eq!(
+        resumed.beacon_chain.block_summaries().len(),
+        reopened_store.block_summaries().len()
+    );
+    assert_eq!(
+        resumed
+            .beacon_chain
+            .get_head_block()
+            .unwrap()
+            .unwrap()
+            .header()
+            .root(),
+        reopened_store.block_summaries().last().unwrap().root
+    );
+
+    // Reset deletes the stored chain.
+    let result = resumed.reset();
+    assert!(result.is_ok());
+    assert!(resumed.beacon_chain.block_summaries().is_empty());
+    let reopened = Simulator::with_store(Box::new(DiskStore::open(&dir).unwrap())).unwrap();
+    assert_eq!(GENESIS_SLOT, reopened.slot);
+    assert!(dir.join("notes.txt").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn restart_keeps_justification_and_finality() {
+    let dir = std::env::temp_dir().join(format!("mousse_test_restart_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut expected = Simulator::new();
+    let mut restarted = Simulator::with_store(Box::new(DiskStore::open(&dir).unwrap())).unwrap();
+    // Restart at the end of an epoch, after the slots without beacon block, and in the middle of an epoch.
+    let end_slots = [
+        compute_start_slot_at_epoch(2) - 1,
+        compute_start_slot_at_epoch(3) + 3,
+        compute_start_slot_at_epoch(5) + 10,
+    ];
+    for (i, end_slot) in end_slots.iter().enumerate() {
+        if i == 1 {
+            assert!(expected
+                .process_slots_without_beacon_block_proposal(*end_slot)
+                .is_ok());
+            assert!(restarted
+                .process_slots_without_beacon_block_proposal(*end_slot)
+                .is_ok());
+        } else {
+            assert!(expected.process_slots_happy(*end_slot).is_ok());
+            assert!(restarted.process_slots_happy(*end_slot).is_ok());
+        }
+        drop(restarted);
+        restarted = Simulator::with_store(Box::new(DiskStore::open(&dir).unwrap())).unwrap();
+        assert_eq!(end_slot + 1, restarted.slot);
+        assert_eq!(expected.beacon_chain.slot, restarted.beacon_chain.slot);
+        assert_eq!(
+            expected.beacon_chain.justified_checkpoint,
+            restarted.beacon_chain.justified_checkpoint
+        );
+        assert_eq!(
+            expected.beacon_chain.finalized_checkpoint,
+            restarted.beacon_chain.finalized_checkpoint
+        );
+        assert_eq!(
+            expected.beacon_chain.state.root(),
+            restarted.beacon_chain.state.root()
+        );
+    }
+    assert!(restarted.beacon_chain.finalized_checkpoint.epoch > GENESIS_EPOCH);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn prune_finalized_states() {
+    let mut simulator = Simulator::new();
+    simulator.beacon_chain.state_pruning = true;
+    let end_slot = compute_start_slot_at_epoch(4);
+    let result = simulator.process_slots_happy(end_slot);
+    assert!(result.is_ok());
+
+    let finalized_slot =
+        compute_start_slot_at_epoch(simulator.beacon_chain.finalized_checkpoint.epoch);
+    for block in simulator.beacon_chain.blocks().unwrap() {
+        let state = simulator.beacon_chain.get_state(block.slot).unwrap();
+        if block.slot < finalized_slot {
+            assert!(state.is_none());
+        } else {
+            assert_eq!(block.state_root, state.unwrap().root());
+        }
+    }
+}
+
+#[test]
+fn get_blocks_by_slot_and_root() {
+    let mut simulator = Simulator::new();
+    simulator.process_slots_happy(2).unwrap();
+    // No block at Slot 3.
+    simulator
+        .process_slots_without_beacon_block_proposal(3)
+        .unwrap();
+    simulator.process_slots_happy(4).unwrap();
+
+    let beacon_chain = &simulator.beacon_chain;
+    for block in beacon_chain.blocks().unwrap() {
+        let found = beacon_chain.get_block_by_slot(block.slot).unwrap().unwrap();
+        assert_eq!(block.header().root(), found.header().root());
+        let found = beacon_chain
+            .get_block_by_root(&block.header().root())
+            .unwrap();
+        assert_eq!(block.slot, found.slot);
+        let found = beacon_chain
+            .get_block_by_state_root(&block.state_root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(block.slot, found.slot);
+    }
+    assert!(beacon_chain.get_block_by_slot(3).unwrap().is_none());
+    assert!(beacon_chain.get_block_by_slot(5).unwrap().is_none());
+    assert!(beacon_chain
+        .get_block_by_root(&H256::zero())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn get_shard_header_status() {
+    let mut simulator = Simulator::new();
+    let result = simulator.process_slots_happy(0);
+    assert!(result.is_ok());
+    let result = simulator.process_slots_without_shard_header_confirmation(1);
+    assert!(result.is_ok());
+
+    let status = simulator.get_shard_header_status(0, 0).unwrap().unwrap();
+    assert!(status.header.is_some());
+    assert_eq!(0, status.included_block.unwrap().slot);
+    assert_eq!(Some(true), status.confirmed);
+    assert!(!status.finalized);
+    let status = simulator.get_shard_header_status(0, 1).unwrap().unwrap();
+    assert_eq!(1, status.included_block.unwrap().slot);
+    assert_eq!(Some(false), status.confirmed);
+    // Not processed yet.
+    assert!(simulator.get_shard_header_status(0, 2).unwrap().is_none());
+    // No such shard.
+    assert!(simulator
+        .get_shard_header_status(SHARD_NUM as Shard, 0)
+        .unwrap()
+        .is_none());
+
+    // The shard headers are confirmed in the later slot and then finalized.
+    let end_slot = compute_start_slot_at_epoch(4);
+    let result = simulator.process_slots_happy(end_slot);
+    assert!(result.is_ok());
+    let status = simulator.get_shard_header_status(0, 1).unwrap().unwrap();
+    assert_eq!(Some(true), status.confirmed);
+    assert!(status.finalized);
+
+    // No shard blob is proposed.
+    let result = simulator.process_slots_without_shard_blob_proposal(end_slot + 1);
+    assert!(result.is_ok());
+    let status = simulator
+        .get_shard_header_status(0, end_slot + 1)
+        .unwrap()
+        .unwrap();
+    assert!(status.header.is_none());
+    assert!(status.included_block.is_none());
+    assert_eq!(Some(false), status.confirmed);
+}
+
+#[test]
+fn get_shard_header_status_with_pruned_states() {
+    let mut simulator = Simulator::new();
+    simulator.beacon_chain.state_pruning = true;
+    let result = simulator.process_slots_happy(compute_start_slot_at_epoch(4));
+    assert!(result.is_ok());
+    // The confirmation is unknown since the state is already pruned.
+    let status = simulator.get_shard_header_status(0, 1).unwrap().unwrap();
+    assert!(status.included_block.is_some());
+    assert_eq!(None, status.confirmed);
+    assert!(status.finalized);
+}
+
+#[test]
+fn prove_confirmed_commitments() {
+    let mut simulator = Simulator::new();
+    let end_slot = compute_start_slot_at_epoch(2) + 3;
+    let result = simulator.process_slots_happy(end_slot);
+    assert!(result.is_ok());
+    let result = simulator.process_slots_without_shard_header_confirmation(end_slot + 1);
+    assert!(result.is_ok());
+
+    let beacon_chain = &simulator.beacon_chain;
+    for slot in [1, compute_start_slot_at_epoch(1) + 1, end_slot].iter() {
+        let proof = beacon_chain
+            .get_commitment_proof(1, *slot)
+            .unwrap()
+            .unwrap();
+        assert!(proof.verify());
+        let block = beacon_chain
+            .get_block_by_slot(proof.block_slot)
+            .unwrap()
+            .unwrap();
+        assert_eq!(block.header().root(), proof.block_root);
+        let header = simulator.shards[1].proposed_headers[*slot as usize]
+            .clone()
+            .unwrap();
+        assert_eq!(header.message.commitment, proof.commitment);
+        // The commitment in the grandparent epoch is in the confirmed commitments.
+        assert_eq!(*slot == 1, proof.pending_header.is_none());
+
+        // A tampered proof is invalid.
+        let mut tampered = proof.clone();
+        tampered.slot += 1;
+        assert!(!tampered.verify());
+        let mut tampered = proof.clone();
+        tampered.state_proof.branch[0] = H256::zero();
+        assert!(!tampered.verify());
+    }
+    // Not confirmed yet.
+    assert!(beacon_chain
+        .get_commitment_proof(1, end_slot + 1)
+        .unwrap()
+        .is_none());
+    // Not processed yet.
+    assert!(beacon_chain
+        .get_commitment_proof(1, end_slot + 2)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn observe_events() {
+    let mut simulator = Simulator::new();
+    let events: Arc<Mutex<Vec<Event>>> = Arc::new(Mutex::new(Vec::new()));
+    let observed_events = events.clone();
+    simulator.subscribe(Box::new(move |event: &Event| {
+        observed_events.lock().unwrap().push(event.clone())
+    }));
+
+    // Publish bids only in shard 0.
+    let bid_end_slot = SLOTS_PER_EPOCH;
+    for slot in 0..bid_end_slot {
+        let bid = Bid {
+            shard: 0,
+            slot,
+            commitment: DataCommitment::dummy_from_bytes(
+                &format!("Bid: Slot {}", slot).into_bytes(),
+            ),
+            fee: MIN_GASPRICE,
+        };
+        let result = simulator.publish_bid(bid);
+        assert!(result.is_ok());
+    }
+    let end_slot = compute_start_slot_at_epoch(3);
+    let result = simulator.process_slots_happy(end_slot);
+    assert!(result.is_ok());
+
+    let events = events.lock().unwrap();
+    let slot_num = end_slot as usize + 1;
+    let count = |f: fn(&Event) -> bool| events.iter().filter(|event| f(event)).count();
+    assert_eq!(
+        slot_num,
+        count(|event| matches!(event, Event::SlotProcessed { .. }))
+    );
+    assert_eq!(
+        slot_num * SHARD_NUM as usize,
+        count(|event| matches!(event, Event::ShardBlobProposed { .. }))
+    );
+    assert_eq!(
+        slot_num * SHARD_NUM as usize,
+        count(|event| matches!(event, Event::ShardHeaderPublished { .. }))
+    );
+    assert_eq!(
+        bid_end_slot as usize,
+        count(|event| matches!(event, Event::BidSelected { .. }))
+    );
+    assert_eq!(
+        slot_num,
+        count(|event| matches!(event, Event::BlockAppended { .. }))
+    );
+    assert_eq!(
+        slot_num * SHARD_NUM as usize,
+        count(|event| matches!(event, Event::ShardHeaderConfirmed { .. }))
+    );
+    assert_eq!(
+        compute_epoch_at_slot(end_slot) as usize,
+        count(|event| matches!(event, Event::EpochProcessed { .. }))
+    );
+
+    // The events of a slot are followed by `SlotProcessed`.
+    assert!(matches!(
+        events.last().unwrap(),
+        Event::SlotProcessed { slot } if *slot == end_slot
+    ));
+    for event in events.iter() {
+        match event {
+            Event::BlockAppended { root, block } => {
+                assert_eq!(*root, block.header().root());
+                assert_eq!(
+                    *root,
+                    simulator.beacon_chain.blocks().unwrap()[block.slot as usize]
+                        .header()
+                        .root()
+                );
+            }
+            Event::BidSelected { bid } => assert_eq!(0, bid.shard),
+            _ => (),
+        }
+    }
+
+    // The checkpoints of epoch 0 and 1 are finalized in order.
+    let finalized_epochs: Vec<Epoch> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::CheckpointFinalized { checkpoint } => Some(checkpoint.epoch),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(vec![0, 1], finalized_epochs);
+}
+
+#[test]
+fn slot_clock() {
+    // The slot 8 of 250 milliseconds starts at 10.5 seconds, so the genesis is at 8.5 seconds,
+    // which is rounded down to 8 seconds.
+    let clock = SlotClock::starting_at(8, 10_500, 250);
+    assert_eq!(8, clock.genesis_time());
+    assert_eq!(8_000, clock.genesis_time_ms);
+    assert_eq!(None, clock.slot_at(7_999));
+    assert_eq!(Some(0), clock.slot_at(8_000));
+    assert_eq!(Some(10), clock.slot_at(10_500));
+    assert_eq!(Some(10), clock.slot_at(10_749));
+    assert_eq!(Some(11), clock.slot_at(10_750));
+    assert_eq!(10_750, clock.slot_start_time_ms(11));
+
+    // The genesis slot is processed at the genesis.
+    let mut simulator = Simulator::new();
+    simulator.slot_clock = SlotClock::starting_at(GENESIS_SLOT, 1_000, 250);
+    simulator.process_slots_happy(0).unwrap();
+    simulator.reset().unwrap();
+    assert_eq!(1, simulator.slot_clock.genesis_time());
+
+    // The observers are notified only if the clock is changed.
+    let events: Arc<Mutex<Vec<Event>>> = Arc::new(Mutex::new(Vec::new()));
+    let observed_events = events.clone();
+    simulator.subscribe(Box::new(move |event: &Event| {
+        observed_events.lock().unwrap().push(event.clone())
+    }));
+    simulator.set_slot_clock(simulator.slot_clock).unwrap();
+    assert!(events.lock().unwrap().is_empty());
+    let slot_clock = SlotClock::starting_at(GENESIS_SLOT, 2_000, 500);
+    simulator.set_slot_clock(slot_clock).unwrap();
+    assert_eq!(slot_clock, simulator.slot_clock);
+    assert!(matches!(
+        events.lock().unwrap().as_slice(),
+        [Event::SlotClockChanged { current, .. }] if *current == slot_clock
+    ));
+}
+
+#[test]
+fn virtual_time() {
+    let mut simulator = Simulator::new();
+    assert_eq!(None, simulator.virtual_time_ms);
+
+    // Switching from the wall-clock time to any time is allowed.
+    simulator.set_time(10_000).unwrap();
+    assert_eq!(10_000, simulator.now_ms());
+    simulator.slot_clock = SlotClock::starting_at(GENESIS_SLOT, simulator.now_ms(), 250);
+    simulator.advance_time(1_250);
+    assert_eq!(11_250, simulator.now_ms());
+    assert_eq!(Some(5), simulator.slot_clock.slot_at(simulator.now_ms()));
+
+    // The virtual time can not go backwards.
+    assert_eq!(
+        Err(TimeError::PastTime {
+            now_ms: 11_250,
+            found: 11_000
+        }),
+        simulator.set_time(11_000)
+    );
+    simulator.set_time(11_250).unwrap();
+
+    // The virtual time is kept after the reset, and the observers are notified of the reset.
+    let events: Arc<Mutex<Vec<Event>>> = Arc::new(Mutex::new(Vec::new()));
+    let observed_events = events.clone();
+    simulator.subscribe(Box::new(move |event: &Event| {
+        observed_events.lock().unwrap().push(event.clone())
+    }));
+    simulator.reset().unwrap();
+    assert_eq!(11_250, simulator.now_ms());
+    assert!(matches!(
+        events.lock().unwrap().as_slice(),
+        [Event::SimulatorReset]
+    ));
+}
+
+#[test]
+fn failure_model() {
+    let model = FailureModel {
+        shards: vec![(
+            1,
+            ShardFailureRates {
+                blob_proposal: 1.0,
+                data_inclusion: 0.0,
+            },
+        )]
+        .into_iter()
+        .collect(),
+        outages: vec![OutageModel {
+            failure: Failure::NoChainFinality,
+            start_rate: 1.0,
+            min_epochs: 2,
+            max_epochs: 2,
+        }],
+        incidents: vec![Incident {
+            failure: Failure::NoShardDataInclusion { shard: Some(2) },
+            start_slot: 3,
+            end_slot: 4,
+        }],
+        ..FailureModel::default()
+    };
+    assert_eq!(Ok(()), model.validate());
+
+    // The outage starts at the start of epoch 0 and lasts for 2 epochs.
+    let mut rng = rand::thread_rng();
+    let mut ongoing_outages = Vec::new();
+    let params = model.sample(GENESIS_SLOT, &mut ongoing_outages, &mut rng);
+    assert_eq!(vec![Some(1)], ongoing_outages);
+    assert!(params.beacon_params.beacon_block_proposed);
+    assert!(!params.beacon_params.beacon_chain_finalized);
+    assert!(params.shard_params[0].blob_proposed);
+    assert!(!params.shard_params[1].blob_proposed);
+    assert!(params.shard_params[2].data_included);
+    model.sample(
+        compute_start_slot_at_epoch(2),
+        &mut ongoing_outages,
+        &mut rng,
+    );
+    assert_eq!(vec![Some(3)], ongoing_outages);
+
+    // The incident is scheduled in slots 3 and 4.
+    let mut simulator = Simulator::new();
+    simulator
+        .process_slots_with_failure_model(5, &model)
+        .unwrap();
+    for (slot, params) in simulator.params.iter().enumerate() {
+        assert!(!params.beacon_params.beacon_chain_finalized);
+        assert_eq!(
+            !(3..=4).contains(&slot),
+            params.shard_params[2].data_included
+        );
+    }
+
+    // The default model has no failure.
+    let params = FailureModel::default().sample(6, &mut Vec::new(), &mut rng);
+    assert!(params.beacon_params.beacon_chain_finalized);
+    assert!(params
+        .shard_params
+        .iter()
+        .all(|params| params.data_included));
+
+    // A slot always fails by a preset failure case.
+    let preset_model = FailureModel {
+        preset: 1.0,
+        ..FailureModel::default()
+    };
+    for slot in 0..10 {
+        let params = preset_model.sample(slot, &mut Vec::new(), &mut rng);
+        let beacon_params = &params.beacon_params;
+        assert!(
+            !beacon_params.beacon_block_proposed
+                || !beacon_params.beacon_chain_finalized
+                || !beacon_params.shard_headers_included
+                || !beacon_params.shard_headers_confirmed
+                || params
+                    .shard_params
+                    .iter()
+                    .any(|params| !params.data_included)
+        );
+    }
+
+    let invalid_model = FailureModel {
+        beacon: BeaconFailureRates {
+            block_proposal: 1.5,
+            ..BeaconFailureRates::default()
+        },
+        ..FailureModel::default()
+    };
+    assert_eq!(
+        Err(FailureModelError::InvalidRate { found: 1.5 }),
+        invalid_model.validate()
+    );
+    let invalid_model = FailureModel {
+        incidents: vec![Incident {
+            failure: Failure::NoShardBlobProposal {
+                shard: Some(SHARD_NUM as ShardId),
+            },
+            start_slot: 0,
+            end_slot: 0,
+        }],
+        ..FailureModel::default()
+    };
+    assert_eq!(
+        Err(FailureModelError::UnknownShard {
+            found: SHARD_NUM as ShardId
+        }),
+        invalid_model.validate()
+    );
+    let invalid_model = FailureModel {
+        outages: vec![OutageModel {
+            failure: Failure::NoBeaconBlockProposal,
+            start_rate: 0.1,
+            min_epochs: 10,
+            max_epochs: 3,
+        }],
+        ..FailureModel::default()
+    };
+    assert_eq!(
+        Err(FailureModelError::InvalidOutageDuration {
+            min_epochs: 10,
+            max_epochs: 3
+        }),
+        invalid_model.validate()
+    );
+}
+
+#[test]
+fn propagation_model() {
+    let mut simulator = Simulator::new();
+    let events: Arc<Mutex<Vec<Event>>> = Arc::new(Mutex::new(Vec::new()));
+    let observed_events = events.clone();
+    simulator.subscribe(Box::new(move |event: &Event| {
+        observed_events.lock().unwrap().push(event.clone())
+    }));
+    simulator.set_time(0).unwrap();
+    simulator.slot_clock = SlotClock::starting_at(GENESIS_SLOT, 0, 1_000);
+
+    let invalid_model = PropagationModel {
+        global_subnet: LinkModel {
+            latency: Latency::Uniform {
+                min_ms: 200,
+                max_ms: 100,
+            },
+            drop_rate: 0.0,
+        },
+        ..PropagationModel::default()
+    };
+    assert_eq!(
+        Err(PropagationModelError::InvalidLatency {
+            min_ms: 200,
+            max_ms: 100
+        }),
+        simulator.set_propagation_model(invalid_model)
+    );
+    let mut shard_subnets = std::collections::BTreeMap::new();
+    shard_subnets.insert(
+        1,
+        LinkModel {
+            latency: Latency::default(),
+            drop_rate: 1.0,
+        },
+    );
+    simulator
+        .set_propagation_model(PropagationModel {
+            // The headers arrive in the next slot.
+            global_subnet: LinkModel {
+                latency: Latency::Constant { ms: 1_500 },
+                drop_rate: 0.0,
+            },
+            shard_subnet: LinkModel {
+                latency: Latency::Constant { ms: 300 },
+                drop_rate: 0.0,
+            },
+            shard_subnets,
+        })
+        .unwrap();
+
+    let bid = |shard: Shard, slot: Slot| Bid {
+        shard,
+        slot,
+        commitment: DataCommitment::dummy_from_bytes(
+            &format!("Slot {}, Shard {}", slot, shard).into_bytes(),
+        ),
+        fee: MIN_GASPRICE,
+    };
+    // The bids arrive at 300 ms, so the bid for slot 0 misses the proposer.
+    simulator.publish_bid(bid(0, 0)).unwrap();
+    simulator.publish_bid(bid(0, 1)).unwrap();
+    // The bid in shard 1 is dropped.
+    simulator.publish_bid(bid(1, 1)).unwrap();
+    simulator.process_slots_happy(0).unwrap();
+    assert!(simulator
+        .beacon_chain
+        .get_block_by_slot(0)
+        .unwrap()
+        .unwrap()
+        .shard_headers
+        .is_empty());
+
+    simulator.advance_time(1_000);
+    simulator.process_slots_happy(1).unwrap();
+    let shard_headers = &simulator
+        .beacon_chain
+        .get_block_by_slot(1)
+        .unwrap()
+        .unwrap()
+        .shard_headers;
+    assert_eq!(SHARD_NUM as usize, shard_headers.len());
+    assert!(shard_headers.iter().all(|header| header.message.slot == 0));
+
+    let events = events.lock().unwrap();
+    let missed_bids: Vec<Bid> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::BidMissed { bid } => Some(bid.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(vec![bid(1, 1), bid(0, 0)], missed_bids);
+    let selected_bids: Vec<Bid> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::BidSelected { bid } => Some(bid.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(vec![bid(0, 1)], selected_bids);
+}
+
+#[test]
+fn bid_deadline() {
+    let mut simulator = Simulator::new();
+    simulator.set_time(0).unwrap();
+    simulator.slot_clock = SlotClock::starting_at(GENESIS_SLOT, 0, 1_000);
+    simulator.process_slots_happy(0).unwrap();
+    let bid = |slot: Slot| Bid {
+        shard: 0,
+        slot,
+        commitment: DataCommitment::dummy_from_bytes(&format!("Slot {}", slot).into_bytes()),
+        fee: MIN_GASPRICE,
+    };
+
+    // The bid window never closes by default.
+    assert_eq!(None, simulator.bid_deadline_time_ms(1));
+    assert_eq!(Some(SlotPhase::BidWindow), simulator.slot_phase());
+
+    // The bid window for slot 1 is in slot 0 and closes at 800 ms.
+    simulator.bid_deadline_ms = Some(800);
+    assert_eq!(Some(800), simulator.bid_deadline_time_ms(1));
+    simulator.advance_time(799);
+    assert_eq!(Some(SlotPhase::BidWindow), simulator.slot_phase());
+    assert_eq!(Ok(()), simulator.publish_bid(bid(1)));
+    simulator.advance_time(1);
+    assert_eq!(Some(SlotPhase::BidsClosed), simulator.slot_phase());
+    assert_eq!(
+        Err(BidPublicationError::TooLate {
+            slot: 1,
+            deadline_ms: 800,
+            now_ms: 800
+        }),
+        simulator.publish_bid(bid(1))
+    );
+    // The bids for the later slots are accepted.
+    assert_eq!(Ok(()), simulator.publish_bid(bid(2)));
+
+    // The bid window for slot 2 opens when slot 1 starts.
+    simulator.advance_time(200);
+    assert_eq!(Some(SlotPhase::BidWindow), simulator.slot_phase());
+}
+
+#[test]
+fn data_submission() {
+    let mut simulator = Simulator::new();
+    simulator.process_slots_happy(0).unwrap();
+    let data: Vec<u8> = (0..MAX_BYTES_PER_PIECE * 5 / 2)
+        .map(|i| (i % 251) as u8)
+        .collect();
+
+    // 3 pieces in shards 1 and 2 at slots 1 and 2.
+    let placement = Placement::consecutive(vec![1, 2], 1, data.len(), MIN_GASPRICE);
+    assert_eq!(vec![1, 2], placement.slots);
+    let manifest = simulator.submit_data(&data, &placement).unwrap();
+    assert_eq!(data.len() as u64, manifest.length);
+    let positions: Vec<(Shard, Slot)> = manifest
+        .pieces
+        .iter()
+        .map(|piece| (piece.shard, piece.slot))
+        .collect();
+    assert_eq!(vec![(1, 1), (2, 1), (1, 2)], positions);
+    assert_eq!(
+        MAX_POINTS_PER_BLOCK / 2,
+        manifest.pieces[2].commitment.length
+    );
+
+    // The pieces are not confirmed yet.
+    assert_eq!(
+        Err(DataReassemblyError::NotConfirmed {
+            index: 0,
+            shard: 1,
+            slot: 1
+        }),
+        simulator.reassemble_data(&manifest)
+    );
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(2))
+        .unwrap();
+    assert_eq!(Ok(data.clone()), simulator.reassemble_data(&manifest));
+    // The pieces are removed once their slots are finalized.
+    simulator
+        .process_slots_happy(compute_start_slot_at_epoch(4))
+        .unwrap();
+    assert!(simulator.beacon_chain.finalized_checkpoint.epoch >= 2);
+    assert_eq!(
+        Err(DataReassemblyError::UnknownPiece { index: 0 }),
+        simulator.reassemble_data(&manifest)
+    );
+
+    // Invalid submissions.
+    let slot = simulator.slot;
+    assert_eq!(
+        Err(DataSubmissionError::EmptyData),
+        simulator.submit_data(&[], &placement)
+    );
+    let no_shards = Placement::consecutive(vec![], slot, data.len(), MIN_GASPRICE);
+    assert_eq!(
+        Err(DataSubmissionError::NoShards),
+        simulator.submit_data(&data, &no_shards)
+    );
+    let duplicate_shards = Placement::consecutive(vec![1, 1], slot, data.len(), MIN_GASPRICE);
+    assert_eq!(
+        Err(DataSubmissionError::DuplicateShard { found: 1 }),
+        simulator.submit_data(&data, &duplicate_shards)
+    );
+    let too_few_slots = Placement {
+        shards: vec![1, 2],
+        slots: vec![slot],
+        fee: MIN_GASPRICE,
+    };
+    assert_eq!(
+        Err(DataSubmissionError::TooManyPieces {
+            pieces: 3,
+            capacity: 2
+        }),
+        simulator.submit_data(&data, &too_few_slots)
+    );
+    assert_eq!(
+        Err(DataSubmissionError::Bid {
+            index: 0,
+            error: BidPublicationError::PastSlot {
+                next: slot,
+                found: 1
+            }
+        }),
+        simulator.submit_data(&data, &placement)
+    );
+    let low_fee = Placement::consecutive(vec![1, 2], slot, data.len(), 0);
+    assert!(matches!(
+        simulator.submit_data(&data, &low_fee),
+        Err(DataSubmissionError::Bid {
+            index: 0,
+            error: BidPublicationError::TooLowFee { found: 0, .. }
+        })
+    ));
 }